@@ -1,10 +1,25 @@
 use parser;
 use parser::{Token, TokenType};
+use options::{Options, Width, Overflow, Division};
+use value::RegType;
 use std::iter;
 use std::io::Cursor;
+use enum_primitive::FromPrimitive;
 
 use byteorder::{BigEndian, WriteBytesExt, ReadBytesExt};
 
+/// Flag in the header that says the register names are present
+const FLAG_REGISTER_NAMES: u32 = 1;
+
+/// Flag in the header that says the register types are present
+const FLAG_REGISTER_TYPES: u32 = 2;
+
+/// Flag in the header that says where each line comes from (for runtime errors)
+const FLAG_LINE_ORIGINS: u32 = 4;
+
+/// Register width in the header for big registers
+const WIDTH_BIG: u8 = 255;
+
 // Bytecode management for Assembunny-plus
 // Bytecode binary files are in '.asmbb'
 
@@ -13,13 +28,31 @@ use byteorder::{BigEndian, WriteBytesExt, ReadBytesExt};
 // An ASMBP Bytecode file contains two segments,
 // first segment represents file metadata (amount of registers to allocate, etc.)
 // second segment represents tokens.
-// The first segment is 32 bytes long. Contents are follows (each '-' represents one byte):
+// The first segment starts with a 32 byte long header. Contents are follows (each '-' represents one byte):
+//
+// |----:----:----:-:-:-:-----------------|
+//   |     |    |   | | |        |
+//   |     |    |   | | |  [Reserved for future use]
+//   |     |    |   | | [Division: 0 truncate, 1 floor]
+//   |     |    |   | [Overflow policy: 0 wrap, 1 trap, 2 saturate]
+//   |     |    |  [Register width in bits: 32, 64 or 128 (0 means 32, 255 means big)]
+//   |     |  [Flags]
+//   |  [Memory buffer count]
+// [Register count]
+//
+// The register width, the overflow policy and the division are the ones given when converting (`--width`, `--overflow` and `--division`),
+// and the bytecode runner uses them instead of its own options; giving a different one on the command line when running the file is an error.
 //
-// |----:----------------------------|
-//   |                     |
-// [Register count]  [Reserved for future use]
+// The header is followed by the size of each memory buffer (declared with MEM), each a u32, in the order of the buffers' indices.
+// If bit 0 of the flags is set, the buffer sizes are followed by the name of each register (for the register dump of ASSERT),
+// each a u8 byte count followed by that many bytes of UTF-8, in the order of the registers' indices.
+// Files without the names get "r0", "r1", etc. instead.
+// If bit 1 of the flags is set, the names are followed by the type of each register (see value::RegType), each a u8, in the order of the registers' indices.
+// Files without the types get registers without a declared type (0).
+// If bit 2 of the flags is set, the types are followed by a u32 count of lines and, for each line of tokens, where it comes from in the source (such as "Line 3"),
+// each a u16 byte count followed by that many bytes of UTF-8. Runtime errors of files without them name the line by its index instead.
 //
-// The second segment consists of token representation Blobs, each 5 bytes long.
+// The second segment consists of token representation Blobs, each 5 bytes long except for LITERAL ones.
 // A token representation Blob consists of the following (each '-' represents one bit):
 //
 // |--------:--------:--------:--------:--------|
@@ -27,49 +60,285 @@ use byteorder::{BigEndian, WriteBytesExt, ReadBytesExt};
 //   |                        |
 //  [Type in u8]        [Data in i32]
 //
+// The data of a LITERAL token is as wide as the registers instead: 4, 8 or 16 bytes, in two's complement.
+// With big registers, a LITERAL blob has no data and is followed by the literal's length instead, so small literals stay small:
+//
+// |--------|--------|--------: ...
+//   ^        ^        \______________/
+//   |        |               |
+//  [Type]  [Byte count   [Value in two's complement,
+//           in u8]        big endian, 1 to 16 bytes]
+//
+// The data of a KEYWORD token is the keyword's index in parser::KEYWORD_INDEX (TGL, for example, is 12).
+// TGL only changes that index while the program runs, so self-modifying programs need no extra encoding.
+// Jump offsets are ordinary LITERAL or REGISTER tokens, so computed jumps (such as `jnz 1 c`) need no extra encoding either.
+// The data of a BUFFER token is the index of a memory buffer, whose size can be found in the first segment.
+//
+// A STRING token (a string literal, such as the parameter of OUTS) is followed by the string's UTF-8 bytes,
+// and the data of its blob is the amount of those bytes instead of the string's index:
+//
+// |--------:--------:--------:--------:--------|--------:--------: ...
+//   ^           \_______|________|_______/       \____________________/
+//   |                        |                             |
+//  [Type in u8]     [Byte count in i32]          [String in UTF-8]
+//
 // Since every line of ASMB+ starts with a KEYWORD token, the tokens provided in the ASMBP Bytecode file are split whenever a new KEYWORD token is reached while iterating.
 
 // Converts a given ASMBP program to bytecode.
 // The program (parameter of this fn) should be a Slice of Strings containing single ASMBP statements.
-pub fn to_bytecode(asmbp: &Vec<&str>) -> Result<Vec<u8>, String> {
+// `origins` names the lines in error messages (see parser::line_origin).
+pub fn to_bytecode(asmbp: &Vec<&str>, origins: &[String], options: &Options) -> Result<Vec<u8>, String> {
     let mut segment1: Vec<u8> = Vec::new();
     let mut segment2: Vec<u8> = Vec::new();
-    let mut regs: Vec<String> = Vec::new();
+    let mut symbols = parser::Symbols::new();
+    symbols.width = options.width;
     
-    for line in asmbp {
-        if let Some(tokens) = try!(parser::to_tokens(line, &mut regs)) {
-            for token in tokens {
-                segment2.append(&mut token.to_bytearray());
+    for tokens in try!(parser::to_program(asmbp, origins, &mut symbols)) {
+        for token in tokens {
+            if token.type_ == TokenType::LITERAL && options.width == Width::Big {
+                let bytes = literal_bytes(token.val);
+                segment2.push(TokenType::LITERAL as u8);
+                segment2.push(bytes.len() as u8);
+                segment2.extend_from_slice(&bytes);
+            } else if token.type_ == TokenType::STRING {
+                let bytes = symbols.strings[token.val as usize].as_bytes();
+                segment2.push(TokenType::STRING as u8);
+                segment2.write_i32::<BigEndian>(bytes.len() as i32).unwrap();
+                segment2.extend_from_slice(bytes);
+            } else {
+                segment2.append(&mut token.to_bytearray(options.width));
             }
         }
     }
 
-    // Querying length from regs after filling segment2 because regs also gets filled in the process.
-    segment1.write_u32::<BigEndian>(regs.len() as u32).unwrap();
-    segment1.extend(iter::repeat(0u8).take(28 /* 32 - 4 */));
+    // Querying lengths from symbols after filling segment2 because symbols also gets filled in the process.
+    segment1.write_u32::<BigEndian>(symbols.regs.len() as u32).unwrap();
+    segment1.write_u32::<BigEndian>(symbols.buffers.len() as u32).unwrap();
+    segment1.write_u32::<BigEndian>(FLAG_REGISTER_NAMES | FLAG_REGISTER_TYPES | FLAG_LINE_ORIGINS).unwrap();
+    segment1.push(match options.width {
+        Width::Big => WIDTH_BIG,
+        width => width.bits() as u8
+    });
+    segment1.push(match options.overflow {
+        Overflow::Wrap => 0,
+        Overflow::Trap => 1,
+        Overflow::Saturate => 2,
+    });
+    segment1.push(match options.division {
+        Division::Truncate => 0,
+        Division::Floor => 1,
+    });
+    segment1.extend(iter::repeat(0u8).take(17 /* 32 - 4 - 4 - 4 - 1 - 1 - 1 */));
     assert_eq!(segment1.len(), 32);
+    for &(_, size) in &symbols.buffers {
+        segment1.write_u32::<BigEndian>(size).unwrap();
+    }
+    for name in &symbols.regs {
+        // Register names are ASCII (see parser::regname_valid), but may be longer than a u8 can count
+        let bytes = &name.as_bytes()[..::std::cmp::min(name.len(), 255)];
+        segment1.push(bytes.len() as u8);
+        segment1.extend_from_slice(bytes);
+    }
+    for &reg_type in &symbols.reg_types {
+        segment1.push(reg_type as u8);
+    }
+    segment1.write_u32::<BigEndian>(symbols.origins.len() as u32).unwrap();
+    for origin in &symbols.origins {
+        let bytes = &origin.as_bytes()[..::std::cmp::min(origin.len(), u16::max_value() as usize)];
+        segment1.write_u16::<BigEndian>(bytes.len() as u16).unwrap();
+        segment1.extend_from_slice(bytes);
+    }
 
     segment1.append(&mut segment2);
     Ok(segment1)
 }
 
-// Converts a given bytecode sequence (Vec<u8>) to (usize /* register count */, Vec<Vec<Token>>).
-pub fn from_bytecode(bytecode: &Vec<u8>) -> Result<(usize, Vec<Vec<Token>>), String> {
-    let mut seg1reader = Cursor::new(&bytecode[0..4]);
+/// A program read back from bytecode by `from_bytecode`.
+pub struct Bytecode {
+    /// Name of each register, in the order of their indices
+    pub reg_names: Vec<String>,
+    /// Type of each register, in the order of their indices
+    pub reg_types: Vec<RegType>,
+    /// Size of each memory buffer, in the order of their indices
+    pub buffer_sizes: Vec<u32>,
+    /// String literals, which the values of STRING tokens are indices into
+    pub strings: Vec<String>,
+    /// Where each line comes from, empty for older files
+    pub origins: Vec<String>,
+    pub tokens: Vec<Vec<Token>>,
+    /// Register width that the file was converted with, which its literals are encoded in
+    pub width: Width,
+    /// Overflow policy that the file was converted with
+    pub overflow: Overflow,
+    /// Division rounding that the file was converted with
+    pub division: Division,
+}
+
+// Converts a given bytecode sequence (Vec<u8>) to the program it holds.
+pub fn from_bytecode(bytecode: &Vec<u8>) -> Result<Bytecode, String> {
+    if bytecode.len() < 32 {
+        return Err("File is too short to contain the metadata header".to_owned());
+    }
+    let mut seg1reader = Cursor::new(&bytecode[0..12]);
     let reg_count = try_failsafe!(seg1reader.read_u32::<BigEndian>(), "Failed to read register count in metadata".to_owned()) as usize;
+    let buffer_count = try_failsafe!(seg1reader.read_u32::<BigEndian>(), "Failed to read memory buffer count in metadata".to_owned()) as usize;
+    let flags = try_failsafe!(seg1reader.read_u32::<BigEndian>(), "Failed to read flags in metadata".to_owned());
+    let width = match bytecode[12] {
+        0 | 32 => Width::I32,
+        64 => Width::I64,
+        128 => Width::I128,
+        WIDTH_BIG => Width::Big,
+        bits => return Err(format!("Unsupported register width of {} bits in metadata", bits))
+    };
+    let overflow = match bytecode[13] {
+        0 => Overflow::Wrap,
+        1 => Overflow::Trap,
+        2 => Overflow::Saturate,
+        policy => return Err(format!("Unknown overflow policy {} in metadata", policy))
+    };
+    let division = match bytecode[14] {
+        0 => Division::Truncate,
+        1 => Division::Floor,
+        rounding => return Err(format!("Unknown division rounding {} in metadata", rounding))
+    };
+
+    let mut segment2_start = 32 + buffer_count * 4;
+    if bytecode.len() < segment2_start {
+        return Err(format!("File is too short to contain the sizes of {} memory buffers", buffer_count));
+    }
+    let mut sizereader = Cursor::new(&bytecode[32..segment2_start]);
+    let mut buffer_sizes: Vec<u32> = Vec::new();
+    for _ in 0..buffer_count {
+        buffer_sizes.push(try_failsafe!(sizereader.read_u32::<BigEndian>(), "Failed to read memory buffer size in metadata".to_owned()));
+    }
+
+    let mut reg_names: Vec<String> = Vec::new();
+    for index in 0..reg_count {
+        if flags & FLAG_REGISTER_NAMES == 0 {
+            reg_names.push(format!("r{}", index));
+            continue;
+        }
+        let length = *try_opt!(bytecode.get(segment2_start), format!("File is too short to contain the name of register {}", index)) as usize;
+        let name = try_opt!(bytecode.get(segment2_start + 1..segment2_start + 1 + length),
+                            format!("File is too short to contain the name of register {}", index));
+        reg_names.push(try_failsafe!(String::from_utf8(name.to_vec()), format!("Name of register {} is not valid UTF-8", index)));
+        segment2_start += 1 + length;
+    }
 
-    let segment2 = bytecode[32..].chunks(5);
+    let mut reg_types: Vec<RegType> = Vec::new();
+    for index in 0..reg_count {
+        if flags & FLAG_REGISTER_TYPES == 0 {
+            reg_types.push(RegType::Int);
+            continue;
+        }
+        let code = *try_opt!(bytecode.get(segment2_start), format!("File is too short to contain the type of register {}", index));
+        reg_types.push(try_opt!(RegType::from_u8(code), format!("Unknown type {} of register {}", code, index)));
+        segment2_start += 1;
+    }
+
+    let mut origins: Vec<String> = Vec::new();
+    if flags & FLAG_LINE_ORIGINS != 0 {
+        let count_bytes = try_opt!(bytecode.get(segment2_start..segment2_start + 4), "File is too short to contain the count of line origins".to_owned());
+        let count = Cursor::new(count_bytes).read_u32::<BigEndian>().unwrap();
+        segment2_start += 4;
+        for index in 0..count {
+            let length_bytes = try_opt!(bytecode.get(segment2_start..segment2_start + 2), format!("File is too short to contain the origin of line {}", index));
+            let length = Cursor::new(length_bytes).read_u16::<BigEndian>().unwrap() as usize;
+            let origin = try_opt!(bytecode.get(segment2_start + 2..segment2_start + 2 + length),
+                                  format!("File is too short to contain the origin of line {}", index));
+            origins.push(try_failsafe!(String::from_utf8(origin.to_vec()), format!("Origin of line {} is not valid UTF-8", index)));
+            segment2_start += 2 + length;
+        }
+    }
+
+    let segment2 = &bytecode[segment2_start..];
     let mut toks: Vec<Vec<Token>> = Vec::new();
+    let mut strings: Vec<String> = Vec::new();
+    let mut position = 0usize;
+    let mut index = 0usize;
 
-    for (index, bytoken) in segment2.enumerate() {
-        let token = try_err_fallthru!(Token::from_bytearray(&bytoken),
+    while position < segment2.len() {
+        let size = 1 + match TokenType::from_u8(segment2[position]) {
+            Some(token_type) => Token::data_size(&token_type, width),
+            None => return Err(format!("Unknown token type {} in chunk index {}", segment2[position], index))
+        };
+        if position + size > segment2.len() {
+            return Err(format!("Chunk index {} is cut off", index));
+        }
+        let mut token = try_err_fallthru!(Token::from_bytearray(&segment2[position..position + size], width),
                                       format!("Failed to convert from bytes to Token in chunk index {}: ", index));
+        position += size;
+        if token.type_ == TokenType::LITERAL && width == Width::Big {
+            let length = *try_opt!(segment2.get(position), format!("Literal in chunk index {} is cut off", index)) as usize;
+            if length == 0 || length > 16 || position + 1 + length > segment2.len() {
+                return Err(format!("Literal in chunk index {} has an invalid length", index));
+            }
+            token.val = read_literal(&segment2[position + 1..position + 1 + length]);
+            position += 1 + length;
+        }
+        if token.type_ == TokenType::STRING {
+            let length = token.val as usize;
+            if token.val < 0 || position + length > segment2.len() {
+                return Err(format!("String in chunk index {} is cut off", index));
+            }
+            let string = try_failsafe!(String::from_utf8(segment2[position..position + length].to_vec()),
+                                       format!("String in chunk index {} is not valid UTF-8", index));
+            position += length;
+            strings.push(string);
+            token.val = strings.len() as i128 - 1;
+        }
         if token.type_ == TokenType::KEYWORD {
+            if token.val < 0 || token.val as usize >= parser::KEYWORD_INDEX.len() {
+                return Err(format!("Unknown keyword index {} in chunk index {}", token.val, index));
+            }
             toks.push(vec![token]);
         } else {
+            if token.type_ == TokenType::BUFFER && (token.val < 0 || token.val as usize >= buffer_count) {
+                return Err(format!("Unknown memory buffer index {} in chunk index {}", token.val, index));
+            }
+            if token.type_ == TokenType::REGISTER && (token.val < 0 || token.val as usize >= reg_count) {
+                return Err(format!("Unknown register index {} in chunk index {}", token.val, index));
+            }
             try_opt!(toks.last_mut(),
                      "First token is not of type KEYWORD".to_owned()).push(token);
         }
+        index += 1;
+    }
+    // Each line needs the parameter count of its keyword, such as 3 for `jeq <A> <B> <offset>`
+    for (index, line) in toks.iter().enumerate() {
+        if !parser::tokens_valid(line) {
+            return Err(format!("Line {} has invalid parameters for keyword '{}'", index, parser::KEYWORD_INDEX[line[0].val as usize]));
+        }
     }
-    Ok((reg_count, toks))
-}
\ No newline at end of file
+    Ok(Bytecode {
+        reg_names: reg_names,
+        reg_types: reg_types,
+        buffer_sizes: buffer_sizes,
+        strings: strings,
+        origins: origins,
+        tokens: toks,
+        width: width,
+        overflow: overflow,
+        division: division,
+    })
+}
+
+/// Returns the shortest big-endian two's complement encoding of `val`.
+fn literal_bytes(val: i128) -> Vec<u8> {
+    let mut length = 1;
+    // Grows until sign-extending the lowest `length` bytes gives the value back
+    while length < 16 && (val << (128 - length * 8)) >> (128 - length * 8) != val {
+        length += 1;
+    }
+    (0..length).rev().map(|byte| (val >> (byte * 8)) as u8).collect()
+}
+
+/// Reads a literal encoded by `literal_bytes`.
+fn read_literal(bytes: &[u8]) -> i128 {
+    // Sign-extended from the first byte
+    let mut value: i128 = if bytes[0] & 0x80 != 0 { -1 } else { 0 };
+    for &byte in bytes {
+        value = (value << 8) | byte as i128;
+    }
+    value
+}
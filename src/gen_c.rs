@@ -1,4 +1,8 @@
 use parser;
+use interpret;
+use options::{Options, Width, Port, PORT_COUNT};
+use value::RegType;
+use std::collections::HashMap;
 /*
   This mod generates C code from Assembunny+.
   The conventional usage of gen_c is after the user has "checked" their code with the interpreter. Therefore, the C generator does not provide any checks except parser::line_valid.
@@ -41,15 +45,74 @@ use parser;
 // C semantics
 // NOTE: This is directly related to the "starting with '__'" check in parser.rs.
 
+/// Name of the C type of registers and every other value, defined before main() according to the register width
+const INT_TYPE: &'static str = "__asmb_int";
+
+/// Name of the unsigned C type of the same width as `INT_TYPE`
+const UINT_TYPE: &'static str = "__asmb_uint";
+
 /// Prefix of a C variable representing a register
-/// Example: "__asmb_reg_" means `int32_t __asmb_reg_rmta;` for register "rmta"
+/// Example: "__asmb_reg_" means `__asmb_int __asmb_reg_rmta;` for register "rmta"
 const REG_VARNAME_PREFIX: &'static str = "__asmb_reg_";
 
+/// Prefix of a C variable representing a local register of a procedure (see parser::Procedure), followed by the length of the procedure's name
+/// Example: "__asmb_local_" means `__asmb_int __asmb_local_4_fact_n;` for parameter "n" of procedure "fact", whose local name is "fact.n"
+const LOCAL_VARNAME_PREFIX: &'static str = "__asmb_local_";
+
+/// Name of the C array holding the arguments of the last CALL, which the PROC line it jumps to takes
+const ARGS_ARRAY: &'static str = "__asmb_args";
+
+/// Name of the C variable holding the amount of values in `ARGS_ARRAY`
+const ARG_COUNT_VARNAME: &'static str = "__asmb_arg_count";
+
+/// Name of the C variable holding the target line of the last CALL, which tells a PROC line whether it is being called
+const CALLEE_VARNAME: &'static str = "__asmb_callee";
+
+/// Prefix of a C array holding a local of a procedure in each frame of the calls of the procedure, followed by the index of the procedure, '_', and the index of the local
+/// Each frame holds the values of the procedure's locals from before the call (see interpret::Frame); one array per local keeps each local's C type.
+const FRAMES_ARRAY_PREFIX: &'static str = "__asmb_frames_";
+
+/// Prefix of a C variable holding the amount of frames of a procedure, followed by the index of the procedure
+const FRAME_DEPTH_PREFIX: &'static str = "__asmb_frame_depth_";
+
+/// Prefix of a C variable holding the line of the procedure that TRAP handed a kind of runtime errors to (0 for none), followed by the kind
+/// Example: "__asmb_trap_" means `int64_t __asmb_trap_div = 0;` for division by zero (see parser::TRAP_KINDS)
+const TRAP_VARNAME_PREFIX: &'static str = "__asmb_trap_";
+
 /// Prefix of a C label representing a line in the .asmb source
 /// Example: "__asmb_line_" means `__asmb_line_41:` for line 41
 /// This is required for `jnz` to work.
 const LINE_LABEL_PREFIX: &'static str = "__asmb_line_";
 
+/// Name of the C variable holding the target line of a computed jump (such as `jnz` with a register offset)
+const JUMP_TARGET_VARNAME: &'static str = "__asmb_target";
+
+/// Label of the jump table that computed jumps go through, since C cannot `goto` a line number held in a variable
+const JUMP_TABLE_LABEL: &'static str = "__asmb_jump_table";
+
+/// Name of the C array holding the return addresses (line numbers) of the CALLs that haven't RETurned yet
+const CALL_STACK_ARRAY: &'static str = "__asmb_calls";
+
+/// Name of the C variable holding the amount of return addresses in `CALL_STACK_ARRAY`
+const CALL_DEPTH_VARNAME: &'static str = "__asmb_call_depth";
+
+/// Name of the C array holding the data stack (PUSH/POP/PEEK)
+const STACK_ARRAY: &'static str = "__asmb_stack";
+
+/// Name of the C variable holding the amount of values in `STACK_ARRAY`
+const STACK_SIZE_VARNAME: &'static str = "__asmb_stack_size";
+
+/// Prefix of a C array representing a memory buffer
+/// Example: "__asmb_mem_" means `static __asmb_int __asmb_mem_grid[100];` for `mem grid 100`
+const MEM_VARNAME_PREFIX: &'static str = "__asmb_mem_";
+
+/// Name of the C variable holding the current line number in dispatch mode (see `compose_dispatch`)
+const IP_VARNAME: &'static str = "__asmb_ip";
+
+/// Name of the C array holding the toggle state of each line in dispatch mode
+/// 0 means the line was never toggled, 1 means it was toggled an odd number of times, and 2 means an even number of times.
+const FORM_ARRAY: &'static str = "__asmb_form";
+
 /// Indentation characters
 /// Choose between Tabs and Spaces (the battle is still on!)
 /// TODO: Make the selection available as a command line option
@@ -57,23 +120,65 @@ const INDENT: &'static str = "\t";
 
 /// Prototype of generated C code
 /// Will be used during the final compilation of C source
-const C_PROTOTYPE: &'static str = "#include <stdio.h>\n#include <stdint.h>\n\nint main(void) {\n##return 0;\n}";
+/// "@@" is replaced by the declarations before main() (see `prelude`), and "##" by the body of main().
+const C_PROTOTYPE: &'static str = "#include <stdio.h>\n#include <stdint.h>\n#include <string.h>\n\n@@int main(void) {\n##return 0;\n}";
 
 macro_rules! eval {
-	( $arg:expr ) => (match $arg.parse::<i32>() {
-		Ok(_) => $arg.to_owned(),
+	( $arg:expr ) => (match parser::is_literal($arg) {
+		Ok(val) => gen_c::c_literal(val),
 		Err(_) => reg!($arg)
 	});
 }
 
+/// Returns a C expression with the value of a literal.
+/// Literals are written in decimal whatever their syntax in ASMB+ (such as 0xFF or 'A'), since C has no binary or character literals of the same meaning.
+fn c_literal(val: i128) -> String {
+	if val == i32::min_value() as i128 {
+		// In C, -2147483648 is the negation of a literal that does not fit in an int
+		"INT32_MIN".to_owned()
+	} else if val == i64::min_value() as i128 {
+		"INT64_MIN".to_owned()
+	} else if val >= i64::min_value() as i128 && val <= i64::max_value() as i128 {
+		val.to_string()
+	} else {
+		// C has no 128-bit literals, so the value is put together from its two halves
+		format!("(({}) ((({}) {}u << 64) | {}u))", INT_TYPE, UINT_TYPE, (val >> 64) as u64, val as u64)
+	}
+}
+
+/// Returns the C type of registers of the given type.
+fn c_type(reg_type: RegType) -> &'static str {
+	match reg_type {
+		RegType::Int => INT_TYPE,
+		RegType::I8 => "int8_t",
+		RegType::I16 => "int16_t",
+		RegType::I32 => "int32_t",
+		RegType::I64 => "int64_t",
+		RegType::U8 => "uint8_t",
+		RegType::U16 => "uint16_t",
+		RegType::U32 => "uint32_t",
+		RegType::U64 => "uint64_t",
+		RegType::Bool => "_Bool"
+	}
+}
+
 macro_rules! reg {
-	( $name:expr ) => (format!("{}{}", gen_c::REG_VARNAME_PREFIX, $name));
+	( $name:expr ) => (gen_c::c_register($name));
 }
 
 macro_rules! line {
 	( $num:expr ) => (format!("{}{}", gen_c::LINE_LABEL_PREFIX, $num));
 }
 
+/// Returns the name of the C variable of the register named `name`.
+/// The dot in the name of a local register of a procedure (see parser::Procedure) cannot be part of a C name, so those get `LOCAL_VARNAME_PREFIX`.
+fn c_register(name: &str) -> String {
+	match name.find('.') {
+		Some(dot) => format!("{}{}_{}", LOCAL_VARNAME_PREFIX, dot, name.replace('.', "_")),
+		None => format!("{}{}", REG_VARNAME_PREFIX, name)
+	}
+}
+
 /// Closures called to make modifications to C source draft
 //static DRAFT_MODIFIERS: HashMap<&'static str, Box<FnMut(&Vec<&str>) -> String>> = hashmap!(
 //	"def" => Box::new(|&args| {
@@ -85,40 +190,178 @@ macro_rules! line {
 // XXX: Please inform me if there's a more efficient way using a static HashMap or something else.
 pub mod generators {
 	use gen_c;
+	use parser;
+	use interpret;
+	use options::{Options, Width, Overflow, Division, PORT_COUNT};
+	use value::RegType;
 
-	pub fn def(args: &Vec<&str>) -> String {
+	/// Returns the type of the register named `arg`, or `Int` if `arg` is a literal.
+	fn value_type(arg: &str, context: &gen_c::LineContext) -> RegType {
+		context.types.get(arg).cloned().unwrap_or(RegType::Int)
+	}
+
+	/// Returns a C statement that stops the program with the given runtime error, naming the line like the interpreter does.
+	/// STDOUT is flushed first (as in every write to STDERR) so that the error follows the output before it.
+	fn runtime_error(message: &str, context: &gen_c::LineContext) -> String {
+		format!("{{ fflush(stdout); fputs({}, stderr); return 1; }}", c_string(&format!("{}: {}\n", context.origin, message)))
+	}
+
+	/// Returns a C statement that hands a runtime error of the given kind (see parser::TRAP_KINDS) to its handler like interpret::exec::fault,
+	/// or runs `default` (which stops the program) if TRAP hasn't given the kind to a procedure.
+	fn fault(kind: &str, default: String, context: &gen_c::LineContext) -> String {
+		if !context.traps.contains(&kind) {
+			return default;
+		}
+		let code = parser::index_of(&parser::TRAP_KINDS, &kind).unwrap() + 1;
+		format!("{{ if ({h} != 0) {{ {a}[0] = {}; {a}[1] = {}; {} = 2; {} = {h}; {} {} = {h}; goto {}; }} {} }}",
+			code, parser::origin_line(context.origin), gen_c::ARG_COUNT_VARNAME, gen_c::CALLEE_VARNAME, push_return_address(&(context.linenum + 1).to_string(), context),
+			gen_c::JUMP_TARGET_VARNAME, gen_c::JUMP_TABLE_LABEL, default, h = format!("{}{}", gen_c::TRAP_VARNAME_PREFIX, kind), a = gen_c::ARGS_ARRAY)
+	}
+
+	/// Returns the C statement to run when the result of an arithmetic instruction on register `reg` does not fit, or None if it should just wrap around.
+	/// `negative` is a C condition telling whether the exact result is negative, for saturation.
+	fn on_overflow(reg: &str, negative: &str, context: &gen_c::LineContext, options: &Options) -> Option<String> {
+		match options.overflow {
+			Overflow::Wrap => None,
+			Overflow::Trap => Some(runtime_error(&format!("Integer overflow ({}-bit)", options.width.bits()), context)),
+			Overflow::Saturate => Some(format!("{} = ({}) ? {} : {};", reg, negative,
+				gen_c::c_literal(options.width.min_value()), gen_c::c_literal(options.width.max_value())))
+		}
+	}
+
+	/// Returns C code that sets the register named `reg_name` to `a <op> b` with `__builtin_<op>_overflow`, which tells whether the exact result fits and stores it wrapped around if it doesn't.
+	/// `negative` is a C condition on `__asmb_a` and `__asmb_b` telling whether the exact result is negative.
+	/// Registers with a declared type always wrap around instead: the result is computed modulo 2^64 and converted to the type, as in the interpreter.
+	fn checked(op: &str, reg_name: &str, a: &str, b: &str, negative: &str, context: &gen_c::LineContext, options: &Options) -> String {
+		let (reg, reg_type) = (reg!(reg_name), value_type(reg_name, context));
+		if reg_type != RegType::Int {
+			let symbol = match op { "add" => "+", "sub" => "-", _ => "*" };
+			return format!("{} = ({}) ((uint64_t) {} {} (uint64_t) {});", reg, gen_c::c_type(reg_type), a, symbol, b);
+		}
+		match on_overflow(&reg, negative, context, options) {
+			None => format!("(void) __builtin_{}_overflow({}, {}, &{});", op, a, b, reg),
+			Some(handler) => format!("{{ {t} __asmb_a = {}, __asmb_b = {}; if (__builtin_{}_overflow(__asmb_a, __asmb_b, &{})) {} }}",
+				a, b, op, reg, handler, t = gen_c::INT_TYPE)
+		}
+	}
+
+	pub fn def(args: &Vec<&str>, context: &gen_c::LineContext) -> String {
 		// Syntax: def <new reg name> <eval>
-		format!("int32_t {} = {};", reg!(args[1]), eval!(args[2]))
+		// Local registers of procedures are declared at the top of main(), since the PROC line saves them before their DEF
+		if args[1].contains('.') {
+			return format!("{} = {};", reg!(args[1]), eval!(args[2]));
+		}
+		format!("{} {} = {};", gen_c::c_type(value_type(args[1], context)), reg!(args[1]), eval!(args[2]))
 	}
 
-	pub fn inc(args: &Vec<&str>) -> String {
+	pub fn inc(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
 		// Syntax: inc <reg name>
-		format!("++{};", reg!(args[1]))
+		checked("add", args[1], &reg!(args[1]), "1", "0", context, options)
 	}
 
-	pub fn inct(args: &Vec<&str>) -> String {
+	pub fn inct(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
 		// Syntax: inct <reg name> <eval>
-		format!("{} += {};", reg!(args[1]), eval!(args[2]))
+		checked("add", args[1], &reg!(args[1]), &eval!(args[2]), "__asmb_b < 0", context, options)
 	}
 
-	pub fn dec(args: &Vec<&str>) -> String {
+	pub fn dec(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
 		// Syntax: dec <reg name>
-		format!("--{};", reg!(args[1]))
+		checked("sub", args[1], &reg!(args[1]), "1", "1", context, options)
 	}
 
-	pub fn dect(args: &Vec<&str>) -> String {
+	pub fn dect(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
 		// Syntax: dect <reg name> <eval>
-		format!("{} -= {};", reg!(args[1]), eval!(args[2]))
+		checked("sub", args[1], &reg!(args[1]), &eval!(args[2]), "__asmb_b > 0", context, options)
 	}
 
-	pub fn mul(args: &Vec<&str>) -> String {
+	pub fn mul(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
 		// Syntax: mul <reg name> <eval>
-		format!("{} *= {};", reg!(args[1]), eval!(args[2]))
+		checked("mul", args[1], &reg!(args[1]), &eval!(args[2]), "(__asmb_a < 0) != (__asmb_b < 0)", context, options)
 	}
 
-	pub fn div(args: &Vec<&str>) -> String {
+	pub fn div(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
 		// Syntax: div <reg name> <eval>
-		format!("{} /= {};", reg!(args[1]), eval!(args[2]))
+		// Division by zero and the minimum divided by -1 are undefined behavior in C, so both are checked first
+		let r = reg!(args[1]);
+		let reg_type = value_type(args[1], context);
+		// C truncates, so a floored quotient is one lower when it is negative and not whole
+		let quotient = match options.division {
+			Division::Truncate => "__asmb_a / __asmb_b",
+			Division::Floor => "__asmb_a / __asmb_b - (__asmb_a % __asmb_b != 0 && (__asmb_a < 0) != (__asmb_b < 0))"
+		};
+		let zero_check = format!("if (__asmb_b == 0) {}", fault("div", runtime_error("Division by zero", context), context));
+		if reg_type != RegType::Int {
+			// Declared types are at most 64 bits wide, and the minimum divided by -1 wraps around to the minimum
+			let (t, quotient) = if reg_type.signed() {
+				("int64_t", format!("__asmb_b == -1 ? 0 - (uint64_t) __asmb_a : (uint64_t) ({})", quotient))
+			} else {
+				("uint64_t", "__asmb_a / __asmb_b".to_owned())
+			};
+			return format!("{{ {t} __asmb_a = {r}, __asmb_b = {}; {} {r} = ({}) ({}); }}",
+				eval!(args[2]), zero_check, gen_c::c_type(reg_type), quotient, r = r, t = t);
+		}
+		let overflow = on_overflow(&r, "0", context, options).unwrap_or(format!("{} = __asmb_a;", r));
+		format!("{{ {t} __asmb_a = {r}, __asmb_b = {}; {} if (__asmb_b == -1 && __asmb_a == {}) {} else {r} = {}; }}",
+			eval!(args[2]), zero_check, gen_c::c_literal(options.width.min_value()), overflow, quotient, r = r, t = gen_c::INT_TYPE)
+	}
+
+	pub fn mod_(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: mod <reg name> <eval>
+		// The minimum % -1 is undefined behavior in C, but the interpreter defines it as 0
+		let r = reg!(args[1]);
+		let (t, remainder) = match value_type(args[1], context) {
+			RegType::Int => (gen_c::INT_TYPE, format!("__asmb_divisor == -1 ? 0 : {} % __asmb_divisor", r)),
+			reg_type if reg_type.signed() => ("int64_t", format!("__asmb_divisor == -1 ? 0 : {} % __asmb_divisor", r)),
+			// Unsigned remainders are the same either way
+			_ => ("uint64_t", format!("{} % __asmb_divisor", r))
+		};
+		let assignment = match options.division {
+			Division::Truncate => format!("{} = {};", r, remainder),
+			// A floored remainder has the sign of the divisor, like the floored quotient of DIV
+			Division::Floor => format!("{{ {} __asmb_rem = {}; {} = __asmb_rem != 0 && (__asmb_rem < 0) != (__asmb_divisor < 0) ? __asmb_rem + __asmb_divisor : __asmb_rem; }}",
+				t, remainder, r)
+		};
+		format!("{{ {} __asmb_divisor = {}; if (__asmb_divisor == 0) {} {} }}",
+			t, eval!(args[2]), fault("div", runtime_error("Modulo by zero", context), context), assignment)
+	}
+
+	pub fn and(args: &Vec<&str>) -> String {
+		// Syntax: and <reg name> <eval>
+		format!("{} &= {};", reg!(args[1]), eval!(args[2]))
+	}
+
+	pub fn or(args: &Vec<&str>) -> String {
+		// Syntax: or <reg name> <eval>
+		format!("{} |= {};", reg!(args[1]), eval!(args[2]))
+	}
+
+	pub fn xor(args: &Vec<&str>) -> String {
+		// Syntax: xor <reg name> <eval>
+		format!("{} ^= {};", reg!(args[1]), eval!(args[2]))
+	}
+
+	pub fn shl(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: shl <reg name> <eval>
+		// Shifted as unsigned, since shifting bits into the sign bit is undefined behavior in C
+		let reg_type = value_type(args[1], context);
+		let unsigned = if reg_type == RegType::Int { gen_c::UINT_TYPE } else { "uint64_t" };
+		format!("{r} = ({}) (({}) {r} << ({} & {}));", gen_c::c_type(reg_type), unsigned, eval!(args[2]), reg_type.bits(options.width) - 1, r = reg!(args[1]))
+	}
+
+	pub fn shr(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: shr <reg name> <eval>
+		let reg_type = value_type(args[1], context);
+		if !reg_type.signed() {
+			return format!("{} >>= {} & {};", reg!(args[1]), eval!(args[2]), reg_type.bits(options.width) - 1);
+		}
+		// Right-shifting a negative number is implementation-defined in C, so the arithmetic shift is spelled out
+		format!("{{ {} __asmb_amount = {} & {}; {r} = {r} < 0 ? ~(~{r} >> __asmb_amount) : {r} >> __asmb_amount; }}",
+			gen_c::INT_TYPE, eval!(args[2]), reg_type.bits(options.width) - 1, r = reg!(args[1]))
+	}
+
+	pub fn neg(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: neg <reg name>
+		checked("sub", args[1], "0", &reg!(args[1]), "0", context, options)
 	}
 
 	pub fn cpy(args: &Vec<&str>) -> String {
@@ -126,35 +369,418 @@ pub mod generators {
 		format!("{} = {};", reg!(args[2]), eval!(args[1]))
 	}
 
-	pub fn jnz(args: &Vec<&str>, linenum: u32) -> String {
-		// Syntax: jnz <eval not 0> <literal>
-		let offset = args[2].parse::<i32>().unwrap();
-		format!("if ({} != 0) goto {};", eval!(args[1]), line!(if offset < 0 {
-			linenum - (-offset as u32)
+	/// Returns the C condition under which the given line jumps, or None if the line is not a jump.
+	pub fn jump_condition(args: &Vec<&str>) -> Option<String> {
+		match args[0].to_lowercase().as_str() {
+			"jnz" => Some(format!("{} != 0", eval!(args[1]))),
+			"jz" => Some(format!("{} == 0", eval!(args[1]))),
+			"jeq" => Some(format!("{} == {}", eval!(args[1]), eval!(args[2]))),
+			"jne" => Some(format!("{} != {}", eval!(args[1]), eval!(args[2]))),
+			"jlt" => Some(format!("{} < {}", eval!(args[1]), eval!(args[2]))),
+			"jgt" => Some(format!("{} > {}", eval!(args[1]), eval!(args[2]))),
+			_ => None
+		}
+	}
+
+	/// Returns a C statement that stops the program if the line number in `target` (a C expression) is before the first line, naming the line that jumps.
+	pub fn check_jump_target(target: &str, context: &gen_c::LineContext, options: &Options) -> String {
+		format!("if ({} < 1) {{ {} return 1; }}", target, print_values("stderr", &format!("{}: Jump target {{}} is before the start of the program\n", context.origin),
+			&[(format!("({} - 1)", target), RegType::I64)], options))
+	}
+
+	/// Returns a C statement that continues execution `offset_arg` lines away from the given line.
+	/// Register offsets are computed at runtime, so they go through the jump table at the end of main() (see `compose`).
+	fn goto_relative(offset_arg: &str, context: &gen_c::LineContext, options: &Options) -> String {
+		let (linenum, linecount) = (context.linenum, context.linecount);
+		let offset = match parser::is_literal(offset_arg) {
+			Ok(offset) => offset as i64,
+			Err(_) => return format!("{{ {t} = {} + {}; {} goto {}; }}",
+				linenum, eval!(offset_arg), check_jump_target(gen_c::JUMP_TARGET_VARNAME, context, options), gen_c::JUMP_TABLE_LABEL, t = gen_c::JUMP_TARGET_VARNAME)
+		};
+		let target = linenum as i64 + offset;
+		if target < 1 {
+			runtime_error(&format!("Jump target {} is before the start of the program", target - 1), context)
+		} else {
+			// Every target past the end of the program ends it, just like the line right after the last one
+			format!("goto {};", line!(::std::cmp::min(target, linecount as i64 + 1)))
+		}
+	}
+
+	pub fn jump(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: jnz <eval not 0> <literal or reg name>, jeq <eval> <eval> <literal or reg name>, etc.
+		// The offset is always the last parameter.
+		format!("if ({}) {}", jump_condition(args).unwrap(), goto_relative(args[args.len() - 1], context, options))
+	}
+
+	/// Returns C code that pushes `return_address` onto the call stack.
+	pub fn push_return_address(return_address: &str, context: &gen_c::LineContext) -> String {
+		format!("if ({d} == {}) {} {}[{d}++] = {};", interpret::CALL_STACK_LIMIT,
+			runtime_error(&format!("Call stack overflow (more than {} nested calls)", interpret::CALL_STACK_LIMIT), context),
+			gen_c::CALL_STACK_ARRAY, return_address, d = gen_c::CALL_DEPTH_VARNAME)
+	}
+
+	/// Returns C code that pops a return address off the call stack into `destination`.
+	pub fn pop_return_address(destination: &str, context: &gen_c::LineContext) -> String {
+		format!("if ({d} == 0) {} {} = {}[--{d}];", runtime_error("Call stack underflow (RET without CALL)", context),
+			destination, gen_c::CALL_STACK_ARRAY, d = gen_c::CALL_DEPTH_VARNAME)
+	}
+
+	pub fn call(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: call <literal or reg name> [eval...]
+		let linenum = context.linenum;
+		if context.procedures.is_empty() {
+			return format!("{{ {} {} }}", push_return_address(&(linenum + 1).to_string(), context), goto_relative(args[1], context, options));
+		}
+		// The PROC line at the target, if there is one, takes the arguments (see `proc_`)
+		let mut handover = String::new();
+		for (index, arg) in args[2..].iter().enumerate() {
+			handover += &format!("{}[{}] = {}; ", gen_c::ARGS_ARRAY, index, eval!(arg));
+		}
+		handover += &format!("{} = {}; {} = {} + {};", gen_c::ARG_COUNT_VARNAME, args.len() - 2, gen_c::CALLEE_VARNAME, linenum, eval!(args[1]));
+		format!("{{ {} {} {} }}", handover, push_return_address(&(linenum + 1).to_string(), context), goto_relative(args[1], context, options))
+	}
+
+	/// Returns the index of the procedure that the line is in, and the C names of its locals.
+	fn procedure_locals(context: &gen_c::LineContext) -> (usize, Vec<String>) {
+		let index = context.procedures.iter().position(|procedure| procedure.entry < context.linenum && context.linenum <= procedure.exit + 1).unwrap();
+		let procedure = &context.procedures[index];
+		(index, procedure.locals.iter().map(|local| reg!(&procedure.local_name(local).unwrap())).collect())
+	}
+
+	pub fn proc_(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: proc <offset past endp> <first local (unused in C)> <local count> <parameter count>
+		// Only a CALL to this very line calls the procedure; running into it skips the procedure
+		let (index, locals) = procedure_locals(context);
+		let param_count = parser::is_literal(args[4]).unwrap() as usize;
+		let skip = line!(context.linenum as i128 + parser::is_literal(args[1]).unwrap());
+		let arg_count_error = print_values("stderr", &format!("{}: Procedure expects {} argument(s), got {{}}\n", context.origin, param_count),
+			&[(gen_c::ARG_COUNT_VARNAME.to_owned(), RegType::I32)], options);
+		let depth = format!("{}{}", gen_c::FRAME_DEPTH_PREFIX, index);
+		let mut code = format!("if ({} == {}) {{ {} = 0; if ({} != {}) {{ {} return 1; }} if ({} == {}) {}", gen_c::CALLEE_VARNAME, context.linenum, gen_c::CALLEE_VARNAME, gen_c::ARG_COUNT_VARNAME, param_count,
+			arg_count_error, depth, interpret::CALL_STACK_LIMIT, runtime_error(&format!("Call stack overflow (more than {} nested calls)", interpret::CALL_STACK_LIMIT), context));
+		for (offset, local) in locals.iter().enumerate() {
+			code += &format!(" {}{}_{}[{}] = {};", gen_c::FRAMES_ARRAY_PREFIX, index, offset, depth, local);
+		}
+		code += &format!(" ++{};", depth);
+		// Locals other than the parameters start out as 0, until their DEF
+		for (offset, local) in locals.iter().enumerate() {
+			if offset < param_count {
+				code += &format!(" {} = {}[{}];", local, gen_c::ARGS_ARRAY, offset);
+			} else {
+				code += &format!(" {} = 0;", local);
+			}
+		}
+		code + &format!(" }} else goto {};", skip)
+	}
+
+	pub fn return_(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: return [eval], endp
+		let (index, locals) = procedure_locals(context);
+		let depth = format!("{}{}", gen_c::FRAME_DEPTH_PREFIX, index);
+		let mut code = format!("if ({} == 0) {}", depth, runtime_error("RETURN or ENDP without a call of a procedure", context));
+		if args.len() > 1 {
+			// The result is pushed before the locals are put back, since it may be one of them
+			code += " ";
+			code += &push(&vec!["push", args[1]], context, options);
+		}
+		code += &format!(" --{};", depth);
+		for (offset, local) in locals.iter().enumerate() {
+			code += &format!(" {} = {}{}_{}[{}];", local, gen_c::FRAMES_ARRAY_PREFIX, index, offset, depth);
+		}
+		format!("{{ {} {} }}", code, ret(args, context))
+	}
+
+	pub fn trap(args: &Vec<&str>, context: &gen_c::LineContext) -> String {
+		// Syntax: trap <kind> <offset of the PROC line of the handler, or 0 for the default>
+		if !context.traps.contains(&args[1]) {
+			return "/* No runtime error of this kind can happen, skipped */;".to_owned();
+		}
+		let offset = parser::is_literal(args[2]).unwrap();
+		format!("{}{} = {};", gen_c::TRAP_VARNAME_PREFIX, args[1], if offset == 0 { 0 } else { context.linenum as i128 + offset })
+	}
+
+	pub fn ret(_args: &Vec<&str>, context: &gen_c::LineContext) -> String {
+		// Syntax: ret
+		// Return addresses are line numbers, so returning always goes through the jump table
+		format!("{{ {} goto {}; }}", pop_return_address(gen_c::JUMP_TARGET_VARNAME, context), gen_c::JUMP_TABLE_LABEL)
+	}
+
+	pub fn push(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: push <eval>
+		format!("if ({s} == {}) {} {}[{s}++] = {};", options.stack_limit,
+			runtime_error(&format!("Stack overflow (more than {} values)", options.stack_limit), context),
+			gen_c::STACK_ARRAY, eval!(args[1]), s = gen_c::STACK_SIZE_VARNAME)
+	}
+
+	pub fn pop(args: &Vec<&str>, context: &gen_c::LineContext) -> String {
+		// Syntax: pop <reg name>
+		let underflow = fault("stack", runtime_error("Stack underflow (POP on empty stack)", context), context);
+		format!("if ({s} == 0) {} {} = {}[--{s}];", underflow, reg!(args[1]), gen_c::STACK_ARRAY, s = gen_c::STACK_SIZE_VARNAME)
+	}
+
+	pub fn peek(args: &Vec<&str>, context: &gen_c::LineContext) -> String {
+		// Syntax: peek <reg name>
+		let underflow = fault("stack", runtime_error("Stack underflow (PEEK on empty stack)", context), context);
+		format!("if ({s} == 0) {} {} = {}[{s} - 1];", underflow, reg!(args[1]), gen_c::STACK_ARRAY, s = gen_c::STACK_SIZE_VARNAME)
+	}
+
+	pub fn mem(args: &Vec<&str>) -> String {
+		// Syntax: mem <buffer name> <literal>
+		// The array itself is declared at the top of main() (see `runtime_declarations`), so MEM only has to clear it.
+		format!("memset({b}, 0, sizeof {b});", b = mem_buffer(args[1]))
+	}
+
+	/// Returns the C array representing the given memory buffer.
+	fn mem_buffer(name: &str) -> String {
+		format!("{}{}", gen_c::MEM_VARNAME_PREFIX, name)
+	}
+
+	/// Returns C code that stores the index `index_arg` into the given memory buffer in `__asmb_index`, exiting if it is out of bounds.
+	fn mem_index(buffer: &str, index_arg: &str, context: &gen_c::LineContext, options: &Options) -> String {
+		let size = format!("({}) (sizeof {b} / sizeof {b}[0])", gen_c::INT_TYPE, b = mem_buffer(buffer));
+		let out_of_bounds = format!("{{ {} return 1; }}", print_values("stderr", &format!("{}: Index {{}} is out of bounds for a memory buffer of size {{}}\n", context.origin),
+			&[("__asmb_index".to_owned(), RegType::Int), (size.clone(), RegType::Int)], options));
+		format!("{} __asmb_index = {}; if (__asmb_index < 0 || __asmb_index >= {}) {}", gen_c::INT_TYPE, eval!(index_arg), size,
+			fault("mem", out_of_bounds, context))
+	}
+
+	pub fn load(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: load <reg name> <buffer name> <eval>
+		format!("{{ {} {} = {}[__asmb_index]; }}", mem_index(args[2], args[3], context, options), reg!(args[1]), mem_buffer(args[2]))
+	}
+
+	pub fn store(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: store <buffer name> <eval> <eval>
+		format!("{{ {} {}[__asmb_index] = {}; }}", mem_index(args[1], args[2], context, options), mem_buffer(args[1]), eval!(args[3]))
+	}
+
+	/// Returns a C statement that prints `format` to `stream`, with each `{}` in `format` replaced by the value of the C expression at the same position in `values`,
+	/// which also holds the type of the value (see `value_type`).
+	fn print_values(stream: &str, format: &str, values: &[(String, RegType)], options: &Options) -> String {
+		let pieces: Vec<&str> = format.split("{}").collect();
+		let flush = if stream == "stderr" { "fflush(stdout); " } else { "" };
+		if options.width == Width::I128 {
+			// printf has no conversion for 128-bit integers, so the values go through the helper declared by `gen_c::prelude`
+			let mut statements: Vec<String> = Vec::new();
+			for (index, piece) in pieces.iter().enumerate() {
+				if !piece.is_empty() {
+					statements.push(format!("fputs({}, {});", c_string(piece), stream));
+				}
+				if let Some(&(ref val, _)) = values.get(index) {
+					statements.push(format!("__asmb_print({}, {});", stream, val));
+				}
+			}
+			return format!("{{ {}{} }}", flush, statements.join(" "));
+		}
+		let mut format = String::new();
+		let mut args = String::new();
+		for (index, piece) in pieces.iter().enumerate() {
+			format += &piece.replace('%', "%%");
+			if let Some(&(ref val, reg_type)) = values.get(index) {
+				let (conversion, cast) = match (reg_type, options.width) {
+					(RegType::Int, Width::I32) => ("%d", ""),
+					(RegType::U64, _) => ("%llu", "(unsigned long long) "),
+					_ => ("%lld", "(long long) ")
+				};
+				format += conversion;
+				args += &format!(", {}{}", cast, val);
+			}
+		}
+		if stream == "stdout" {
+			format!("printf({}{});", c_string(&format), args)
 		} else {
-			linenum + (offset as u32)
-		}))
+			format!("{}fprintf({}, {}{});", flush, stream, c_string(&format), args)
+		}
 	}
 
-	pub fn out(args: &Vec<&str>) -> String {
+	pub fn out(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
 		// Syntax: out <eval>
-		format!("printf(\"%d \", {});", eval!(args[1]))
+		print_values("stdout", "{} ", &[(eval!(args[1]), value_type(args[1], context))], options)
 	}
 
-	pub fn outn(args: &Vec<&str>) -> String {
+	pub fn outn(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
 		// Syntax: outn <eval>
-		format!("printf(\"%d\\n\", {});", eval!(args[1]))
+		print_values("stdout", "{}\n", &[(eval!(args[1]), value_type(args[1], context))], options)
 	}
 
-	pub fn outc(args: &Vec<&str>) -> String {
+	pub fn outc(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
 		// Syntax: outc <eval>
-		// NOTE: Does not support Unicode, because C doesn't
-		format!("printf(\"%c\", {});", eval!(args[1]))
+		// Char codes are checked like the interpreter does, and printed in UTF-8 by the helper declared by `gen_c::prelude`
+		let reg_type = value_type(args[1], context);
+		let message = |problem: &str| print_values("stderr", &format!("{}: Char code ({{}}) {}\n", context.origin, problem), &[("__asmb_c".to_owned(), reg_type)], options);
+		format!("{{ {} __asmb_c = {}; if (__asmb_c < 0) {} if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) {} __asmb_outc((uint32_t) __asmb_c); }}",
+			gen_c::c_type(reg_type), eval!(args[1]), fault("outc", format!("{{ {} return 1; }}", message("should not be less than zero")), context),
+			fault("outc", format!("{{ {} return 1; }}", message("is invalid")), context))
 	}
+
+	pub fn outs(args: &Vec<&str>) -> String {
+		// Syntax: outs <string literal>
+		// Written with its length, since fputs would stop at a \0 in the string
+		let string = parser::string_literal(args[1]).unwrap();
+		format!("fwrite({}, 1, {}, stdout);", c_string(&string), string.len())
+	}
+
+	/// Returns a C string literal with the given contents.
+	/// Everything else than printable ASCII and the escape sequences of ASMB+ is written as an octal escape, so the C program prints the same UTF-8 bytes as the interpreter.
+	pub fn c_string(text: &str) -> String {
+		let mut output = "\"".to_owned();
+		for &byte in text.as_bytes() {
+			match byte {
+				b'"' | b'\\' => { output.push('\\'); output.push(byte as char); },
+				b'\n' => output += "\\n",
+				b'\t' => output += "\\t",
+				b'\r' => output += "\\r",
+				_ if byte >= b' ' && byte <= b'~' => output.push(byte as char),
+				_ => output += &format!("\\{:03o}", byte)
+			}
+		}
+		output + "\""
+	}
+
+	pub fn hlt(args: &Vec<&str>) -> String {
+		// Syntax: hlt [eval]
+		format!("return (int) {};", if args.len() > 1 { eval!(args[1]) } else { "0".to_owned() })
+	}
+
+	pub fn rnd(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: rnd <reg name> <eval> <eval>
+		// The size of the range is computed in unsigned arithmetic, which is exact since the lowest value is not greater than the highest
+		let reg_type = value_type(args[1], context);
+		let (t, u, print_type) = match reg_type {
+			RegType::Int => (gen_c::INT_TYPE, gen_c::UINT_TYPE, RegType::Int),
+			_ if reg_type.signed() => ("int64_t", "uint64_t", RegType::I64),
+			_ => ("uint64_t", "uint64_t", RegType::U64)
+		};
+		let error = print_values("stderr", &format!("{}: Empty range of RND ({{}} is greater than {{}})\n", context.origin),
+			&[("__asmb_low".to_owned(), print_type), ("__asmb_high".to_owned(), print_type)], options);
+		format!("{{ {t} __asmb_low = {}, __asmb_high = {}; if (__asmb_low > __asmb_high) {{ {} return 1; }} {} = ({}) (({u}) __asmb_low + ({u}) __asmb_random(({u}) __asmb_high - ({u}) __asmb_low)); }}",
+			eval!(args[2]), eval!(args[3]), error, reg!(args[1]), gen_c::c_type(reg_type), t = t, u = u)
+	}
+
+	pub fn seed(args: &Vec<&str>) -> String {
+		// Syntax: seed <eval>
+		format!("__asmb_seed = (uint64_t) {};", eval!(args[1]))
+	}
+
+	/// Returns C code that declares `name` with the value of `arg`, followed by a statement that stops the program with `error` if the value is not between 0 and `max`.
+	/// `error` is a format for `print_values`, with `{}` standing for the value.
+	fn byte_operand(name: &str, arg: &str, max: usize, error: &str, context: &gen_c::LineContext, options: &Options) -> String {
+		let (t, print_type) = match value_type(arg, context) {
+			RegType::Int => (gen_c::INT_TYPE, RegType::Int),
+			RegType::U64 => ("uint64_t", RegType::U64),
+			_ => ("int64_t", RegType::I64)
+		};
+		let print = print_values("stderr", &format!("{}: {}\n", context.origin, error), &[(name.to_owned(), print_type)], options);
+		format!("{} {} = {}; if ({} < 0 || {} > {}) {{ {} return 1; }}", t, name, eval!(arg), name, name, max, print)
+	}
+
+	pub fn outp(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: outp <eval> <eval>
+		// The ports are written to by the helper declared by `gen_c::prelude`, which prints its own errors
+		format!("{{ {} {} if (!__asmb_outp({}, (int) __asmb_port, (int) __asmb_byte)) return 1; }}",
+			byte_operand("__asmb_port", args[1], PORT_COUNT - 1, &format!("Port {{}} does not exist (ports are numbered from 0 to {})", PORT_COUNT - 1), context, options),
+			byte_operand("__asmb_byte", args[2], 255, "Value {} does not fit in a byte", context, options), c_string(context.origin))
+	}
+
+	pub fn inp(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: inp <reg name> <eval>
+		format!("{{ {} int __asmb_input; if (!__asmb_inp({}, (int) __asmb_port, &__asmb_input)) return 1; {} = __asmb_input; }}",
+			byte_operand("__asmb_port", args[2], PORT_COUNT - 1, &format!("Port {{}} does not exist (ports are numbered from 0 to {})", PORT_COUNT - 1), context, options),
+			c_string(context.origin), reg!(args[1]))
+	}
+
+	pub fn assert(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: assert <eval> <operator> <eval> [string literal]
+		if !options.asserts {
+			return "/* Assertion skipped (--no-asserts) */;".to_owned();
+		}
+		let dump = if context.regs.is_empty() {
+			"fputs(\"Registers: (none)\\n\", stderr);".to_owned()
+		} else {
+			print_values("stderr", &format!("Registers: {}\n", context.regs.iter().map(|name| format!("{} = {{}}", name)).collect::<Vec<_>>().join(", ")),
+				&context.regs.iter().map(|name| (reg!(name), value_type(name, context))).collect::<Vec<_>>(), options)
+		};
+		format!("if (!({} {} {})) {{ fflush(stdout); fputs({}, stderr); {} return 1; }}", eval!(args[1]), args[2], eval!(args[3]),
+			c_string(&format!("{}: Assertion failed: {}\n", context.origin, context.description)), dump)
+	}
+
+	pub fn inn(args: &Vec<&str>, context: &gen_c::LineContext) -> String {
+		// Syntax: inn <reg name>
+		// Read by the helper declared by `gen_c::prelude`, so out-of-range integers are reported instead of being undefined behavior
+		let error = runtime_error("Expected an integer from STDIN", context);
+		if value_type(args[1], context) == RegType::Int {
+			return format!("if (!__asmb_read(&{})) {}", reg!(args[1]), error);
+		}
+		// Registers with a declared type get the integer converted to their type
+		format!("{{ {} __asmb_input; if (!__asmb_read(&__asmb_input)) {} {} = __asmb_input; }}", gen_c::INT_TYPE, error, reg!(args[1]))
+	}
+
+	pub fn inch(args: &Vec<&str>) -> String {
+		// Syntax: inch <reg name>
+		// NOTE: getchar() returns EOF (-1) at the end of STDIN, which matches the interpreter
+		format!("{} = getchar();", reg!(args[1]))
+	}
+}
+
+/// Collection of functions that generate C code for lines whose behavior differs in dispatch mode.
+/// In dispatch mode, each line is a `case` of a `switch` over `IP_VARNAME` instead of a labelled statement.
+pub mod dispatch_generators {
+	use gen_c;
+	use parser;
+	use options::Options;
+
+	pub fn def(args: &Vec<&str>) -> String {
+		// Syntax: def <new reg name> <eval>
+		// The register itself is declared at the top of main(), because this line may be toggled.
+		format!("{} = {};", reg!(args[1]), eval!(args[2]))
+	}
+
+	pub fn jump(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: jnz <eval not 0> <eval>, jeq <eval> <eval> <eval>, etc.
+		format!("if ({}) {{ {} += {}; {} continue; }}", gen_c::generators::jump_condition(args).unwrap(), gen_c::IP_VARNAME, eval!(args[args.len() - 1]),
+			gen_c::generators::check_jump_target(gen_c::IP_VARNAME, context, options))
+	}
+
+	pub fn call(args: &Vec<&str>, context: &gen_c::LineContext, options: &Options) -> String {
+		// Syntax: call <eval>
+		format!("{{ {} {} += {}; {} continue; }}", gen_c::generators::push_return_address(&format!("{} + 1", gen_c::IP_VARNAME), context),
+			gen_c::IP_VARNAME, eval!(args[1]), gen_c::generators::check_jump_target(gen_c::IP_VARNAME, context, options))
+	}
+
+	pub fn ret(_args: &Vec<&str>, context: &gen_c::LineContext) -> String {
+		// Syntax: ret
+		format!("{{ {} continue; }}", gen_c::generators::pop_return_address(gen_c::IP_VARNAME, context))
+	}
+
+	pub fn tgl(args: &Vec<&str>, linecount: u32) -> String {
+		// Syntax: tgl <eval>
+		format!("{{ int64_t __asmb_target = {ip} + {}; if (__asmb_target >= 1 && __asmb_target <= {}) {form}[__asmb_target] = {form}[__asmb_target] == 1 ? 2 : 1; }}",
+			eval!(args[1]), linecount, ip = gen_c::IP_VARNAME, form = gen_c::FORM_ARRAY)
+	}
+}
+
+/// What a generator may need to know about a line of ASMB+ besides its tokens.
+pub struct LineContext<'a> {
+	/// Number of the line among the lines that have something to execute, starting at 1
+	pub linenum: u32,
+	/// Amount of lines that have something to execute
+	pub linecount: u32,
+	/// Where the line comes from, such as "Line 3" (see parser::line_origin)
+	pub origin: &'a str,
+	/// Registers that are declared (in C) where the line is
+	pub regs: &'a [&'a str],
+	/// Type of every register of the program
+	pub types: &'a HashMap<String, RegType>,
+	/// Procedures of the program (see parser::procedures)
+	pub procedures: &'a [parser::Procedure],
+	/// Kinds of runtime errors that TRAP may hand to a procedure (see `trapped_kinds`)
+	pub traps: &'a [&'a str],
+	/// Text of the line for the message of a failed ASSERT (see parser::assert_description)
+	pub description: &'a str,
 }
 
 /// Returns a line of C source code from a line of ASMB+.
-pub fn get_cline(toks: &Vec<&str>, linenum: u32) -> Result<String, String> {
+pub fn get_cline(toks: &Vec<&str>, context: &LineContext, options: &Options) -> Result<String, String> {
 	// Execution worth is already checked at compose().
 
 	// Line checked and invalid
@@ -163,33 +789,404 @@ pub fn get_cline(toks: &Vec<&str>, linenum: u32) -> Result<String, String> {
 	}
 
 	match toks[0].to_lowercase().as_str() {
-		"def" => Ok(generators::def(toks)),
-		"inc" => Ok(generators::inc(toks)),
-		"inct" => Ok(generators::inct(toks)),
-		"dec" => Ok(generators::dec(toks)),
-		"dect" => Ok(generators::dect(toks)),
-		"mul" => Ok(generators::mul(toks)),
-		"div" => Ok(generators::div(toks)),
+		"def" => Ok(generators::def(toks, context)),
+		"inc" => Ok(generators::inc(toks, context, options)),
+		"inct" => Ok(generators::inct(toks, context, options)),
+		"dec" => Ok(generators::dec(toks, context, options)),
+		"dect" => Ok(generators::dect(toks, context, options)),
+		"mul" => Ok(generators::mul(toks, context, options)),
+		"div" => Ok(generators::div(toks, context, options)),
 		"cpy" => Ok(generators::cpy(toks)),
-		"jnz" => Ok(generators::jnz(toks, linenum)),
-		"out" => Ok(generators::out(toks)),
-		"outn" => Ok(generators::outn(toks)),
-		"outc" => Ok(generators::outc(toks)),
+		"mod" => Ok(generators::mod_(toks, context, options)),
+		"and" => Ok(generators::and(toks)),
+		"or" => Ok(generators::or(toks)),
+		"xor" => Ok(generators::xor(toks)),
+		"shl" => Ok(generators::shl(toks, context, options)),
+		"shr" => Ok(generators::shr(toks, context, options)),
+		"neg" => Ok(generators::neg(toks, context, options)),
+		"jnz" | "jz" | "jeq" | "jne" | "jlt" | "jgt" => Ok(generators::jump(toks, context, options)),
+		"call" => Ok(generators::call(toks, context, options)),
+		"proc" => Ok(generators::proc_(toks, context, options)),
+		"endp" | "return" => Ok(generators::return_(toks, context, options)),
+		"ret" => Ok(generators::ret(toks, context)),
+		"push" => Ok(generators::push(toks, context, options)),
+		"pop" => Ok(generators::pop(toks, context)),
+		"peek" => Ok(generators::peek(toks, context)),
+		"mem" => Ok(generators::mem(toks)),
+		"load" => Ok(generators::load(toks, context, options)),
+		"store" => Ok(generators::store(toks, context, options)),
+		"out" => Ok(generators::out(toks, context, options)),
+		"outn" => Ok(generators::outn(toks, context, options)),
+		"outc" => Ok(generators::outc(toks, context, options)),
+		"outs" => Ok(generators::outs(toks)),
+		"inn" => Ok(generators::inn(toks, context)),
+		"inch" => Ok(generators::inch(toks)),
+		"hlt" => Ok(generators::hlt(toks)),
+		"assert" => Ok(generators::assert(toks, context, options)),
+		"rnd" => Ok(generators::rnd(toks, context, options)),
+		"seed" => Ok(generators::seed(toks)),
+		"outp" => Ok(generators::outp(toks, context, options)),
+		"inp" => Ok(generators::inp(toks, context, options)),
+		"trap" => Ok(generators::trap(toks, context)),
 		_ => Err(format!("Unknown keyword: {}", toks[0]))
 	}
 }
 
-/// Returns the entire C program, ready to be written to a file.
-pub fn compose(clines: &Vec<&str>) -> Result<String, String> {
+/// Returns the kinds of runtime errors (see parser::TRAP_KINDS) that the given lines hand to a procedure with TRAP and that some line can run into.
+/// Only these kinds check for a handler, so that a program without TRAP compiles to the same C as before.
+fn trapped_kinds(lines: &Vec<Vec<&str>>) -> Vec<&'static str> {
+	let uses = |keywords: &[&str]| lines.iter().any(|tokens| keywords.contains(&tokens[0].to_lowercase().as_str()));
+	parser::TRAP_KINDS.iter().cloned().filter(|&kind| {
+		let sites: &[&str] = match kind {
+			"div" => &["div", "mod"],
+			"outc" => &["outc"],
+			"stack" => &["pop", "peek"],
+			_ => &["load", "store"]
+		};
+		uses(sites) && lines.iter().any(|tokens| tokens[0].to_lowercase() == "trap" && tokens[1] == kind)
+	}).collect()
+}
+
+/// Returns declarations of the runtime state (other than registers) that the given lines need at the top of main().
+fn runtime_declarations(lines: &Vec<Vec<&str>>, options: &Options) -> String {
+	let uses = |keywords: &[&str]| lines.iter().any(|tokens| keywords.contains(&tokens[0].to_lowercase().as_str()));
+	let mut decls = String::new();
+	if uses(&["call", "ret", "endp", "return"]) {
+		decls += &format!("{i}int64_t {}[{}];\n{i}int32_t {} = 0;\n", CALL_STACK_ARRAY, interpret::CALL_STACK_LIMIT, CALL_DEPTH_VARNAME, i = INDENT);
+	}
+	if uses(&["push", "pop", "peek", "return"]) {
+		// Static, since a large stack may not fit in main()'s stack frame
+		decls += &format!("{i}static {} {}[{}];\n{i}int64_t {} = 0;\n", INT_TYPE, STACK_ARRAY, ::std::cmp::max(options.stack_limit, 1), STACK_SIZE_VARNAME, i = INDENT);
+	}
+	for kind in trapped_kinds(lines) {
+		decls += &format!("{}int64_t {}{} = 0;\n", INDENT, TRAP_VARNAME_PREFIX, kind);
+	}
+	for tokens in lines.iter().filter(|tokens| tokens[0].to_lowercase() == "mem") {
+		// Static, since a large buffer may not fit in main()'s stack frame; static arrays also start out filled with zeros
+		decls += &format!("{}static {} {}{}[{}];\n", INDENT, INT_TYPE, MEM_VARNAME_PREFIX, tokens[1], tokens[2]);
+	}
+	decls
+}
+
+/// Returns declarations of the local registers of the given procedures and of their frames, which go at the top of main().
+/// `types` holds the type of every register defined by DEF; parameters are plain registers.
+fn procedure_declarations(procedures: &[parser::Procedure], types: &HashMap<String, RegType>) -> String {
+	if procedures.is_empty() {
+		return String::new();
+	}
+	let mut decls = format!("{i}{} {}[{}];\n{i}int {} = 0;\n{i}int64_t {} = 0;\n", INT_TYPE, ARGS_ARRAY, parser::PROCEDURE_PARAM_LIMIT,
+		ARG_COUNT_VARNAME, CALLEE_VARNAME, i = INDENT);
+	for (index, procedure) in procedures.iter().enumerate() {
+		for (offset, local) in procedure.locals.iter().enumerate() {
+			let name = procedure.local_name(local).unwrap();
+			let local_type = c_type(types.get(&name).cloned().unwrap_or(RegType::Int));
+			decls += &format!("{}{} {} = 0;\n", INDENT, local_type, c_register(&name));
+			// Static, since the frames may not fit in main()'s stack frame
+			decls += &format!("{}static {} {}{}_{}[{}];\n", INDENT, local_type, FRAMES_ARRAY_PREFIX, index, offset, interpret::CALL_STACK_LIMIT);
+		}
+		decls += &format!("{}int32_t {}{} = 0;\n", INDENT, FRAME_DEPTH_PREFIX, index);
+	}
+	decls
+}
+
+/// Returns the declarations that go before main(): the integer types for the register width, and the helper functions the given lines need.
+fn prelude(lines: &Vec<Vec<&str>>, options: &Options) -> String {
+	let (int, uint) = match options.width {
+		Width::I32 => ("int32_t", "uint32_t"),
+		Width::I64 => ("int64_t", "uint64_t"),
+		Width::I128 => ("__int128", "unsigned __int128"),
+		Width::Big => unreachable!("compose refuses big registers")
+	};
+	let mut prelude = format!("typedef {} {};\ntypedef {} {};\n\n", int, INT_TYPE, uint, UINT_TYPE);
+	if options.width == Width::I128 {
+		// Prints a value in decimal, since printf cannot
+		prelude += &format!("static inline void __asmb_print(FILE *stream, {t} val) {{\n{i}char digits[40];\n{i}int length = 0;\n{i}{u} magnitude = val < 0 ? -({u}) val : ({u}) val;\n{i}do {{\n{i}{i}digits[length++] = (char) ('0' + (int) (magnitude % 10));\n{i}{i}magnitude /= 10;\n{i}}} while (magnitude != 0);\n{i}if (val < 0) fputc('-', stream);\n{i}while (length > 0) fputc(digits[--length], stream);\n}}\n\n",
+			t = INT_TYPE, u = UINT_TYPE, i = INDENT);
+	}
+	if lines.iter().any(|tokens| tokens[0].to_lowercase() == "inn") {
+		// Reads an integer the way the interpreter does: whitespace is skipped, then an optionally signed decimal integer that has to fit in the register width
+		prelude += &format!("static inline int __asmb_read({t} *out) {{\n{i}int ch, negative = 0, digits = 0;\n{i}{t} val = 0;\n{i}fflush(stdout);\n{i}do ch = getchar(); while (ch == ' ' || (ch >= '\\t' && ch <= '\\r'));\n{i}if (ch == '-' || ch == '+') {{\n{i}{i}negative = ch == '-';\n{i}{i}ch = getchar();\n{i}}}\n{i}for (; ch >= '0' && ch <= '9'; ch = getchar(), ++digits) {{\n{i}{i}if (__builtin_mul_overflow(val, 10, &val) || (negative ? __builtin_sub_overflow(val, ch - '0', &val) : __builtin_add_overflow(val, ch - '0', &val))) return 0;\n{i}}}\n{i}if (ch != EOF) ungetc(ch, stdin);\n{i}*out = val;\n{i}return digits > 0;\n}}\n\n",
+			t = INT_TYPE, i = INDENT);
+	}
+	if lines.iter().any(|tokens| tokens[0].to_lowercase() == "outc") {
+		// Prints a char code in UTF-8 like the interpreter does, since printf("%c") only prints one byte
+		prelude += &format!("static inline void __asmb_outc(uint32_t code) {{\n{i}if (code < 0x80) {{\n{i}{i}putchar((int) code);\n{i}}} else if (code < 0x800) {{\n{i}{i}putchar((int) (0xC0 | code >> 6));\n{i}{i}putchar((int) (0x80 | (code & 0x3F)));\n{i}}} else if (code < 0x10000) {{\n{i}{i}putchar((int) (0xE0 | code >> 12));\n{i}{i}putchar((int) (0x80 | (code >> 6 & 0x3F)));\n{i}{i}putchar((int) (0x80 | (code & 0x3F)));\n{i}}} else {{\n{i}{i}putchar((int) (0xF0 | code >> 18));\n{i}{i}putchar((int) (0x80 | (code >> 12 & 0x3F)));\n{i}{i}putchar((int) (0x80 | (code >> 6 & 0x3F)));\n{i}{i}putchar((int) (0x80 | (code & 0x3F)));\n{i}}}\n}}\n\n",
+			i = INDENT);
+	}
+	if lines.iter().any(|tokens| ["rnd", "seed"].contains(&tokens[0].to_lowercase().as_str())) {
+		// The SplitMix64 generator of interpret::next_random, and the offset into a range of RND that is `range` + 1 values long
+		prelude += &format!("static uint64_t __asmb_seed = {}ULL;\n\nstatic inline uint64_t __asmb_next(void) {{\n{i}uint64_t z = (__asmb_seed += 0x9E3779B97F4A7C15ULL);\n{i}z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ULL;\n{i}z = (z ^ (z >> 27)) * 0x94D049BB133111EBULL;\n{i}return z ^ (z >> 31);\n}}\n\n",
+			options.seed, i = INDENT);
+		if options.width == Width::I128 {
+			// Ranges of more than 2^64 values take two numbers, the first one as the high half
+			prelude += &format!("static inline unsigned __int128 __asmb_random(unsigned __int128 range) {{\n{i}unsigned __int128 draw;\n{i}if (range <= UINT64_MAX) {{\n{i}{i}draw = __asmb_next();\n{i}{i}return range == UINT64_MAX ? draw : draw % (range + 1);\n{i}}}\n{i}draw = (unsigned __int128) __asmb_next() << 64;\n{i}draw |= __asmb_next();\n{i}return range == ~(unsigned __int128) 0 ? draw : draw % (range + 1);\n}}\n\n",
+				i = INDENT);
+		} else {
+			prelude += &format!("static inline uint64_t __asmb_random(uint64_t range) {{\n{i}uint64_t draw = __asmb_next();\n{i}return range == UINT64_MAX ? draw : draw % (range + 1);\n}}\n\n",
+				i = INDENT);
+		}
+	}
+	let (uses_outp, uses_inp) = (lines.iter().any(|tokens| tokens[0].to_lowercase() == "outp"), lines.iter().any(|tokens| tokens[0].to_lowercase() == "inp"));
+	if uses_outp || uses_inp {
+		// The ports of interpret::AsmbiState: each one is unbound (0), stdin (1), stdout (2), stderr (3), a file (4), or a memory buffer (5), which is a temporary file with its own read position.
+		// Files are opened when their port is first used, which decides whether the port writes or reads.
+		let mut kinds: Vec<String> = Vec::new();
+		let mut paths: Vec<String> = Vec::new();
+		for (&port, target) in options.ports.iter() {
+			kinds.push(format!("[{}] = {}", port, match *target {
+				Port::Stdin => 1,
+				Port::Stdout => 2,
+				Port::Stderr => 3,
+				Port::File(ref path) => {
+					paths.push(format!("[{}] = {}", port, generators::c_string(path)));
+					4
+				},
+				Port::Memory => 5
+			}));
+		}
+		prelude += &format!("static const unsigned char __asmb_port_kinds[{n}] = {{ {} }};\nstatic const char *const __asmb_port_paths[{n}] = {{ {} }};\nstatic FILE *__asmb_port_files[{n}];\nstatic int __asmb_port_writing[{n}];\n\n",
+			if kinds.is_empty() { "0".to_owned() } else { kinds.join(", ") }, if paths.is_empty() { "0".to_owned() } else { paths.join(", ") }, n = PORT_COUNT);
+		prelude += &format!("static FILE *__asmb_port_stream(const char *origin, int port, int writing) {{\n{i}FILE *stream = NULL;\n{i}switch (__asmb_port_kinds[port]) {{\n{i}case 0:\n{i}{i}fflush(stdout);\n{i}{i}fprintf(stderr, \"%s: Port %d is not bound to anything (see --port)\\n\", origin, port);\n{i}{i}return NULL;\n{i}case 1:\n{i}{i}stream = writing ? NULL : stdin;\n{i}{i}break;\n{i}case 2:\n{i}{i}stream = writing ? stdout : NULL;\n{i}{i}break;\n{i}case 3:\n{i}{i}stream = writing ? stderr : NULL;\n{i}{i}break;\n{i}case 4:\n{i}{i}if (!__asmb_port_files[port]) {{\n{i}{i}{i}__asmb_port_files[port] = fopen(__asmb_port_paths[port], writing ? \"wb\" : \"rb\");\n{i}{i}{i}if (!__asmb_port_files[port]) {{\n{i}{i}{i}{i}fflush(stdout);\n{i}{i}{i}{i}fprintf(stderr, \"%s: Cannot open '%s' for %s (port %d)\\n\", origin, __asmb_port_paths[port], writing ? \"writing\" : \"reading\", port);\n{i}{i}{i}{i}return NULL;\n{i}{i}{i}}}\n{i}{i}{i}__asmb_port_writing[port] = writing;\n{i}{i}}}\n{i}{i}if (__asmb_port_writing[port] == writing) stream = __asmb_port_files[port];\n{i}{i}break;\n{i}default:\n{i}{i}if (!__asmb_port_files[port] && !(__asmb_port_files[port] = tmpfile())) {{\n{i}{i}{i}fflush(stdout);\n{i}{i}{i}fprintf(stderr, \"%s: Failed to %s port %d\\n\", origin, writing ? \"write to\" : \"read from\", port);\n{i}{i}{i}return NULL;\n{i}{i}}}\n{i}{i}return __asmb_port_files[port];\n{i}}}\n{i}if (!stream) {{\n{i}{i}fflush(stdout);\n{i}{i}fprintf(stderr, \"%s: Port %d cannot be %s\\n\", origin, port, writing ? \"written to\" : \"read from\");\n{i}}}\n{i}return stream;\n}}\n\n",
+			i = INDENT);
+	}
+	if uses_outp {
+		prelude += &format!("static int __asmb_outp(const char *origin, int port, int byte) {{\n{i}FILE *stream = __asmb_port_stream(origin, port, 1);\n{i}if (!stream) return 0;\n{i}if (__asmb_port_kinds[port] == 5) fseek(stream, 0, SEEK_END);\n{i}if (fputc(byte, stream) == EOF) {{\n{i}{i}fflush(stdout);\n{i}{i}fprintf(stderr, \"%s: Failed to write to port %d\\n\", origin, port);\n{i}{i}return 0;\n{i}}}\n{i}return 1;\n}}\n\n",
+			i = INDENT);
+	}
+	if uses_inp {
+		// -1 at the end of the port, like INCH
+		prelude += &format!("static long __asmb_port_read_pos[{}];\n\nstatic int __asmb_inp(const char *origin, int port, int *byte) {{\n{i}FILE *stream = __asmb_port_stream(origin, port, 0);\n{i}if (!stream) return 0;\n{i}if (__asmb_port_kinds[port] == 5) fseek(stream, __asmb_port_read_pos[port], SEEK_SET);\n{i}*byte = fgetc(stream);\n{i}if (*byte == EOF && ferror(stream)) {{\n{i}{i}fflush(stdout);\n{i}{i}fprintf(stderr, \"%s: Failed to read from port %d\\n\", origin, port);\n{i}{i}return 0;\n{i}}}\n{i}if (*byte != EOF && __asmb_port_kinds[port] == 5) ++__asmb_port_read_pos[port];\n{i}return 1;\n}}\n\n",
+			PORT_COUNT, i = INDENT);
+	}
+	prelude
+}
+
+/// Returns the names of the memory buffers declared by the given lines, or an error if a name is declared twice or the size is not positive.
+fn mem_buffers<'a>(lines: &Vec<Vec<&'a str>>) -> Result<Vec<&'a str>, String> {
+	let mut buffers: Vec<&str> = Vec::new();
+	for tokens in lines.iter().filter(|tokens| tokens[0].to_lowercase() == "mem") {
+		if buffers.contains(&tokens[1]) {
+			return Err(format!("mem {}: Memory buffer name already exists", tokens[1]));
+		}
+		let size = parser::is_literal(tokens[2]).unwrap();
+		if size <= 0 || size > u32::max_value() as i128 {
+			return Err(format!("mem {}: Memory buffer size should be between 1 and {}", tokens[1], u32::max_value()));
+		}
+		buffers.push(tokens[1]);
+	}
+	Ok(buffers)
+}
+
+/// Returns a line of C source code for one form of a line in dispatch mode.
+/// `toks` may have been toggled into an invalid instruction, in which case the line does nothing.
+/// `buffers` holds the names of the program's memory buffers.
+fn get_dispatch_cline(toks: &Vec<&str>, context: &LineContext, buffers: &[&str], options: &Options) -> Result<String, String> {
+	let keyword = toks[0].to_lowercase();
+	if !parser::params_valid(toks, buffers) {
+		return Ok("/* Invalid instruction, skipped */;".to_owned());
+	}
+	match keyword.as_str() {
+		"def" => Ok(dispatch_generators::def(toks)),
+		"jnz" | "jz" | "jeq" | "jne" | "jlt" | "jgt" => Ok(dispatch_generators::jump(toks, context, options)),
+		"call" => Ok(dispatch_generators::call(toks, context, options)),
+		"ret" => Ok(dispatch_generators::ret(toks, context)),
+		"tgl" => Ok(dispatch_generators::tgl(toks, context.linecount)),
+		_ => get_cline(toks, context, options)
+	}
+}
+
+/// Returns the entire C program for a program that modifies itself with TGL, which static `goto` labels cannot express.
+/// Every line becomes a `case` of a `switch` over the current line number, containing one `case` per form the line can be toggled into.
+/// `origins` names each line in runtime errors, and `descriptions` holds the text of each line for failed ASSERTs (see parser::assert_description).
+/// `types` holds the type of every register.
+fn compose_dispatch(lines: &Vec<Vec<&str>>, origins: &[String], descriptions: &[String], types: &HashMap<String, RegType>, options: &Options) -> Result<String, String> {
+	let linecount = lines.len() as u32;
+	let buffers = try!(mem_buffers(lines));
+	// Every register is declared at the top of main()
+	let regs: Vec<&str> = lines.iter().filter(|tokens| tokens[0].to_lowercase() == "def").map(|tokens| tokens[1]).collect();
+	// Registers defined by the lines so far, which are the ones the interpreter knows of on each line
+	let mut defined: Vec<&str> = Vec::new();
 	let mut infix = String::new();
-	let mut linenum = 1;
-	for line in clines.iter() {
-		let tokens = parser::tokenize_line(line);
+	let mut cases = String::new();
+
+	for (index, tokens) in lines.iter().enumerate() {
+		if let Err(err) = parser::line_valid(tokens) {
+			return Err(format!("Invalid line: {}", err));
+		}
+		try_err_fallthru!(parser::registers_defined(tokens, |name| defined.contains(&name)), format!("{}: ", origins[index]));
+		let keyword = tokens[0].to_lowercase();
+		if keyword == "def" {
+			infix += &format!("{}{} {}{} = 0;\n", INDENT, c_type(types[tokens[1]]), REG_VARNAME_PREFIX, tokens[1]);
+			defined.push(tokens[1]);
+		}
+
+		// A line toggled twice is the same as the original line unless the first toggle changed its keyword for good (such as `out` to `inc`)
+		let once = parser::toggled_keyword(&keyword, tokens.len() - 1);
+		let twice = parser::toggled_keyword(once, tokens.len() - 1);
+		let mut forms = vec![(if twice == keyword { "case 0: case 2:" } else { "case 0:" }, keyword.as_str()), ("case 1:", once)];
+		if twice != keyword {
+			forms.push(("case 2:", twice));
+		}
+
+		let context = LineContext {
+			linenum: index as u32 + 1,
+			linecount: linecount,
+			origin: &origins[index],
+			regs: &regs,
+			types: types,
+			procedures: &[],
+			traps: &[],
+			description: &descriptions[index],
+		};
+		cases += &format!("{i}{i}case {}:\n{i}{i}{i}switch ({}[{}]) {{\n", index + 1, FORM_ARRAY, index + 1, i = INDENT);
+		for (label, form_keyword) in forms {
+			let mut form = tokens.clone();
+			form[0] = form_keyword;
+			cases += &format!("{i}{i}{i}{}\n{i}{i}{i}{i}{}\n{i}{i}{i}{i}break;\n", label,
+				try!(get_dispatch_cline(&form, &context, &buffers, options)), i = INDENT);
+		}
+		cases += &format!("{i}{i}{i}}}\n{i}{i}{i}break;\n", i = INDENT);
+	}
+
+	infix += &runtime_declarations(lines, options);
+	infix += &format!("{i}int64_t {ip} = 1;\n{i}uint8_t {}[{}] = {{0}};\n", FORM_ARRAY, linecount + 1, i = INDENT, ip = IP_VARNAME);
+	infix += &format!("{i}while ({ip} >= 1 && {ip} <= {}) {{\n{i}{i}switch ({ip}) {{\n{}{i}{i}}}\n{i}{i}++{ip};\n{i}}}\n",
+		linecount, cases, i = INDENT, ip = IP_VARNAME);
+	Ok(C_PROTOTYPE.to_owned().replace("@@", &prelude(lines, options)).replace("##", &infix))
+}
+
+/// Returns the entire C program, ready to be written to a file.
+/// `origins` names the lines in error messages (see parser::line_origin).
+pub fn compose(clines: &Vec<&str>, origins: &[String], options: &Options) -> Result<String, String> {
+	if options.width == Width::Big {
+		return Err("Big registers (--width big) are only supported by the interpreter and the bytecode runner, not by the C compiler".to_owned());
+	}
+	let labels = try!(parser::collect_labels(clines, origins));
+	let procedures = try!(parser::procedures(clines, origins));
+	let mut unresolved: Vec<Vec<&str>> = Vec::new();
+	// Origin of each line in `unresolved`
+	let mut line_origins: Vec<String> = Vec::new();
+	for (index, line) in clines.iter().enumerate() {
+		let tokens = parser::split_label(&parser::tokenize_line(line)).1;
 		if parser::worth_execution(&tokens).is_ok() {
-			infix += &format!("{}{}:;\n{}{}\n", LINE_LABEL_PREFIX, linenum, INDENT,
-				try!(get_cline(&tokens, linenum as u32)));
-			linenum += 1;
+			unresolved.push(tokens);
+			line_origins.push(parser::line_origin(origins, index));
+		}
+	}
+	// Constant expressions are folded and labels are resolved to the same offsets the interpreter sees
+	let mut resolved: Vec<Vec<String>> = Vec::new();
+	// Text of each line for failed ASSERTs, before its labels are resolved and its locals renamed
+	let mut descriptions: Vec<String> = Vec::new();
+	for (index, tokens) in unresolved.iter().enumerate() {
+		try_err_fallthru!(parser::call_valid(tokens, &procedures), format!("{}: ", line_origins[index]));
+		try_err_fallthru!(parser::trap_valid(tokens, &procedures), format!("{}: ", line_origins[index]));
+		// C finds the locals of a procedure by the line they are used on, so the index of the first one does not matter
+		let procedure_toks = match parser::procedure_at(&procedures, index as u32) {
+			Some(procedure) if tokens[0].to_lowercase() == "proc" => parser::procedure_tokens(procedure, 0),
+			_ => Vec::new()
+		};
+		let tokens = if procedure_toks.is_empty() { tokens.clone() } else { procedure_toks.iter().map(|tok| tok.as_str()).collect() };
+		let folded = try_err_fallthru!(parser::fold_constants(&tokens, options.width), format!("{}: ", line_origins[index]));
+		let tokens: Vec<&str> = folded.iter().map(|tok| tok.as_str()).collect();
+		try_err_fallthru!(parser::literals_fit(&tokens, options.width), format!("{}: ", line_origins[index]));
+		if let Err(err) = parser::line_valid(&tokens) {
+			return Err(format!("Invalid line: {}", err));
+		}
+		descriptions.push(parser::assert_description(&tokens));
+		resolved.push(parser::qualify_locals(try!(parser::resolve_labels(&tokens, &labels, index as u32)), parser::procedure_at(&procedures, index as u32)));
+	}
+	// The types of registers are split off their DEFs, after every line is checked against them
+	let mut types: HashMap<String, RegType> = HashMap::new();
+	// Parameters have no DEF, and are untyped like in the interpreter
+	for procedure in &procedures {
+		for param in &procedure.locals[..procedure.param_count] {
+			types.insert(procedure.local_name(param).unwrap(), RegType::Int);
+		}
+	}
+	for (index, tokens) in resolved.iter().enumerate() {
+		if tokens[0].to_lowercase() == "def" {
+			let (name, reg_type) = try_err_fallthru!(parser::split_type(&tokens[1]), format!("{}: ", line_origins[index]));
+			types.insert(name.to_owned(), reg_type);
+		}
+	}
+	for (index, tokens) in resolved.iter_mut().enumerate() {
+		{
+			let strs: Vec<&str> = tokens.iter().map(|tok| tok.as_str()).collect();
+			try_err_fallthru!(parser::types_valid(&strs, |name| types.get(name).cloned(), options.width), format!("{}: ", line_origins[index]));
+		}
+		if tokens[0].to_lowercase() == "def" {
+			let name = parser::split_type(&tokens[1]).unwrap().0.to_owned();
+			tokens[1] = name;
+		}
+	}
+	let lines: Vec<Vec<&str>> = resolved.iter()
+		.map(|tokens| tokens.iter().map(|tok| tok.as_str()).collect())
+		.collect();
+	if lines.iter().any(|tokens| ["snd", "rcv"].contains(&tokens[0].to_lowercase().as_str())) {
+		return Err("SND and RCV are only supported by the interpreter and the bytecode runner, not by the C compiler".to_owned());
+	}
+	if lines.iter().any(|tokens| tokens[0].to_lowercase() == "tgl") {
+		if !procedures.is_empty() || lines.iter().any(|tokens| tokens[0].to_lowercase() == "trap") {
+			return Err("Procedures and TRAP cannot be used together with TGL by the C compiler".to_owned());
+		}
+		return compose_dispatch(&lines, &line_origins, &descriptions, &types, options);
+	}
+
+	let linecount = lines.len() as u32;
+	let buffers = try!(mem_buffers(&lines));
+	for tokens in lines.iter() {
+		if !parser::params_valid(tokens, &buffers) {
+			return Err(format!("Invalid line: {} has a memory buffer where it should not, or an unknown memory buffer", tokens.join(" ")));
+		}
+	}
+	// Jumps with register offsets, returns, and handlers of runtime errors only know their target line at runtime
+	let traps = trapped_kinds(&lines);
+	let needs_jump_table = !traps.is_empty() || lines.iter().any(|tokens| ["ret", "endp", "return"].contains(&tokens[0].to_lowercase().as_str()) ||
+		parser::PARAM_RULES.get(tokens[0].to_lowercase().as_str()).unwrap().find('J').map_or(false, |target| parser::is_literal(tokens[target + 1]).is_err()));
+	let mut infix = runtime_declarations(&lines, options);
+	infix += &procedure_declarations(&procedures, &types);
+	if needs_jump_table {
+		infix += &format!("{}int64_t {} = 0;\n", INDENT, JUMP_TARGET_VARNAME);
+	}
+	// Registers declared by the lines so far, since each one is declared where its DEF is
+	let mut regs: Vec<&str> = Vec::new();
+	// Local names of the parameters of each procedure, which are declared by its PROC line as far as ASSERT is concerned
+	let params: Vec<Vec<String>> = procedures.iter()
+		.map(|procedure| procedure.locals[..procedure.param_count].iter().map(|param| procedure.local_name(param).unwrap()).collect())
+		.collect();
+	for (index, tokens) in lines.iter().enumerate() {
+		// C would not compile a register that isn't declared yet, so it is reported like the interpreter does
+		try_err_fallthru!(parser::registers_defined(tokens, |name| regs.contains(&name)), format!("{}: ", line_origins[index]));
+		let cline = try!(get_cline(tokens, &LineContext {
+			linenum: index as u32 + 1,
+			linecount: linecount,
+			origin: &line_origins[index],
+			regs: &regs,
+			types: &types,
+			procedures: &procedures,
+			traps: &traps,
+			description: &descriptions[index],
+		}, options));
+		if tokens[0].to_lowercase() == "proc" {
+			let procedure = procedures.iter().position(|procedure| procedure.entry == index as u32).unwrap();
+			regs.extend(params[procedure].iter().map(|param| param.as_str()));
+		}
+		infix += &format!("{}{}:;\n{}{}\n", LINE_LABEL_PREFIX, index + 1, INDENT, cline);
+		if tokens[0].to_lowercase() == "def" {
+			regs.push(tokens[1]);
+		}
+	}
+	if needs_jump_table {
+		// The jump table maps every line number to its label; targets past the end of the program end it.
+		infix += &format!("{i}goto {l}{};\n{}:\n{i}switch ({}) {{\n", linecount + 1, JUMP_TABLE_LABEL, JUMP_TARGET_VARNAME,
+			i = INDENT, l = LINE_LABEL_PREFIX);
+		for linenum in 1..linecount + 1 {
+			infix += &format!("{i}case {n}: goto {l}{n};\n", n = linenum, i = INDENT, l = LINE_LABEL_PREFIX);
 		}
+		// Jumps before the start of the program are stopped by the line that jumps (see generators::goto_relative)
+		infix += &format!("{i}default: goto {l}{};\n{i}}}\n", linecount + 1, i = INDENT, l = LINE_LABEL_PREFIX);
 	}
-	Ok(C_PROTOTYPE.to_owned().replace("##", &infix))
+	// Jumps to a label at the very end of the program land here
+	infix += &format!("{}{}:;\n", LINE_LABEL_PREFIX, linecount + 1);
+	Ok(C_PROTOTYPE.to_owned().replace("@@", &prelude(&lines, options)).replace("##", &infix))
 }
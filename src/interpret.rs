@@ -1,7 +1,15 @@
+use parser;
 use parser::{Token, TokenType};
+use options::{Options, Width, Overflow, Division};
+use value::{Value, RegType};
+use ports::Ports;
+use num_bigint::BigInt;
 use std::ops::Index;
+use std::collections::VecDeque;
 use std::iter;
 use std::iter::FromIterator;
+use std::io;
+use std::io::{BufRead, Write};
 
 /*
   This mod contains the interpreter part of Assembunny+. The abbreviated terminology for this mod is "ASMBI", for "ASseMBunny+ Interpreter".
@@ -13,30 +21,133 @@ pub struct AsmbiState {
     /// Register map (with its own type)
     pub regs: RegisterMap,
 
+    /// Register names, in the same order as `regs`, for the register dump of a failed ASSERT
+    pub reg_names: Vec<String>,
+
     /// Instruction Pointer, declared as u32 for ability to run more than 4 billion lines of ASMB.
     /// (I don't anticipate any combined ASMB program to have more than 4 billion lines!)
     pub ip: u32,
 
+    /// Index of the line that the last executed TGL asked to toggle, if any.
+    /// TGL cannot modify the program by itself because the executors only borrow the line being executed; see `execute_in`.
+    pub toggle_target: Option<usize>,
+
+    /// Whether each line of the program can be executed (see parser::tokens_valid), which only changes when TGL toggles the line.
+    /// Filled when the program is loaded, so that `execute_in` does not check every line it runs.
+    pub valid: Vec<bool>,
+
+    /// Instruction Pointer of the next line to execute, if the last executed line jumped instead of moving on to the next line.
+    pub jump_target: Option<u32>,
+
+    /// Return addresses of the CALLs that haven't RETurned yet, innermost last.
+    pub call_stack: Vec<u32>,
+
+    /// Target and arguments of the last CALL, which the PROC line it jumps to takes to start a call of its procedure (see `exec::proc_`).
+    pub call_args: Option<(u32, Vec<Value>)>,
+
+    /// Frames of the calls of procedures that haven't returned yet, innermost last.
+    pub frames: Vec<Frame>,
+
+    /// Handler of each kind of runtime error in parser::TRAP_KINDS, as the instruction index of the PROC line of the procedure that TRAP gave it to.
+    /// Errors of a kind without a handler end the program.
+    pub traps: [Option<u32>; 4],
+
+    /// Line number of each instruction in its source (see parser::origin_line), which the handlers of TRAP receive.
+    /// Instructions without one (such as in the REPL) are numbered from 1 instead.
+    pub line_numbers: Vec<u32>,
+
+    /// Data stack for PUSH/POP/PEEK, top last.
+    pub stack: Vec<Value>,
+
+    /// Maximum amount of values on `stack`
+    pub stack_limit: usize,
+
+    /// Memory buffers for MEM/LOAD/STORE, in the order of their MEMs.
+    pub mem: Vec<Vec<Value>>,
+
+    /// Contents of the program's string literals (see `parser::Symbols::strings`)
+    pub strings: Vec<String>,
+
+    /// Exit status requested by HLT, if the program was halted.
+    pub exit_code: Option<i32>,
+
+    /// Whether ASSERT is checked (see `Options::asserts`)
+    pub asserts: bool,
+
+    /// Register width (see `Options::width`)
+    pub width: Width,
+
+    /// What to do when an arithmetic result does not fit in `width` (see `Options::overflow`)
+    pub overflow: Overflow,
+
+    /// Rounding of DIV and MOD (see `Options::division`)
+    pub division: Division,
+
+    /// State of the generator of RND, which SEED sets (see `next_random`)
+    pub random_state: u64,
+
+    /// I/O ports of OUTP and INP (see `Options::ports`)
+    pub ports: Ports,
+
+    /// Values sent by SND that haven't been delivered to the receiving program yet, oldest first (see `loader::run_programs`)
+    pub outbox: VecDeque<Value>,
+
+    /// Values delivered to this program for RCV, oldest first
+    pub inbox: VecDeque<Value>,
+
+    /// Amount of values sent by SND so far
+    pub sent: u64,
+
+    /// Whether the last executed RCV had nothing to receive, in which case it stays on its line to try again when the program resumes.
+    pub waiting: bool,
+
 }
 
+/// The local registers of a procedure are ordinary registers (see `parser::Procedure`), which a call of the procedure takes over:
+/// their values from before the call are kept in its frame, and put back when it returns.
+pub struct Frame {
+    /// Index of the first local register
+    pub first_reg: usize,
+    /// Values of the local registers from before the call
+    pub saved: Vec<Value>,
+}
+
+/// Maximum amount of nested CALLs, shared with the C generator so both report a stack overflow at the same depth.
+pub const CALL_STACK_LIMIT: usize = 4096;
+
+/// Maximum amount of bits that a big register can be shifted by at once, so that a single SHL cannot take up all the memory.
+const BIG_SHIFT_LIMIT: usize = 65536;
+
 /// This struct/impl wraps the Register Vec in order to reduce boilerplate and redundancy on certain functions; It also makes code more readable.
+/// Values are stored as `Value`s whatever the register width; `AsmbiState::fit` keeps them within the width.
+/// Registers declared with a type (see `RegType`) are converted to it whenever they are set.
 pub struct RegisterMap {
-    pub vec: Vec<i32>,
+    pub vec: Vec<Value>,
+    /// Type of each register in `vec`
+    pub types: Vec<RegType>,
 }
 impl RegisterMap {
-    pub fn index_set(&mut self, regindex: usize, val: i32) -> bool {
+    pub fn index_set(&mut self, regindex: usize, val: Value) -> bool {
         if self.vec.len() <= regindex {
             return false;
         }
-        self.vec[regindex] = val;
+        self.vec[regindex] = match self.types[regindex] {
+            RegType::Int => val,
+            reg_type => Value::Small(reg_type.convert(val.wrapping_i128()))
+        };
         true
     }
 
-    pub fn set(&mut self, regtok: &Token, newval: i32) -> bool {
+    /// Returns the type of the register in `regtok`.
+    pub fn type_of(&self, regtok: &Token) -> RegType {
+        self.types[regtok.val as usize]
+    }
+
+    pub fn set(&mut self, regtok: &Token, newval: Value) -> bool {
         self.index_set(regtok.val as usize, newval)
     }
 
-    pub fn get(&self, index: usize) -> Option<&i32> {
+    pub fn get(&self, index: usize) -> Option<&Value> {
         if self.vec.len() <= index {
             None
         } else {
@@ -44,35 +155,70 @@ impl RegisterMap {
         }
     }
 
-    #[allow(unused_assignments)]
     pub fn index_modify<F>(&mut self, index: usize, modifier: F) -> bool
-            where F: Fn(i32) -> i32 {
-        let mut optval: i32 = 0;
-        {
-            match self.get(index) {
-                Some(val) => optval = *val,
-                None => return false
-            }
-        }
+            where F: Fn(Value) -> Value {
+        let optval = match self.get(index) {
+            Some(&Value::Small(val)) => Value::Small(val),
+            Some(val) => val.clone(),
+            None => return false
+        };
         self.index_set(index, modifier(optval))
     }
 
     pub fn modify<F>(&mut self, regtok: &Token, modifier: F) -> bool
-            where F: Fn(i32) -> i32 {
+            where F: Fn(Value) -> Value {
         self.index_modify(regtok.val as usize, modifier)
     }
 
-    pub fn parse_token(&self, tok: &Token) -> i32 {
+    /// Returns the value of the given LITERAL or REGISTER token. Only big values are cloned; the `parse_*` functions below avoid even that.
+    pub fn parse_token(&self, tok: &Token) -> Value {
         match tok.type_ {
-            TokenType::LITERAL => tok.val,
-            TokenType::REGISTER => *self.get(tok.val as usize).unwrap(),
+            TokenType::LITERAL => Value::Small(tok.val),
+            TokenType::REGISTER => match *self.get(tok.val as usize).unwrap() {
+                Value::Small(val) => Value::Small(val),
+                ref big => big.clone()
+            },
             _ => panic!("parse_token does not parse keyword tokens.")
         }
     }
 
-    pub fn new(capacity: usize) -> Self {
+    /// Returns the register in the given REGISTER token, or None for a LITERAL token.
+    fn register(&self, tok: &Token) -> Option<&Value> {
+        match tok.type_ {
+            TokenType::LITERAL => None,
+            TokenType::REGISTER => self.get(tok.val as usize),
+            _ => panic!("RegisterMap does not parse keyword tokens.")
+        }
+    }
+
+    /// Returns the value of the given LITERAL or REGISTER token as `Value::wrapping_i128` does, without cloning it.
+    pub fn parse_wrapping(&self, tok: &Token) -> i128 {
+        self.register(tok).map_or(tok.val, Value::wrapping_i128)
+    }
+
+    /// Returns the value of the given LITERAL or REGISTER token as `Value::saturate` does, without cloning it.
+    pub fn parse_saturated(&self, tok: &Token) -> i128 {
+        self.register(tok).map_or(tok.val, Value::saturate)
+    }
+
+    /// Returns the value of the given LITERAL or REGISTER token as a BigInt, without cloning it first.
+    pub fn parse_big(&self, tok: &Token) -> BigInt {
+        self.register(tok).map_or_else(|| BigInt::from(tok.val), Value::to_big)
+    }
+
+    /// Returns the value of the given LITERAL or REGISTER token if it fits in an i128, which saves cloning the value otherwise.
+    pub fn parse_small(&self, tok: &Token) -> Option<i128> {
+        match tok.type_ {
+            TokenType::LITERAL => Some(tok.val),
+            TokenType::REGISTER => self.get(tok.val as usize).unwrap().small(),
+            _ => panic!("parse_small does not parse keyword tokens.")
+        }
+    }
+
+    pub fn new(types: Vec<RegType>) -> Self {
         RegisterMap {
-            vec: Vec::from_iter(iter::repeat(0).take(capacity)),
+            vec: Vec::from_iter(iter::repeat(Value::Small(0)).take(types.len())),
+            types: types,
         }
     }
 }
@@ -80,13 +226,96 @@ impl RegisterMap {
 /// Syntactic sugar for all return values in exec.
 type Response = Result<(), String>;
 
+impl AsmbiState {
+    /// Fits the result of an arithmetic instruction into the register width, according to the overflow policy.
+    /// `exact` is the mathematically exact result, or None if even i128 cannot hold it, in which case `wrapped` is the result modulo 2^128
+    /// and `negative` tells which way it overflowed.
+    /// Big registers never overflow, so `big` computes the exact result for them when `exact` is None.
+    pub fn fit<F>(&self, exact: Option<i128>, wrapped: i128, negative: bool, big: F) -> Result<Value, String>
+            where F: FnOnce() -> BigInt {
+        let (wrapped, negative) = match exact {
+            Some(val) if self.width.contains(val) => return Ok(Value::Small(val)),
+            None if self.width == Width::Big => return Ok(Value::from_big(big())),
+            Some(val) => (val, val < 0),
+            None => (wrapped, negative)
+        };
+        match self.overflow {
+            Overflow::Wrap => Ok(Value::Small(self.width.wrap(wrapped))),
+            Overflow::Trap => Err(format!("Integer overflow ({}-bit)", self.width.bits())),
+            Overflow::Saturate => Ok(Value::Small(if negative { self.width.min_value() } else { self.width.max_value() }))
+        }
+    }
+}
+
+/// Advances the state of the SplitMix64 generator and returns its next output, for RND.
+/// `gen_c::prelude` emits the same steps in C, so that a seed gives the same numbers in every backend.
+pub fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Returns the next byte of `input` (locked STDIN, or a stand-in for it in tests), or None at the end of STDIN.
+/// If `consume` is false, the byte is left in STDIN to be read again (peeking, like `ungetc` in C).
+pub fn stdin_byte<R: BufRead>(input: &mut R, consume: bool) -> Result<Option<u8>, String> {
+    let byte = match input.fill_buf() {
+        Ok(buf) => buf.first().cloned(),
+        Err(err) => return Err(format!("Failed to read from STDIN: {}", err))
+    };
+    if consume && byte.is_some() {
+        input.consume(1);
+    }
+    Ok(byte)
+}
+
+/// Reads an integer from `input` (see `stdin_byte`) the same way `scanf("%d")` does: whitespace is skipped, then an optionally signed decimal integer is read.
+/// The integer has to fit in the given register width.
+fn stdin_integer<R: BufRead>(input: &mut R, width: Width) -> Result<Value, String> {
+    // Output that asks for the input should be visible before the program blocks on STDIN
+    io::stdout().flush().expect("Stdout clogged");
+    while let Some(byte) = try!(stdin_byte(input, false)) {
+        if !(byte as char).is_whitespace() {
+            break;
+        }
+        try!(stdin_byte(input, true));
+    }
+    let mut digits = String::new();
+    while let Some(byte) = try!(stdin_byte(input, false)) {
+        if !((byte as char).is_digit(10) || (digits.is_empty() && (byte == b'-' || byte == b'+'))) {
+            break;
+        }
+        digits.push(byte as char);
+        try!(stdin_byte(input, true));
+    }
+    if width == Width::Big {
+        return match digits.parse::<BigInt>() {
+            Ok(val) => Ok(Value::from_big(val)),
+            Err(_) if digits.is_empty() => Err("Expected an integer from STDIN".to_owned()),
+            Err(_) => Err(format!("'{}' from STDIN is not an integer", digits))
+        };
+    }
+    match digits.parse::<i128>() {
+        Ok(val) if width.contains(val) => Ok(Value::Small(val)),
+        Err(_) if digits.is_empty() => Err("Expected an integer from STDIN".to_owned()),
+        _ => Err(format!("'{}' from STDIN is not a {}-bit integer", digits, width.bits()))
+    }
+}
+
 /// Module consisting of executors for each keyword.
 /// Each function has two arguments: mutable reference to AsmbiState and Vec<&str> tokens from the parser.
 /// The tokens are expected to be passed by parser::line_valid. If an error that was supposed to be caught in that function is encountered here, the program will panic!, reminding the developer that parser::line_valid is not working properly.
 mod exec {
     use std::char;
-    use interpret::{AsmbiState, Response};
+    use std::io;
+    use interpret::{AsmbiState, Frame, Response, CALL_STACK_LIMIT, BIG_SHIFT_LIMIT, stdin_byte, stdin_integer, next_random};
+    use parser;
     use parser::Token;
+    use options::{Width, Division, PORT_COUNT};
+    use value::{Value, RegType};
+    use num_bigint::BigInt;
+    use num_traits::{Signed, Zero};
 
     macro_rules! try_do {
         ( $fun:expr, $err:expr ) => (if $fun {
@@ -106,39 +335,121 @@ mod exec {
         try_set!(state.regs.set(&toks[1], newval))
     }
 
+    /// Arithmetic operations whose result may not fit in the register width
+    enum Op { Add, Sub, Mul, Div }
+
+    /// Applies `op` to `left` and `right` exactly, for big registers. Division rounds as `division` says.
+    fn big_arithmetic(op: &Op, left: BigInt, right: BigInt, division: Division) -> BigInt {
+        match *op {
+            Op::Add => left + right,
+            Op::Sub => left - right,
+            Op::Mul => left * right,
+            Op::Div => {
+                let round_down = division == Division::Floor && !(&left % &right).is_zero() && left.is_negative() != right.is_negative();
+                let quotient = left / right;
+                if round_down { quotient - 1 } else { quotient }
+            }
+        }
+    }
+
+    /// Divides `left` by `right` (which is not 0), rounding as `division` says. The minimum divided by -1 wraps around to the minimum.
+    fn wrapping_quotient(left: i128, right: i128, division: Division) -> i128 {
+        let quotient = left.wrapping_div(right);
+        // A truncated quotient is one too high when it is negative and not whole
+        if division == Division::Floor && left.wrapping_rem(right) != 0 && (left < 0) != (right < 0) {
+            quotient - 1
+        } else {
+            quotient
+        }
+    }
+
+    /// Applies `op` to the register in `reg_tok` and `operand`, storing the result according to the overflow policy (see `AsmbiState::fit`).
+    fn arithmetic(state: &mut AsmbiState, reg_tok: &Token, op: Op, operand: Value) -> Response {
+        let division = state.division;
+        if state.regs.type_of(reg_tok) != RegType::Int {
+            // Registers with a declared type wrap around whatever the overflow policy; setting the register converts the result
+            let (v, operand) = (state.regs.parse_wrapping(reg_tok), operand.wrapping_i128());
+            let wrapped = match op {
+                Op::Add => v.wrapping_add(operand),
+                Op::Sub => v.wrapping_sub(operand),
+                Op::Mul => v.wrapping_mul(operand),
+                Op::Div => wrapping_quotient(v, operand, division)
+            };
+            return try_set!(state.regs.set(reg_tok, Value::Small(wrapped)));
+        }
+        let newval = match (state.regs.parse_small(reg_tok), operand.small()) {
+            (Some(v), Some(operand)) => {
+                let (exact, wrapped, negative) = match op {
+                    Op::Add => (v.checked_add(operand), v.wrapping_add(operand), operand < 0),
+                    Op::Sub => (v.checked_sub(operand), v.wrapping_sub(operand), operand > 0),
+                    Op::Mul => (v.checked_mul(operand), v.wrapping_mul(operand), (v < 0) != (operand < 0)),
+                    // Only the minimum divided by -1 overflows, whose quotient is whole either way
+                    Op::Div => (v.checked_div(operand).map(|_| wrapping_quotient(v, operand, division)), wrapping_quotient(v, operand, division), false)
+                };
+                try!(state.fit(exact, wrapped, negative, || big_arithmetic(&op, BigInt::from(v), BigInt::from(operand), division)))
+            },
+            // Only big registers can hold values that do not fit in an i128
+            _ => Value::from_big(big_arithmetic(&op, state.regs.parse_big(reg_tok), operand.to_big(), division))
+        };
+        try_set!(state.regs.set(reg_tok, newval))
+    }
+
+    /// Adds 1 to or subtracts 1 from the register in `reg_tok` (as `op` says), for INC and DEC, which most Assembunny loops spend their time on.
+    /// Untyped registers are updated in place as long as they stay within the register width; everything else goes through `arithmetic`.
+    fn step(state: &mut AsmbiState, reg_tok: &Token, op: Op) -> Response {
+        let index = reg_tok.val as usize;
+        if let (RegType::Int, Some(v)) = (state.regs.types[index], state.regs.vec[index].small()) {
+            let stepped = match op {
+                Op::Add => v.checked_add(1),
+                _ => v.checked_sub(1)
+            };
+            match stepped {
+                Some(val) if state.width.contains(val) => {
+                    state.regs.vec[index] = Value::Small(val);
+                    return Ok(());
+                },
+                _ => {}
+            }
+        }
+        arithmetic(state, reg_tok, op, Value::Small(1))
+    }
+
     pub fn inc(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
         // Syntax: inc <register index>
-        try_set!(state.regs.modify(&toks[1], |v| v + 1))
+        step(state, &toks[1], Op::Add)
     }
 
     pub fn inct(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
         // Syntax: inct <register index> <value to add>
         let adder = state.regs.parse_token(&toks[2]);
-        try_set!(state.regs.modify(&toks[1], |v| v + adder))
+        arithmetic(state, &toks[1], Op::Add, adder)
     }
 
     pub fn dec(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
         // Syntax: dec <register name>
-        try_set!(state.regs.modify(&toks[1], |v| v - 1))
+        step(state, &toks[1], Op::Sub)
     }
 
     pub fn dect(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
         // Syntax: dect <register name> <value to be eval'd>
         let subtractor = state.regs.parse_token(&toks[2]);
-        try_set!(state.regs.modify(&toks[1], |v| v - subtractor))
+        arithmetic(state, &toks[1], Op::Sub, subtractor)
     }
 
     pub fn mul(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
         // Syntax: mul <register name> <eval-ue>
         let multiplier = state.regs.parse_token(&toks[2]);
-        try_set!(state.regs.modify(&toks[1], |v| v * multiplier))
+        arithmetic(state, &toks[1], Op::Mul, multiplier)
     }
 
     pub fn div(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
         // Syntax: div <register name> <eval-ue>
-        // Note: floor the result
+        // Note: rounds toward zero, or down with `--division floor`; the only overflow is the minimum divided by -1
         let quotient = state.regs.parse_token(&toks[2]);
-        try_set!(state.regs.modify(&toks[1], |v| v / quotient))
+        if quotient.is_zero() {
+            return fault(state, "div", "Division by zero".to_owned());
+        }
+        arithmetic(state, &toks[1], Op::Div, quotient)
     }
 
     pub fn cpy(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
@@ -147,19 +458,151 @@ mod exec {
         try_set!(state.regs.set(&toks[2], newval))
     }
 
-    pub fn jnz(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
-        // Syntax: cpy <eval-ue> <literal>
-        // Since IP is incremented after each line, go to relative line **minus 1** so the program works properly.
-        if state.regs.parse_token(&toks[1]) != 0 {
-            // TODO: add under/overflow checks
-            // Ugly hack for u32 adding i32; hope this will be supported in future versions of Rust.
-            let diff = state.regs.parse_token(&toks[2]) - 1;
-            if diff < 0 {
-                state.ip -= (-diff) as u32
-            } else {
-                state.ip += diff as u32
+    /// Jumps by the offset in `offset_tok` relative to the current line, if `condition` holds.
+    /// The offset can be a register, in which case the jump is computed at runtime.
+    fn jump_if(state: &mut AsmbiState, condition: bool, offset_tok: &Token) -> Response {
+        if condition {
+            let offset = match state.regs.parse_small(offset_tok) {
+                Some(offset) => offset,
+                None => state.regs.parse_saturated(offset_tok)
+            };
+            let target = (state.ip as i128).saturating_add(offset);
+            if target < 0 {
+                return Err(format!("Jump target {} is before the start of the program", target));
             }
+            // Every target past the end of the program ends it
+            state.jump_target = Some(::std::cmp::min(target, u32::max_value() as i128) as u32);
+        }
+        Ok(())
+    }
+
+    pub fn jnz(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: jnz <eval-ue> <eval-ue>
+        let condition = state.regs.parse_small(&toks[1]) != Some(0);
+        jump_if(state, condition, &toks[2])
+    }
+
+    pub fn jz(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: jz <eval-ue> <eval-ue>
+        let condition = state.regs.parse_small(&toks[1]) == Some(0);
+        jump_if(state, condition, &toks[2])
+    }
+
+    pub fn jeq(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: jeq <eval-ue> <eval-ue> <eval-ue>
+        let condition = state.regs.parse_token(&toks[1]) == state.regs.parse_token(&toks[2]);
+        jump_if(state, condition, &toks[3])
+    }
+
+    pub fn jne(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: jne <eval-ue> <eval-ue> <eval-ue>
+        let condition = state.regs.parse_token(&toks[1]) != state.regs.parse_token(&toks[2]);
+        jump_if(state, condition, &toks[3])
+    }
+
+    pub fn jlt(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: jlt <eval-ue> <eval-ue> <eval-ue>
+        let condition = state.regs.parse_token(&toks[1]) < state.regs.parse_token(&toks[2]);
+        jump_if(state, condition, &toks[3])
+    }
+
+    pub fn jgt(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: jgt <eval-ue> <eval-ue> <eval-ue>
+        let condition = state.regs.parse_token(&toks[1]) > state.regs.parse_token(&toks[2]);
+        jump_if(state, condition, &toks[3])
+    }
+
+    pub fn call(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: call <eval-ue>
+        if state.call_stack.len() >= CALL_STACK_LIMIT {
+            return Err(format!("Call stack overflow (more than {} nested calls)", CALL_STACK_LIMIT));
+        }
+        let return_address = state.ip + 1;
+        state.call_stack.push(return_address);
+        try!(jump_if(state, true, &toks[1]));
+        // Taken by the PROC line at the target, if there is one
+        let args = toks[2..].iter().map(|tok| state.regs.parse_token(tok)).collect();
+        state.call_args = state.jump_target.map(|target| (target, args));
+        Ok(())
+    }
+
+    pub fn ret(state: &mut AsmbiState, _toks: &Vec<Token>) -> Response {
+        // Syntax: ret
+        match state.call_stack.pop() {
+            Some(return_address) => {
+                state.jump_target = Some(return_address);
+                Ok(())
+            },
+            None => Err("Call stack underflow (RET without CALL)".to_owned())
+        }
+    }
+
+    pub fn push(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: push <eval-ue>
+        if state.stack.len() >= state.stack_limit {
+            return Err(format!("Stack overflow (more than {} values)", state.stack_limit));
+        }
+        let val = state.regs.parse_token(&toks[1]);
+        state.stack.push(val);
+        Ok(())
+    }
+
+    pub fn pop(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: pop <register name>
+        let val = match state.stack.pop() {
+            Some(val) => val,
+            None => return fault(state, "stack", "Stack underflow (POP on empty stack)".to_owned())
+        };
+        try_set!(state.regs.set(&toks[1], val))
+    }
+
+    pub fn peek(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: peek <register name>
+        let val = match state.stack.last() {
+            Some(val) => val.clone(),
+            None => return fault(state, "stack", "Stack underflow (PEEK on empty stack)".to_owned())
+        };
+        try_set!(state.regs.set(&toks[1], val))
+    }
+
+    pub fn mem(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: mem <buffer index> <size>
+        // Note: The buffers of a program are allocated by new_state, but the REPL only learns about them here
+        let index = toks[1].val as usize;
+        while state.mem.len() <= index {
+            state.mem.push(Vec::new());
         }
+        state.mem[index] = vec![Value::Small(0); toks[2].val as usize];
+        Ok(())
+    }
+
+    /// Checks that `index` points into the memory buffer referred to by `buffer_tok`, and returns it as usize.
+    fn mem_index(state: &AsmbiState, buffer_tok: &Token, index: Value) -> Result<usize, String> {
+        let size = state.mem[buffer_tok.val as usize].len();
+        match index.small() {
+            Some(small) if small >= 0 && small < size as i128 => Ok(small as usize),
+            _ => Err(format!("Index {} is out of bounds for a memory buffer of size {}", index, size))
+        }
+    }
+
+    pub fn load(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: load <register name> <buffer index> <eval-ue>
+        let index = match mem_index(state, &toks[2], state.regs.parse_token(&toks[3])) {
+            Ok(index) => index,
+            Err(message) => return fault(state, "mem", message)
+        };
+        let newval = state.mem[toks[2].val as usize][index].clone();
+        try_set!(state.regs.set(&toks[1], newval))
+    }
+
+    pub fn store(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: store <buffer index> <eval-ue> <eval-ue>
+        let index = match mem_index(state, &toks[1], state.regs.parse_token(&toks[2])) {
+            Ok(index) => index,
+            Err(message) => return fault(state, "mem", message)
+        };
+        let newval = state.regs.parse_token(&toks[3]);
+        state.mem[toks[1].val as usize][index] = newval;
         Ok(())
     }
 
@@ -175,20 +618,342 @@ mod exec {
         Ok(())
     }
 
+    pub fn outs(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: outs <string index>
+        print!("{}", state.strings[toks[1].val as usize]);
+        Ok(())
+    }
+
     pub fn outc(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
         // Syntax: outc <eval-ue>
         let val = state.regs.parse_token(&toks[1]);
-        if val < 0 {
-            return Err(format!("Char code ({}) should not be less than zero", val));
+        let code = val.saturate();
+        if code < 0 {
+            return fault(state, "outc", format!("Char code ({}) should not be less than zero", val));
         }
-        match char::from_u32(val as u32) {
+        if code > u32::max_value() as i128 {
+            return fault(state, "outc", format!("Char code ({}) is invalid", val));
+        }
+        match char::from_u32(code as u32) {
             Some(v) => print!("{}", v),
-            _ => return Err(format!("Char code ({}) is invalid", val))
+            _ => return fault(state, "outc", format!("Char code ({}) is invalid", val))
         }
         Ok(())
     }
 
-    pub const INDEX: [fn(&mut AsmbiState, &Vec<Token>) -> Response; 12] = [def, inc, inct, dec, dect, mul, div, cpy, jnz, out, outn, outc];
+    pub fn tgl(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: tgl <eval-ue>
+        // Targets outside of the program are ignored, as in Advent of Code 2016 Day 23.
+        let target = (state.ip as i128).saturating_add(state.regs.parse_saturated(&toks[1]));
+        if target >= 0 && target <= usize::max_value() as i128 {
+            state.toggle_target = Some(target as usize);
+        }
+        Ok(())
+    }
+
+    pub fn inn(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: inn <register name>
+        let stdin = io::stdin();
+        let newval = try!(stdin_integer(&mut stdin.lock(), state.width));
+        try_set!(state.regs.set(&toks[1], newval))
+    }
+
+    pub fn inch(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: inch <register name>
+        // Note: -1 at the end of STDIN, like getchar() in C
+        let stdin = io::stdin();
+        let newval = Value::Small(match try!(stdin_byte(&mut stdin.lock(), true)) {
+            Some(byte) => byte as i128,
+            None => -1
+        });
+        try_set!(state.regs.set(&toks[1], newval))
+    }
+
+    pub fn mod_(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: mod <register name> <eval-ue>
+        // Note: `mod` is a Rust keyword. The remainder has the sign of the dividend (or of the divisor with `--division floor`, to match DIV),
+        // and x % -1 is 0 even for the minimum.
+        let divisor = state.regs.parse_token(&toks[2]);
+        if divisor.is_zero() {
+            return fault(state, "div", "Modulo by zero".to_owned());
+        }
+        let floor = state.division == Division::Floor;
+        try_set!(state.regs.modify(&toks[1], |v| match (v.small(), divisor.small()) {
+            (Some(v), Some(divisor)) => {
+                let remainder = v.wrapping_rem(divisor);
+                Value::Small(if floor && remainder != 0 && (remainder < 0) != (divisor < 0) { remainder + divisor } else { remainder })
+            },
+            _ => {
+                let (remainder, divisor) = (v.to_big() % divisor.to_big(), divisor.to_big());
+                Value::from_big(if floor && !remainder.is_zero() && remainder.is_negative() != divisor.is_negative() { remainder + divisor } else { remainder })
+            }
+        }))
+    }
+
+    /// Applies a bitwise operation to the register in `reg_tok` and `mask`, in two's complement.
+    fn bitwise<F, G>(state: &mut AsmbiState, reg_tok: &Token, mask: Value, small_op: F, big_op: G) -> Response
+            where F: Fn(i128, i128) -> i128, G: Fn(BigInt, BigInt) -> BigInt {
+        try_set!(state.regs.modify(reg_tok, |v| match (v.small(), mask.small()) {
+            (Some(v), Some(mask)) => Value::Small(small_op(v, mask)),
+            _ => Value::from_big(big_op(v.to_big(), mask.to_big()))
+        }))
+    }
+
+    pub fn and(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: and <register name> <eval-ue>
+        let mask = state.regs.parse_token(&toks[2]);
+        bitwise(state, &toks[1], mask, |v, mask| v & mask, |v, mask| v & mask)
+    }
+
+    pub fn or(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: or <register name> <eval-ue>
+        let mask = state.regs.parse_token(&toks[2]);
+        bitwise(state, &toks[1], mask, |v, mask| v | mask, |v, mask| v | mask)
+    }
+
+    pub fn xor(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: xor <register name> <eval-ue>
+        let mask = state.regs.parse_token(&toks[2]);
+        bitwise(state, &toks[1], mask, |v, mask| v ^ mask, |v, mask| v ^ mask)
+    }
+
+    /// Returns the amount of a shift of the register in `reg_tok` by `amount` bits.
+    /// Fixed-width registers only use the lowest bits of the amount (5 bits for 32-bit registers, 3 bits for u8 ones), while big registers use all of it up to BIG_SHIFT_LIMIT.
+    fn shift_amount(state: &AsmbiState, reg_tok: &Token, amount: Value) -> Result<usize, String> {
+        let reg_type = state.regs.type_of(reg_tok);
+        if reg_type != RegType::Int || state.width != Width::Big {
+            return Ok((amount.wrapping_i128() as u32 & (reg_type.bits(state.width) - 1)) as usize);
+        }
+        match amount.small() {
+            Some(small) if small >= 0 && small <= BIG_SHIFT_LIMIT as i128 => Ok(small as usize),
+            _ => Err(format!("Shift amount ({}) should be between 0 and {}", amount, BIG_SHIFT_LIMIT))
+        }
+    }
+
+    pub fn shl(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: shl <register name> <eval-ue>
+        // Note: the bits shifted out of fixed-width registers are lost whatever the overflow policy
+        let amount = try!(shift_amount(state, &toks[1], state.regs.parse_token(&toks[2])));
+        // Registers with a declared type are at most 64 bits wide, and setting the register converts the result
+        let width = if state.regs.type_of(&toks[1]) == RegType::Int { state.width } else { Width::I128 };
+        try_set!(state.regs.modify(&toks[1], |v| match v.small() {
+            Some(v) if width != Width::Big => Value::Small(width.wrap(v << amount)),
+            _ => Value::from_big(v.to_big() << amount)
+        }))
+    }
+
+    pub fn shr(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: shr <register name> <eval-ue>
+        // Note: arithmetic shift, since the register is signed
+        let amount = try!(shift_amount(state, &toks[1], state.regs.parse_token(&toks[2])));
+        try_set!(state.regs.modify(&toks[1], |v| match v.small() {
+            Some(v) => Value::Small(v >> ::std::cmp::min(amount, 127)),
+            None => Value::from_big(v.to_big() >> amount)
+        }))
+    }
+
+    pub fn neg(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: neg <register name>
+        // Note: negating the minimum overflows
+        let value = state.regs.parse_token(&toks[1]);
+        let newval = match value.small() {
+            Some(v) if state.regs.type_of(&toks[1]) != RegType::Int => Value::Small(v.wrapping_neg()),
+            Some(v) => try!(state.fit(v.checked_neg(), v.wrapping_neg(), false, || -BigInt::from(v))),
+            None => Value::from_big(-value.to_big())
+        };
+        try_set!(state.regs.set(&toks[1], newval))
+    }
+
+    pub fn hlt(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: hlt [eval-ue]
+        // Like `return` from main() in C, wider values are truncated to an int
+        state.exit_code = Some(if toks.len() > 1 { state.regs.parse_wrapping(&toks[1]) as i32 } else { 0 });
+        // Every target past the end of the program ends it
+        state.jump_target = Some(u32::max_value());
+        Ok(())
+    }
+
+    pub fn assert(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: assert <eval-ue> <operator index> <eval-ue> <string index>
+        if !state.asserts ||
+                parser::compare(state.regs.parse_token(&toks[1]), toks[2].val as usize, state.regs.parse_token(&toks[3])) {
+            return Ok(());
+        }
+        let dump: Vec<String> = state.reg_names.iter().zip(state.regs.vec.iter())
+            .map(|(name, val)| format!("{} = {}", name, val))
+            .collect();
+        Err(format!("Assertion failed: {}\nRegisters: {}", state.strings[toks[4].val as usize],
+            if dump.is_empty() { "(none)".to_owned() } else { dump.join(", ") }))
+    }
+
+    pub fn rnd(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: rnd <register name> <eval-ue> <eval-ue>
+        let (low, high) = match (state.regs.parse_small(&toks[2]), state.regs.parse_small(&toks[3])) {
+            (Some(low), Some(high)) => (low, high),
+            _ => return Err("The range of RND has to fit in 128-bit integers".to_owned())
+        };
+        if low > high {
+            return Err(format!("Empty range of RND ({} is greater than {})", low, high));
+        }
+        // One less than the size of the range, which is exact since low <= high
+        let range = high.wrapping_sub(low) as u128;
+        let offset = if range <= u64::max_value() as u128 {
+            next_random(&mut state.random_state) as u128 % (range + 1)
+        } else {
+            let high_half = next_random(&mut state.random_state) as u128;
+            let draw = high_half << 64 | next_random(&mut state.random_state) as u128;
+            if range == u128::max_value() { draw } else { draw % (range + 1) }
+        };
+        try_set!(state.regs.set(&toks[1], Value::Small((low as u128).wrapping_add(offset) as i128)))
+    }
+
+    pub fn seed(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: seed <eval-ue>
+        state.random_state = state.regs.parse_wrapping(&toks[1]) as u64;
+        Ok(())
+    }
+
+    /// Returns the number of the port in `port_tok`, which has to be one of the PORT_COUNT ports.
+    fn port_number(state: &AsmbiState, port_tok: &Token) -> Result<u8, String> {
+        let port = state.regs.parse_token(port_tok);
+        match port.small() {
+            Some(number) if number >= 0 && number < PORT_COUNT as i128 => Ok(number as u8),
+            _ => Err(format!("Port {} does not exist (ports are numbered from 0 to {})", port, PORT_COUNT - 1))
+        }
+    }
+
+    pub fn outp(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: outp <eval-ue> <eval-ue>
+        let port = try!(port_number(state, &toks[1]));
+        let val = state.regs.parse_token(&toks[2]);
+        match val.small() {
+            Some(byte) if byte >= 0 && byte <= 255 => state.ports.write(port, byte as u8),
+            _ => Err(format!("Value {} does not fit in a byte", val))
+        }
+    }
+
+    pub fn inp(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: inp <register name> <eval-ue>
+        // Note: -1 at the end of the port, like INCH
+        let port = try!(port_number(state, &toks[2]));
+        let newval = Value::Small(match try!(state.ports.read(port)) {
+            Some(byte) => byte as i128,
+            None => -1
+        });
+        try_set!(state.regs.set(&toks[1], newval))
+    }
+
+    pub fn snd(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: snd <eval-ue>
+        let val = state.regs.parse_token(&toks[1]);
+        state.outbox.push_back(val);
+        state.sent += 1;
+        Ok(())
+    }
+
+    pub fn rcv(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: rcv <register name>
+        match state.inbox.pop_front() {
+            Some(val) => {
+                state.waiting = false;
+                try_set!(state.regs.set(&toks[1], val))
+            },
+            None => {
+                // The runner gets the program going again once another program has sent something
+                state.waiting = true;
+                state.jump_target = Some(state.ip);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn proc_(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: proc <offset past ENDP> <index of the first local register> <local count> <parameter count> (see parser::procedure_tokens)
+        let (first_reg, local_count, param_count) = (toks[2].val as usize, toks[3].val as usize, toks[4].val as usize);
+        let args = match state.call_args.take() {
+            Some((target, args)) if target == state.ip => args,
+            _ => {
+                // Running into a procedure skips it
+                state.jump_target = Some(state.ip + toks[1].val as u32);
+                return Ok(());
+            }
+        };
+        if args.len() != param_count {
+            return Err(format!("Procedure expects {} argument(s), got {}", param_count, args.len()));
+        }
+        if state.frames.len() >= CALL_STACK_LIMIT {
+            return Err(format!("Call stack overflow (more than {} nested calls)", CALL_STACK_LIMIT));
+        }
+        let saved = state.regs.vec[first_reg..first_reg + local_count].to_vec();
+        state.frames.push(Frame { first_reg: first_reg, saved: saved });
+        // Locals other than the parameters start out as 0, until their DEF
+        let mut args = args.into_iter();
+        for index in first_reg..first_reg + local_count {
+            state.regs.index_set(index, args.next().unwrap_or(Value::Small(0)));
+        }
+        Ok(())
+    }
+
+    /// Returns from the innermost call of a procedure, pushing `result` onto the data stack if there is one.
+    fn leave(state: &mut AsmbiState, result: Option<Value>) -> Response {
+        if state.frames.is_empty() {
+            return Err("RETURN or ENDP without a call of a procedure".to_owned());
+        }
+        if let Some(result) = result {
+            if state.stack.len() >= state.stack_limit {
+                return Err(format!("Stack overflow (more than {} values)", state.stack_limit));
+            }
+            state.stack.push(result);
+        }
+        let frame = state.frames.pop().unwrap();
+        for (offset, val) in frame.saved.into_iter().enumerate() {
+            state.regs.vec[frame.first_reg + offset] = val;
+        }
+        let return_address = try_opt!(state.call_stack.pop(), "Call stack underflow (RET without CALL)".to_owned());
+        state.jump_target = Some(return_address);
+        Ok(())
+    }
+
+    pub fn trap(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: trap <kind of runtime error + 1> <offset of the PROC line of the handler, or 0 for the default>
+        let offset = state.regs.parse_saturated(&toks[2]);
+        state.traps[toks[1].val as usize - 1] = if offset == 0 { None } else { Some((state.ip as i128 + offset) as u32) };
+        Ok(())
+    }
+
+    /// Hands a runtime error of the given kind (see parser::TRAP_KINDS) to its handler, as if the failing line called the handler with the error code and its line number.
+    /// RETURN or ENDP of the handler then resumes the program after the failing line. Without a handler, `message` ends the program.
+    fn fault(state: &mut AsmbiState, kind: &str, message: String) -> Response {
+        let code = parser::index_of(&parser::TRAP_KINDS, &kind).unwrap();
+        let handler = match state.traps[code] {
+            Some(handler) => handler,
+            None => return Err(message)
+        };
+        if state.call_stack.len() >= CALL_STACK_LIMIT {
+            return Err(format!("Call stack overflow (more than {} nested calls)", CALL_STACK_LIMIT));
+        }
+        let line = state.line_numbers.get(state.ip as usize).cloned().unwrap_or(state.ip + 1);
+        state.call_stack.push(state.ip + 1);
+        state.call_args = Some((handler, vec![Value::Small(code as i128 + 1), Value::Small(line as i128)]));
+        state.jump_target = Some(handler);
+        Ok(())
+    }
+
+    pub fn endp(state: &mut AsmbiState, _toks: &Vec<Token>) -> Response {
+        // Syntax: endp
+        leave(state, None)
+    }
+
+    pub fn return_(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
+        // Syntax: return [eval]
+        let result = toks.get(1).map(|tok| state.regs.parse_token(tok));
+        leave(state, result)
+    }
+
+    pub const INDEX: [fn(&mut AsmbiState, &Vec<Token>) -> Response; 48] = [def, inc, inct, dec, dect, mul, div, cpy, jnz, out, outn, outc, tgl, inn, inch,
+                                                                           mod_, and, or, xor, shl, shr, neg, jz, jeq, jne, jlt, jgt, call, ret,
+                                                                           push, pop, peek, mem, load, store, outs, hlt, assert, rnd, seed, outp, inp,
+                                                                           snd, rcv, proc_, endp, return_, trap];
 }
 
 pub fn execute(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
@@ -196,9 +961,433 @@ pub fn execute(state: &mut AsmbiState, toks: &Vec<Token>) -> Response {
     exec::INDEX[toks[0].val as usize](state, toks)
 }
 
-pub fn new_state(capacity: usize) -> AsmbiState {
+/// Executes the line of `program` at the instruction pointer, then moves the instruction pointer to the next line to execute.
+/// Lines that were toggled into invalid instructions are skipped, and the toggle requested by a TGL on this line is applied to `program`.
+/// `state.valid` has to hold the validity of every line of `program`, as the loader fills it.
+pub fn execute_in(state: &mut AsmbiState, program: &mut Vec<Vec<Token>>) -> Response {
+    let ip = state.ip as usize;
+    if state.valid[ip] {
+        try!(execute(state, &program[ip]));
+        if let Some(target) = state.toggle_target.take() {
+            if let Some(line) = program.get_mut(target) {
+                toggle(line);
+                state.valid[target] = parser::tokens_valid(line);
+            }
+        }
+    }
+    state.ip = match state.jump_target.take() {
+        Some(target) => target,
+        None => state.ip + 1
+    };
+    Ok(())
+}
+
+/// Executes the lines of `program` (see `execute_in`) until it ends, waits on RCV or sends a value, which are the only times that the programs running with it
+/// are concerned. This saves the runner from checking on them after every line.
+pub fn run_in(state: &mut AsmbiState, program: &mut Vec<Vec<Token>>) -> Response {
+    while (state.ip as usize) < program.len() {
+        try!(execute_in(state, program));
+        if state.waiting || !state.outbox.is_empty() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Toggles the given line of tokens according to the rules of TGL (see `parser::toggled_keyword`).
+pub fn toggle(line: &mut Vec<Token>) {
+    let keyword = parser::toggled_keyword(parser::KEYWORD_INDEX[line[0].val as usize], line.len() - 1);
+    line[0].val = parser::index_of(&parser::KEYWORD_INDEX, &keyword).unwrap() as i128;
+}
+
+/// `reg_names` holds the names of the program's registers, in the order of their DEFs, and `reg_types` holds their types.
+/// `buffer_sizes` holds the size of each memory buffer of the program, in the order of their MEMs, and `strings` holds its string literals.
+pub fn new_state(reg_names: Vec<String>, reg_types: Vec<RegType>, buffer_sizes: &[u32], strings: Vec<String>, options: &Options) -> AsmbiState {
     AsmbiState {
-        regs: RegisterMap::new(capacity),
-        ip: 0
+        regs: RegisterMap::new(reg_types),
+        reg_names: reg_names,
+        ip: 0,
+        toggle_target: None,
+        valid: Vec::new(),
+        jump_target: None,
+        call_stack: Vec::new(),
+        call_args: None,
+        frames: Vec::new(),
+        traps: [None; 4],
+        line_numbers: Vec::new(),
+        stack: Vec::new(),
+        stack_limit: options.stack_limit,
+        mem: buffer_sizes.iter().map(|&size| vec![Value::Small(0); size as usize]).collect(),
+        strings: strings,
+        exit_code: None,
+        asserts: options.asserts,
+        width: options.width,
+        overflow: options.overflow,
+        division: options.division,
+        random_state: options.seed,
+        ports: Ports::new(&options.ports),
+        outbox: VecDeque::new(),
+        inbox: VecDeque::new(),
+        sent: 0,
+        waiting: false
+    }
+}
+
+#[cfg(test)]
+mod interpret_test {
+    use interpret::*;
+    use options::{Port, PORT_COUNT};
+
+    /// Runs the given lines with the given options, and returns the final state, or the first runtime error.
+    fn run_state(lines: &[&str], options: &Options) -> Result<AsmbiState, String> {
+        let mut symbols = parser::Symbols::new();
+        symbols.width = options.width;
+        let mut program = parser::to_program(lines, &[], &mut symbols).unwrap();
+        let mut state = new_state(symbols.regs, symbols.reg_types, &[], symbols.strings, options);
+        state.valid = program.iter().map(parser::tokens_valid).collect();
+        while (state.ip as usize) < program.len() {
+            try!(execute_in(&mut state, &mut program));
+        }
+        Ok(state)
+    }
+
+    /// Runs the given lines with the given options, and returns the values of their registers, or the first runtime error.
+    fn run(lines: &[&str], options: &Options) -> Result<Vec<Value>, String> {
+        run_state(lines, options).map(|state| state.regs.vec)
+    }
+
+    /// Runs the given lines with the given width and overflow policy, and returns the value of their first register.
+    fn first_register(lines: &[&str], width: Width, overflow: Overflow) -> Result<Value, String> {
+        let options = Options { width: width, overflow: overflow, ..Options::default() };
+        run(lines, &options).map(|regs| regs[0].clone())
+    }
+
+    #[test]
+    fn wrap() {
+        assert_eq!(Width::I32.wrap(i32::max_value() as i128 + 1), i32::min_value() as i128);
+        assert_eq!(Width::I32.wrap(i32::min_value() as i128 - 1), i32::max_value() as i128);
+        assert_eq!(Width::I32.wrap(-1), -1);
+        assert_eq!(Width::I64.wrap(i64::max_value() as i128 + 1), i64::min_value() as i128);
+        assert_eq!(Width::I64.wrap(1 << 64), 0);
+        assert_eq!(Width::I128.wrap(i128::min_value()), i128::min_value());
+    }
+
+    #[test]
+    fn overflow() {
+        for &width in &[Width::I32, Width::I64, Width::I128] {
+            let (min, max) = (width.min_value(), width.max_value());
+            let (def_min, def_max) = (format!("def a {}", min), format!("def a {}", max));
+            let edges: [(&[&str], i128, i128); 5] = [
+                (&[&def_max, "inc a"], min, max),
+                (&[&def_min, "dec a"], max, min),
+                (&[&def_max, "mul a 2"], -2, max),
+                (&[&def_min, "neg a"], min, max),
+                (&[&def_min, "div a -1"], min, max),
+            ];
+            for &(lines, wrapped, saturated) in edges.iter() {
+                assert_eq!(first_register(lines, width, Overflow::Wrap), Ok(Value::Small(wrapped)), "{:?} {:?}", width, lines);
+                assert_eq!(first_register(lines, width, Overflow::Saturate), Ok(Value::Small(saturated)), "{:?} {:?}", width, lines);
+                assert_eq!(first_register(lines, width, Overflow::Trap), Err(format!("Integer overflow ({}-bit)", width.bits())), "{:?} {:?}", width, lines);
+            }
+            // Results right at the limits fit
+            for &overflow in &[Overflow::Wrap, Overflow::Trap, Overflow::Saturate] {
+                assert_eq!(first_register(&[&def_max, "dec a", "inc a"], width, overflow), Ok(Value::Small(max)));
+                assert_eq!(first_register(&[&def_min, "inc a", "dec a"], width, overflow), Ok(Value::Small(min)));
+                assert_eq!(first_register(&[&def_max, "neg a"], width, overflow), Ok(Value::Small(-max)));
+            }
+        }
+
+        // Big registers grow instead
+        assert_eq!(first_register(&[&format!("def a {}", i128::max_value()), "inc a"], Width::Big, Overflow::Trap),
+                   Ok(Value::from_big(BigInt::from(i128::max_value()) + 1)));
+
+        // Registers with a declared type wrap around whatever the policy
+        for &overflow in &[Overflow::Wrap, Overflow::Trap, Overflow::Saturate] {
+            assert_eq!(first_register(&["def a:i8 127", "inc a"], Width::I32, overflow), Ok(Value::Small(-128)));
+            assert_eq!(first_register(&["def a:u8 0", "dec a"], Width::I32, overflow), Ok(Value::Small(255)));
+            assert_eq!(first_register(&["def a:i16 -32768", "neg a"], Width::I32, overflow), Ok(Value::Small(-32768)));
+            assert_eq!(first_register(&["def a:i32 -2147483648", "div a -1"], Width::I64, overflow), Ok(Value::Small(-2147483648)));
+            assert_eq!(first_register(&["def a:u64 0", "dect a 1"], Width::I32, overflow), Ok(Value::Small(u64::max_value() as i128)));
+            assert_eq!(first_register(&["def a:i64 9223372036854775807", "mul a 2"], Width::I128, overflow), Ok(Value::Small(-2)));
+        }
+    }
+
+    #[test]
+    fn division() {
+        // Dividend, divisor, and the quotient and remainder when truncating and when flooring
+        let cases: [(i128, i128, i128, i128, i128, i128); 7] = [
+            (7, 2, 3, 1, 3, 1),
+            (-7, 2, -3, -1, -4, 1),
+            (7, -2, -3, 1, -4, -1),
+            (-7, -2, 3, -1, 3, -1),
+            // Whole quotients are the same either way
+            (6, -3, -2, 0, -2, 0),
+            (-6, -3, 2, 0, 2, 0),
+            (0, -2, 0, 0, 0, 0),
+        ];
+        for &(dividend, divisor, truncated, truncated_rem, floored, floored_rem) in cases.iter() {
+            for &(division, quotient, remainder) in &[(Division::Truncate, truncated, truncated_rem), (Division::Floor, floored, floored_rem)] {
+                let options = Options { division: division, ..Options::default() };
+                let (div, mod_) = (format!("div a {}", divisor), format!("mod b {}", divisor));
+                let lines = [&format!("def a {}", dividend) as &str, &format!("def b {}", dividend), &div, &mod_];
+                assert_eq!(run(&lines, &options), Ok(vec![Value::Small(quotient), Value::Small(remainder)]), "{} / {} ({:?})", dividend, divisor, division);
+                // Registers with a declared type round the same way
+                let lines = [&format!("def a:i8 {}", dividend) as &str, &format!("def b:i8 {}", dividend), &div, &mod_];
+                assert_eq!(run(&lines, &options), Ok(vec![Value::Small(quotient), Value::Small(remainder)]), "{} / {} ({:?}, i8)", dividend, divisor, division);
+            }
+        }
+
+        // So do big registers, here with ±(2^130 + 1) divided by ±2
+        let half = BigInt::from(1) << 129;
+        let big_cases: [(bool, i128, BigInt, i128, BigInt, i128); 4] = [
+            (false, 2, half.clone(), 1, half.clone(), 1),
+            (true, 2, -half.clone(), -1, -half.clone() - 1, 1),
+            (false, -2, -half.clone(), 1, -half.clone() - 1, -1),
+            (true, -2, half.clone(), -1, half.clone(), -1),
+        ];
+        for &(negative, divisor, ref truncated, truncated_rem, ref floored, floored_rem) in big_cases.iter() {
+            for &(division, quotient, remainder) in &[(Division::Truncate, truncated, truncated_rem), (Division::Floor, floored, floored_rem)] {
+                let options = Options { width: Width::Big, division: division, ..Options::default() };
+                let (div, mod_) = (format!("div a {}", divisor), format!("mod b {}", divisor));
+                let mut lines = vec!["def a 1", "def b 0", "shl a 130", "inc a"];
+                if negative {
+                    lines.push("neg a");
+                }
+                lines.extend_from_slice(&["cpy a b", &div, &mod_]);
+                assert_eq!(run(&lines, &options), Ok(vec![Value::from_big(quotient.clone()), Value::Small(remainder)]),
+                           "{}(2^130 + 1) / {} ({:?})", if negative { "-" } else { "" }, divisor, division);
+            }
+        }
+
+        // The minimum divided by -1 overflows, but the remainder is 0
+        for &division in &[Division::Truncate, Division::Floor] {
+            let options = Options { division: division, ..Options::default() };
+            assert_eq!(run(&["def a -2147483648", "def b -2147483648", "div a -1", "mod b -1"], &options),
+                       Ok(vec![Value::Small(-2147483648), Value::Small(0)]));
+            let options = Options { division: division, overflow: Overflow::Trap, ..Options::default() };
+            assert_eq!(run(&["def a -2147483648", "div a -1"], &options), Err("Integer overflow (32-bit)".to_owned()));
+            assert_eq!(run(&["def a 7", "div a 0"], &options), Err("Division by zero".to_owned()));
+            assert_eq!(run(&["def a 7", "mod a 0"], &options), Err("Modulo by zero".to_owned()));
+        }
+    }
+
+    #[test]
+    fn bitwise() {
+        let options = Options::default();
+        let pairs: [(&[&str], &[&str]); 5] = [
+            // Fixed-width shifts only use the lowest bits of the amount
+            (&["def a 5", "shl a 33"], &["def a 5", "shl a 1"]),
+            (&["def a 5", "shl a -1"], &["def a 5", "shl a 31"]),
+            (&["def a -20", "shr a 34"], &["def a -20", "shr a 2"]),
+            (&["def a:u8 1", "shl a 9"], &["def a:u8 1", "shl a 1"]),
+            // XOR with -1 flips every bit
+            (&["def a 12", "xor a -1"], &["def a -13"]),
+        ];
+        for &(lines, same) in pairs.iter() {
+            assert_eq!(run(lines, &options), run(same, &options), "{:?} {:?}", lines, same);
+        }
+        assert_eq!(run(&["def a 5", "shl a 31"], &options), Ok(vec![Value::Small(-2147483648)]));
+        assert_eq!(run(&["def a -20", "shr a 2"], &options), Ok(vec![Value::Small(-5)]));
+        assert_eq!(run(&["def a 12", "def b 12", "and a 10", "or b 3"], &options), Ok(vec![Value::Small(8), Value::Small(15)]));
+        // The remainder takes the sign of the dividend
+        assert_eq!(run(&["def a -7", "def b 7", "mod a 3", "mod b -3"], &options), Ok(vec![Value::Small(-1), Value::Small(1)]));
+
+        // Big registers shift by the whole amount, within limits
+        let options = Options { width: Width::Big, ..Options::default() };
+        assert_eq!(run(&["def a 1", "shl a 200", "shr a 199"], &options), Ok(vec![Value::Small(2)]));
+        assert_eq!(run(&["def a 1", "shl a -1"], &options), Err(format!("Shift amount (-1) should be between 0 and {}", BIG_SHIFT_LIMIT)));
+    }
+
+    #[test]
+    fn jumps() {
+        let options = Options::default();
+        // Each jump skips `inc a` when taken
+        let jumps = [("jz 0 2", true), ("jz y 2", false), ("jnz y 2", true), ("jnz 0 2", false),
+                     ("jeq x -3 2", true), ("jeq x y 2", false), ("jne x y 2", true), ("jne y 5 2", false),
+                     ("jlt x y 2", true), ("jlt y x 2", false), ("jlt x x 2", false),
+                     ("jgt y x 2", true), ("jgt x y 2", false), ("jgt y y 2", false)];
+        for &(jump, taken) in jumps.iter() {
+            let regs = run(&["def a 0", "def x -3", "def y 5", jump, "inc a"], &options).map(|regs| regs[0].clone());
+            assert_eq!(regs, Ok(Value::Small(if taken { 0 } else { 1 })), "{}", jump);
+        }
+        // Backward jumps loop, jumps past the end end the program, and jumps before the start fail
+        assert_eq!(run(&["def a 0", "def n 3", "inct a n", "dec n", "jgt n 0 -2"], &options), Ok(vec![Value::Small(6), Value::Small(0)]));
+        assert_eq!(run(&["def a 0", "jeq a 0 100", "inc a"], &options), Ok(vec![Value::Small(0)]));
+        assert_eq!(run(&["def a 0", "jlt a 1 -5"], &options), Err("Jump target -4 is before the start of the program".to_owned()));
+
+        // Big registers compare by value
+        let options = Options { width: Width::Big, ..Options::default() };
+        assert_eq!(run(&["def a 0", "def b 1", "shl b 200", "jz b 2", "inc a", "jgt b 1 2", "inc a"], &options).map(|regs| regs[0].clone()),
+                   Ok(Value::Small(1)));
+    }
+
+    #[test]
+    fn calls() {
+        let options = Options::default();
+        // RET goes back to the line after the CALL
+        assert_eq!(run(&["def a 0", "call 3", "inc a", "jnz 1 3", "inct a 10", "ret"], &options), Ok(vec![Value::Small(11)]));
+        assert_eq!(run(&["def a 0", "ret"], &options), Err("Call stack underflow (RET without CALL)".to_owned()));
+
+        // Each pass of the loop calls one level deeper until n is 0, so n - 1 calls are nested
+        let nested = |n: usize| run(&[&format!("def n {}", n) as &str, "dec n", "jz n 2", "call -2"], &options);
+        assert_eq!(nested(CALL_STACK_LIMIT + 1), Ok(vec![Value::Small(0)]));
+        assert_eq!(nested(CALL_STACK_LIMIT + 2), Err(format!("Call stack overflow (more than {} nested calls)", CALL_STACK_LIMIT)));
+    }
+
+    #[test]
+    fn stack() {
+        let options = Options::default();
+        // Last in, first out, and PEEK leaves the value on the stack
+        assert_eq!(run(&["def a 0", "def b 0", "def c 0", "push 1", "push 2", "peek a", "pop b", "pop c"], &options),
+                   Ok(vec![Value::Small(2), Value::Small(2), Value::Small(1)]));
+        assert_eq!(run(&["def a 0", "push 1", "pop a", "pop a"], &options), Err("Stack underflow (POP on empty stack)".to_owned()));
+        assert_eq!(run(&["def a 0", "peek a"], &options), Err("Stack underflow (PEEK on empty stack)".to_owned()));
+
+        // The limit is the number of values the stack can hold
+        let options = Options { stack_limit: 2, ..Options::default() };
+        assert_eq!(run(&["def a 0", "push 1", "push 2", "pop a", "push 3"], &options), Ok(vec![Value::Small(2)]));
+        assert_eq!(run(&["def a 0", "push 1", "push 2", "push 3"], &options), Err("Stack overflow (more than 2 values)".to_owned()));
+        let options = Options { stack_limit: 0, ..Options::default() };
+        assert_eq!(run(&["def a 0", "push a"], &options), Err("Stack overflow (more than 0 values)".to_owned()));
+    }
+
+    #[test]
+    fn memory() {
+        let options = Options::default();
+        // Buffers start out as zeros, and each one holds its own values
+        let lines = ["def a 0", "def b 0", "def c 0", "mem buf 3", "mem other 1", "store buf 2 42", "store other 0 7",
+                     "load a buf 2", "load b buf 0", "load c other 0"];
+        assert_eq!(run(&lines, &options), Ok(vec![Value::Small(42), Value::Small(0), Value::Small(7)]));
+
+        let out_of_bounds = [("load a buf 3", 3), ("load a buf -1", -1), ("store buf 4 1", 4), ("store buf -2147483648 1", -2147483648)];
+        for &(line, index) in out_of_bounds.iter() {
+            assert_eq!(run(&["def a 0", "mem buf 3", line], &options),
+                       Err(format!("Index {} is out of bounds for a memory buffer of size 3", index)), "{}", line);
+        }
+        // A buffer of one value only has index 0
+        assert_eq!(run(&["def a 0", "mem buf 1", "load a buf 1"], &options),
+                   Err("Index 1 is out of bounds for a memory buffer of size 1".to_owned()));
+    }
+
+    #[test]
+    fn hlt() {
+        let exit_code = |lines: &[&str]| run_state(lines, &Options::default()).map(|state| (state.exit_code, state.regs.vec));
+        // HLT ends the program at once, with 0 unless it is given a status
+        assert_eq!(exit_code(&["def a 0", "hlt", "inc a"]), Ok((Some(0), vec![Value::Small(0)])));
+        assert_eq!(exit_code(&["def a 3", "hlt a", "inc a"]), Ok((Some(3), vec![Value::Small(3)])));
+        assert_eq!(exit_code(&["def a -1", "hlt a"]), Ok((Some(-1), vec![Value::Small(-1)])));
+        assert_eq!(exit_code(&["def a 0", "inc a"]), Ok((None, vec![Value::Small(1)])));
+
+        // Statuses wider than an int are truncated to one
+        let options = Options { width: Width::I64, ..Options::default() };
+        assert_eq!(run_state(&["hlt 4294967298"], &options).map(|state| state.exit_code), Ok(Some(2)));
+    }
+
+    #[test]
+    fn assert() {
+        let options = Options::default();
+        assert_eq!(run(&["def a 3", "def b 4", "assert a < b", "assert a <= 3 \"a is too big\"", "assert b != a"], &options),
+                   Ok(vec![Value::Small(3), Value::Small(4)]));
+        assert_eq!(run(&["def a 3", "def b 4", "assert a <= 2 \"a is too big\"", "inc a"], &options),
+                   Err("Assertion failed: assert a <= 2 \"a is too big\"\nRegisters: a = 3, b = 4".to_owned()));
+        assert_eq!(run(&["assert 1 == 2"], &options), Err("Assertion failed: assert 1 == 2\nRegisters: (none)".to_owned()));
+
+        // --no-asserts skips them
+        let options = Options { asserts: false, ..Options::default() };
+        assert_eq!(run(&["def a 3", "assert a <= 2 \"a is too big\"", "inc a"], &options), Ok(vec![Value::Small(4)]));
+    }
+
+    #[test]
+    fn ports() {
+        let options = Options { ports: btreemap!(5 => Port::Memory), ..Options::default() };
+        // INP reads back what OUTP wrote, in order, and -1 once the port is empty
+        let lines = ["def a 0", "def b 0", "def c 0", "outp 5 104", "outp 5 255", "inp a 5", "inp b 5", "inp c 5"];
+        assert_eq!(run(&lines, &options), Ok(vec![Value::Small(104), Value::Small(255), Value::Small(-1)]));
+
+        let errors = [("outp 5 256", "Value 256 does not fit in a byte".to_owned()),
+                      ("outp 5 -1", "Value -1 does not fit in a byte".to_owned()),
+                      ("inp a 0", "Port 0 is not bound to anything (see --port)".to_owned()),
+                      ("outp 7 a", "Port 7 is not bound to anything (see --port)".to_owned()),
+                      ("inp a 300", format!("Port 300 does not exist (ports are numbered from 0 to {})", PORT_COUNT - 1))];
+        for &(line, ref error) in errors.iter() {
+            assert_eq!(run(&["def a 0", line], &options), Err(error.clone()), "{}", line);
+        }
+    }
+
+    #[test]
+    fn input() {
+        // INN skips whitespace and stops at the first byte that doesn't belong to the integer, which is left for the next read
+        let mut stdin: &[u8] = b" \n-42 +7x";
+        assert_eq!(stdin_integer(&mut stdin, Width::I32), Ok(Value::Small(-42)));
+        assert_eq!(stdin_integer(&mut stdin, Width::I32), Ok(Value::Small(7)));
+        assert_eq!(stdin_integer(&mut stdin, Width::I32), Err("Expected an integer from STDIN".to_owned()));
+        assert_eq!(stdin_byte(&mut stdin, true), Ok(Some(b'x')));
+        // At the end of STDIN, INN fails and INCH gives -1 (see exec::inch)
+        assert_eq!(stdin_integer(&mut stdin, Width::I32), Err("Expected an integer from STDIN".to_owned()));
+        assert_eq!(stdin_byte(&mut stdin, true), Ok(None));
+
+        let bad: [(&[u8], Width, &str); 4] = [
+            (b"-", Width::I32, "'-' from STDIN is not a 32-bit integer"),
+            (b"2147483648", Width::I32, "'2147483648' from STDIN is not a 32-bit integer"),
+            (b"170141183460469231731687303715884105728", Width::I128, "'170141183460469231731687303715884105728' from STDIN is not a 128-bit integer"),
+            (b"+", Width::Big, "'+' from STDIN is not an integer"),
+        ];
+        for &(input, width, error) in bad.iter() {
+            let mut stdin = input;
+            assert_eq!(stdin_integer(&mut stdin, width), Err(error.to_owned()));
+        }
+        let mut stdin: &[u8] = b"2147483648 -170141183460469231731687303715884105729";
+        assert_eq!(stdin_integer(&mut stdin, Width::I64), Ok(Value::Small(2147483648)));
+        assert_eq!(stdin_integer(&mut stdin, Width::Big), Ok(Value::from_big(-BigInt::from(i128::max_value()) - 2)));
+    }
+
+    #[test]
+    fn tgl() {
+        let options = Options::default();
+        // The example of Advent of Code 2016 Day 23
+        assert_eq!(run(&["def a 0", "cpy 2 a", "tgl a", "tgl a", "tgl a", "cpy 1 a", "dec a", "dec a"], &options), Ok(vec![Value::Small(3)]));
+        // Targets outside of the program are ignored
+        assert_eq!(run(&["def a 0", "tgl 10", "tgl -10", "tgl 2147483647", "inc a"], &options), Ok(vec![Value::Small(1)]));
+        // `jnz 1 2` toggled into `cpy 1 2` is skipped, until it is toggled back
+        assert_eq!(run(&["def a 0", "tgl 1", "jnz 1 2", "inc a"], &options), Ok(vec![Value::Small(1)]));
+        assert_eq!(run(&["def a 0", "tgl 2", "tgl 1", "jnz 1 2", "inc a"], &options), Ok(vec![Value::Small(0)]));
+
+        let toggles = [("inc", 1, "dec"), ("dec", 1, "inc"), ("tgl", 1, "inc"), ("outn", 1, "inc"), ("hlt", 1, "inc"),
+                       ("jnz", 2, "cpy"), ("cpy", 2, "jnz"), ("mul", 2, "jnz"),
+                       ("hlt", 0, "hlt"), ("jeq", 3, "jeq")];
+        for &(keyword, param_count, toggled) in toggles.iter() {
+            assert_eq!(parser::toggled_keyword(keyword, param_count), toggled, "{} with {} parameter(s)", keyword, param_count);
+        }
+    }
+
+    #[test]
+    fn trap() {
+        // The handler adds the error code and the line number (from 1, since `run` gives no origins) to the digits of c and l
+        let handler = ["def c 0", "def l 0", "def n 7", "def after 0",
+                       "proc h code line", "mul c 10", "inct c code", "mul l 100", "inct l line", "endp"];
+        let mut lines = handler.to_vec();
+        lines.extend_from_slice(&["trap div h", "trap mem h", "div n 0", "inc after", "mem buf 4", "load n buf 4", "inc after"]);
+        // The failing lines change nothing, and the program resumes after them
+        let regs = run(&lines, &Options::default()).map(|regs| regs[..4].to_vec());
+        assert_eq!(regs, Ok(vec![Value::Small(14), Value::Small(1316), Value::Small(7), Value::Small(2)]));
+
+        // TRAP with 0 gives the errors back to the default, and other kinds are not handed to the handler
+        let mut lines = handler.to_vec();
+        lines.extend_from_slice(&["trap div h", "trap div 0", "div n 0"]);
+        assert_eq!(run(&lines, &Options::default()), Err("Division by zero".to_owned()));
+        let mut lines = handler.to_vec();
+        lines.extend_from_slice(&["trap div h", "pop n"]);
+        assert_eq!(run(&lines, &Options::default()), Err("Stack underflow (POP on empty stack)".to_owned()));
+    }
+
+    #[test]
+    fn random() {
+        // The reference outputs of SplitMix64, which the C backend has to reproduce
+        let mut state: u64 = 0;
+        let outputs: Vec<u64> = (0..3).map(|_| next_random(&mut state)).collect();
+        assert_eq!(outputs, vec![0xE220A8397B1DCDAF, 0x6E789E6AA1B965F4, 0x06C45D188009454F]);
+        let mut state: u64 = 1234567;
+        let outputs: Vec<u64> = (0..3).map(|_| next_random(&mut state)).collect();
+        assert_eq!(outputs, vec![0x599ED017FB08FC85, 0x2C73F08458540FA5, 0x883EBCE5A3F27C77]);
     }
 }
@@ -1,84 +1,226 @@
 // The loader of files for ASMBI. A function here is directly called from main.rs.
 use std::io::{Read, Write};
-use std::fs::{File, OpenOptions};
-use std::ops::Index;
+use std::fs::File;
+use std::slice;
 use interpret;
 use parser;
 use parser::Token;
 use gen_c;
 use bytecode;
+use preprocess;
+use options::{Options, Width};
+use value::Value;
 
 macro_rules! try_do_res {
     ( $fun:expr, $err:expr ) => (try_failsafe!($fun, $err.to_owned()));
 }
 
-macro_rules! index_option {
-    ( $vec:expr, $index:expr ) => (if $index >= $vec.len() {
-        None
-    } else {
-        Some($vec.index($index))
-    })
+/// A program loaded for running, with the origin of each of its lines for runtime errors.
+struct Program {
+    state: interpret::AsmbiState,
+    tokens: Vec<Vec<Token>>,
+    origins: Vec<String>,
 }
 
-pub fn run_file(filename: &str) -> Result<u64, String> {
-    let fstr = file_to_string!(filename);
+impl Program {
+    fn new(mut state: interpret::AsmbiState, tokens: Vec<Vec<Token>>, origins: Vec<String>) -> Self {
+        // The handlers of TRAP receive the line numbers of the origins
+        state.line_numbers = origins.iter().map(|origin| parser::origin_line(origin)).collect();
+        state.valid = tokens.iter().map(parser::tokens_valid).collect();
+        Program {
+            state: state,
+            tokens: tokens,
+            origins: origins,
+        }
+    }
 
-    let mut regs: Vec<String> = Vec::new();
-    let mut ftoks: Vec<Vec<Token>> = Vec::new();
+    fn ended(&self) -> bool {
+        (self.state.ip as usize) >= self.tokens.len()
+    }
 
-    let mut line_count: u64 = 0;
+    /// Returns where the line at the instruction pointer comes from, for runtime errors.
+    fn location(&self) -> String {
+        match self.origins.get(self.state.ip as usize) {
+            Some(origin) => origin.clone(),
+            // Older bytecode files do not say where their lines come from
+            None => format!("Error executing bytecode tokens, group {}", self.state.ip)
+        }
+    }
+}
+
+/// Loads the given ASMB+ file for running. `defines` holds the names and values for the preprocessor.
+fn load_file(filename: &str, defines: &[(String, String)], options: &Options) -> Result<Program, String> {
+    let source = try!(preprocess::preprocess(filename, defines));
+    load_lines(&source.as_strs(), &source.origins, options)
+}
 
+/// Loads the given preprocessed lines for running. `origins` holds where each line comes from (see `parser::line_origin`).
+fn load_lines(lines: &[&str], origins: &[String], options: &Options) -> Result<Program, String> {
+    let mut symbols = parser::Symbols::new();
+    symbols.width = options.width;
     // ftoks: File tokens
-    for line in fstr.lines() {
-        if let Some(tokens) = try!(parser::to_tokens(line, &mut regs)) {
-            ftoks.push(tokens);
+    let ftoks: Vec<Vec<Token>> = try!(parser::to_program(lines, origins, &mut symbols));
+
+    let buffer_sizes: Vec<u32> = symbols.buffers.iter().map(|&(_, size)| size).collect();
+    Ok(Program::new(interpret::new_state(symbols.regs, symbols.reg_types, &buffer_sizes, symbols.strings, options), ftoks, symbols.origins))
+}
+
+/// Runs the given programs together until every one of them has ended, or until they are deadlocked, which is when every program that hasn't ended is waiting on RCV.
+/// Returns whether they were deadlocked, or the index of the program that failed with its error.
+/// Each program runs until it ends or waits, then the next one takes over. What a program sends goes to the program after it, and from the last one to the first one.
+fn run_programs(programs: &mut [Program]) -> Result<bool, (usize, String)> {
+    let count = programs.len();
+    loop {
+        let mut progressed = false;
+        for index in 0..count {
+            while !programs[index].ended() {
+                {
+                    let program = &mut programs[index];
+                    if let Err(errno) = interpret::run_in(&mut program.state, &mut program.tokens) {
+                        return Err((index, format!("{}: {}", program.location(), errno)));
+                    }
+                }
+                if !programs[index].state.outbox.is_empty() {
+                    let sent: Vec<Value> = programs[index].state.outbox.drain(..).collect();
+                    programs[(index + 1) % count].state.inbox.extend(sent);
+                }
+                if programs[index].state.waiting {
+                    break;
+                }
+                progressed = true;
+            }
+        }
+        if programs.iter().all(|program| program.ended()) {
+            return Ok(false);
+        }
+        if !progressed {
+            return Ok(true);
         }
     }
+}
 
-    let mut state = interpret::new_state(regs.len());
-    while let Some(line) = index_option!(ftoks, state.ip as usize) {
-        if let Err(errno) = interpret::execute(&mut state, line) {
-            return Err(format!("Interpretation of line {} failed: {}", state.ip, errno));
-        }
-        state.ip += 1;
-        line_count += 1;
+/// Runs a program on its own, which receives what it sends itself, and returns its exit status (see HLT).
+fn run_alone(mut program: Program) -> Result<i32, String> {
+    let deadlocked = match run_programs(slice::from_mut(&mut program)) {
+        Ok(deadlocked) => deadlocked,
+        Err((_, errno)) => return Err(errno)
+    };
+    if deadlocked {
+        return Err(format!("{}: Deadlock (RCV is waiting for a value, but the program is the only one that could send it)", program.location()));
+    }
+    Ok(program.state.exit_code.unwrap_or(0))
+}
+
+/// Runs the given ASMB+ file and returns its exit status (see HLT).
+pub fn run_file(filename: &str, options: &Options) -> Result<i32, String> {
+    run_alone(try!(load_file(filename, &options.defines, options)))
+}
+
+/// Outcome of `run_duet`
+pub struct Duet {
+    /// Amount of values sent by each program
+    pub sent: Vec<u64>,
+    /// Whether the programs stopped because every one that hadn't ended was waiting on RCV
+    pub deadlocked: bool,
+    /// Exit status of the first program (see HLT)
+    pub exit_code: i32,
+}
+
+/// Runs the given ASMB+ files together (see `run_programs`); the same file may be given more than once.
+/// PROGRAM_ID is defined for the preprocessor as the position of each file, from 0, so that copies of a file can tell each other apart.
+pub fn run_duet(filenames: &[&str], options: &Options) -> Result<Duet, String> {
+    let mut programs: Vec<Program> = Vec::new();
+    for (index, filename) in filenames.iter().enumerate() {
+        let mut defines = options.defines.clone();
+        defines.push(("PROGRAM_ID".to_owned(), index.to_string()));
+        programs.push(try_err_fallthru!(load_file(filename, &defines, options), format!("Program {} ({}): ", index, filename)));
     }
-    Ok(line_count)
+    let deadlocked = match run_programs(&mut programs) {
+        Ok(deadlocked) => deadlocked,
+        Err((index, errno)) => return Err(format!("Program {} ({}): {}", index, filenames[index], errno))
+    };
+    Ok(Duet {
+        sent: programs.iter().map(|program| program.state.sent).collect(),
+        deadlocked: deadlocked,
+        exit_code: programs[0].state.exit_code.unwrap_or(0),
+    })
 }
 
-pub fn compile_file(filename: &str) -> Result<String, String> {
-    let fstr = file_to_string!(filename);
-    gen_c::compose(&fstr.lines().collect::<Vec<_>>())
+pub fn compile_file(filename: &str, options: &Options) -> Result<String, String> {
+    let source = try!(preprocess::preprocess(filename, &options.defines));
+    gen_c::compose(&source.as_strs(), &source.origins, options)
 }
 
-pub fn convert_to_bytecode(src_file: &str, target_file: &str) -> Result<(), String> {
-    let src = file_to_string!(src_file);
-    let mut outfile: File = try_do_res!(OpenOptions::new()
-        .write(true)
-        .create(true)
-        .open(target_file), "Unable to create file");
+pub fn convert_to_bytecode(src_file: &str, target_file: &str, options: &Options) -> Result<(), String> {
+    let source = try!(preprocess::preprocess(src_file, &options.defines));
+    let mut outfile: File = try_do_res!(File::create(target_file), "Unable to create file");
     try_do_res!(
         outfile.write(
-            &*try_err_fallthru!(bytecode::to_bytecode(&src.lines().collect::<Vec<_>>()), "Bytecode generation failed: ")),
+            &*try_err_fallthru!(bytecode::to_bytecode(&source.as_strs(), &source.origins, options), "Bytecode generation failed: ")),
             "Unable to write to bytecode output file"
     );
     Ok(())
 }
 
-pub fn run_bytecode(bt_path: &str) -> Result<u64, String> {
+/// Runs the given bytecode file and returns its exit status (see HLT).
+pub fn run_bytecode(bt_path: &str, options: &Options) -> Result<i32, String> {
     let bytes = file_to_bytevec!(bt_path);
-    let (regcount, tokens) = try_err_fallthru!(bytecode::from_bytecode(&bytes), 
-                                              "Bytecode interpretation (to tokens) failed: ");
+    let program = try_err_fallthru!(bytecode::from_bytecode(&bytes), "Bytecode interpretation (to tokens) failed: ");
+
+    // The bytecode was converted for a certain register width, which its literals are encoded in, so the options given for running it have to agree
+    let width_name = |width: Width| if width == Width::Big { "big".to_owned() } else { width.bits().to_string() };
+    let settings = [("width", width_name(options.width), width_name(program.width)),
+                    ("overflow", format!("{:?}", options.overflow).to_lowercase(), format!("{:?}", program.overflow).to_lowercase()),
+                    ("division", format!("{:?}", options.division).to_lowercase(), format!("{:?}", program.division).to_lowercase())];
+    for &(name, ref given, ref converted) in settings.iter() {
+        if options.given.contains(&name) && given != converted {
+            return Err(format!("--{} {} conflicts with the bytecode file, which was converted with --{} {}", name, given, name, converted));
+        }
+    }
 
-    let mut state = interpret::new_state(regcount);
-    let mut linecount = 0u64;
+    let mut state = interpret::new_state(program.reg_names, program.reg_types, &program.buffer_sizes, program.strings, options);
+    state.width = program.width;
+    state.overflow = program.overflow;
+    state.division = program.division;
 
-    while let Some(line_tokens) = index_option!(tokens, state.ip as usize) {
-        try_err_fallthru!(interpret::execute(&mut state, &line_tokens),
-                          format!("Error executing bytecode tokens, group {}: ", state.ip));
-        linecount += 1;
-        state.ip += 1;
+    run_alone(Program::new(state, program.tokens, program.origins))
+}
+
+#[cfg(test)]
+mod loader_test {
+    use loader::*;
+
+    /// Loads each program from its lines, as if they were files without preprocessor directives.
+    fn load_programs(programs: &[&[&str]]) -> Vec<Program> {
+        programs.iter().map(|lines| load_lines(lines, &[], &Options::default()).unwrap()).collect()
     }
 
-    Ok(linecount)
-}
\ No newline at end of file
+    #[test]
+    fn duet() {
+        // What a program sends goes to the next program, and from the last one back to the first one
+        let mut programs = load_programs(&[&["def a 0", "snd 1", "snd 2", "rcv a", "hlt a"],
+                                           &["def x 0", "def y 0", "rcv x", "rcv y", "inct x y", "snd x"]]);
+        assert_eq!(run_programs(&mut programs), Ok(false));
+        assert_eq!(programs.iter().map(|program| program.state.sent).collect::<Vec<u64>>(), vec![2, 1]);
+        assert_eq!(programs[0].state.exit_code, Some(3));
+        assert_eq!(programs[1].state.regs.vec, vec![Value::Small(3), Value::Small(2)]);
+
+        // A program on its own receives what it sends itself
+        let mut programs = load_programs(&[&["def a 0", "snd 7", "rcv a"]]);
+        assert_eq!(run_programs(&mut programs), Ok(false));
+        assert_eq!(programs[0].state.regs.vec, vec![Value::Small(7)]);
+
+        // Every program that hasn't ended is waiting on RCV
+        let mut programs = load_programs(&[&["def a 0", "rcv a"], &["def b 0", "snd 1", "rcv b", "rcv b"], &["hlt"]]);
+        assert_eq!(run_programs(&mut programs), Ok(true));
+        assert!(!programs[0].ended() && !programs[1].ended() && programs[2].ended());
+        assert_eq!(programs[0].state.sent + programs[1].state.sent, 1);
+
+        // Errors name the program that failed and the line
+        let mut programs = load_programs(&[&["def a 0", "rcv a"], &["def b 1", "div b 0"]]);
+        match run_programs(&mut programs) {
+            Err((index, errno)) => assert!(index == 1 && errno.starts_with("Line 2: "), "{}", errno),
+            Ok(_) => panic!("Division by 0 did not fail")
+        }
+    }
+}
@@ -55,4 +55,12 @@ macro_rules! try_err_fallthru {
 /// Aborts the program with exit code 1.
 macro_rules! abort {
     () => (std::process::exit(1))
+}
+
+/// Ends the program with the given exit code, flushing STDOUT first since `std::process::exit` does not.
+macro_rules! exit_with {
+    ( $code:expr ) => ({
+        io::stdout().flush().expect("Stdout clogged");
+        std::process::exit($code)
+    })
 }
\ No newline at end of file
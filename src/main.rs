@@ -6,6 +6,8 @@ extern crate regex;
 extern crate clap;
 extern crate ansi_term;
 extern crate byteorder;
+extern crate num_bigint;
+extern crate num_traits;
 use clap::{Arg, App};
 use std::io;
 use std::io::Write;
@@ -16,6 +18,10 @@ pub mod interpret;
 pub mod gen_c;
 pub mod loader;
 pub mod bytecode;
+pub mod options;
+pub mod preprocess;
+pub mod value;
+pub mod ports;
 
 /// Main function for the CLI. Uses `clap` for args handling.
 fn main() {
@@ -52,35 +58,169 @@ fn main() {
 			.help("Reads ASMBP bytecode from the specified input file and executes the instructions")
 			.takes_value(true)
 			.conflicts_with_all(&["interpret", "compile", "to-bytecode"]))
+		.arg(Arg::with_name("duet")
+			.long("duet")
+			.multiple(true)
+			.value_name("asmb files")
+			.help("Interprets the given ASMB files together, each one receiving (RCV) what the one before it sends (SND), and reports how many values each one sent; \
+				PROGRAM_ID is defined as the position of each file, from 0, and the same file can be given more than once")
+			.takes_value(true)
+			.conflicts_with_all(&["interpret", "compile", "to-bytecode", "from-bytecode"]))
+		.arg(Arg::with_name("stack-limit")
+			.long("stack-limit")
+			.value_name("value count")
+			.help("Sets the maximum amount of values on the data stack (PUSH/POP); defaults to 65536")
+			.takes_value(true))
+		.arg(Arg::with_name("no-asserts")
+			.long("no-asserts")
+			.help("Skips ASSERT instructions when interpreting, running bytecode, or compiling to C"))
+		.arg(Arg::with_name("width")
+			.long("width")
+			.value_name("bits")
+			.help("Sets the size of registers and values to 32, 64, or 128 bits, or to arbitrary precision (big, not supported by the C compiler); defaults to 32")
+			.takes_value(true)
+			.possible_values(&["32", "64", "128", "big"]))
+		.arg(Arg::with_name("overflow")
+			.long("overflow")
+			.value_name("policy")
+			.help("Sets what happens when arithmetic overflows: wrap around, trap (stop with an error), or saturate; defaults to wrap")
+			.takes_value(true)
+			.possible_values(&["wrap", "trap", "saturate"]))
+		.arg(Arg::with_name("division")
+			.long("division")
+			.value_name("rounding")
+			.help("Sets how DIV rounds quotients that are not whole: toward zero (truncate) or toward negative infinity (floor), with MOD following suit; defaults to truncate")
+			.takes_value(true)
+			.possible_values(&["truncate", "floor"]))
+		.arg(Arg::with_name("seed")
+			.long("seed")
+			.value_name("value")
+			.help("Sets the initial seed of RND, from 0 to 18446744073709551615 (SEED can change it later); defaults to 0")
+			.takes_value(true))
+		.arg(Arg::with_name("port")
+			.long("port")
+			.value_name("N=TARGET")
+			.help("Binds I/O port N (0 to 255) of OUTP and INP to stdin, stdout, stderr, mem (a buffer that INP reads back), or a file path; \
+				ports 0, 1 and 2 start out bound to stdin, stdout and stderr")
+			.takes_value(true)
+			.multiple(true)
+			.number_of_values(1))
+		.arg(Arg::with_name("define")
+			.short("D")
+			.long("define")
+			.value_name("NAME=VAL")
+			.help("Defines a constant for %ifdef and %const in the preprocessor; VAL defaults to 1")
+			.takes_value(true)
+			.multiple(true)
+			.number_of_values(1))
 		.get_matches();
 
+	let mut options = options::Options::default();
+	if let Some(limit) = clap_matches.value_of("stack-limit") {
+		match limit.parse::<usize>() {
+			Ok(limit) => options.stack_limit = limit,
+			Err(_) => {
+				println!("{} '{}' is not a valid stack limit", Red.paint("Invalid option:"), limit);
+				abort!();
+			}
+		}
+	}
+
+	options.asserts = !clap_matches.is_present("no-asserts");
+	// clap has already checked these against the possible values
+	if let Some(bits) = clap_matches.value_of("width") {
+		options.width = options::Width::from_bits(bits).unwrap();
+		options.given.push("width");
+	}
+	if let Some(policy) = clap_matches.value_of("overflow") {
+		options.overflow = options::Overflow::from_name(policy).unwrap();
+		options.given.push("overflow");
+	}
+	if let Some(seed) = clap_matches.value_of("seed") {
+		match seed.parse::<u64>() {
+			Ok(seed) => options.seed = seed,
+			Err(_) => {
+				println!("{} '{}' is not a valid seed", Red.paint("Invalid option:"), seed);
+				abort!();
+			}
+		}
+	}
+	if let Some(rounding) = clap_matches.value_of("division") {
+		options.division = options::Division::from_name(rounding).unwrap();
+		options.given.push("division");
+	}
+	if let Some(bindings) = clap_matches.values_of("port") {
+		for binding in bindings {
+			let mut parts = binding.splitn(2, '=');
+			match (parts.next().unwrap().parse::<u8>(), parts.next()) {
+				(Ok(port), Some(target)) if !target.is_empty() => {
+					options.ports.insert(port, options::Port::from_target(target));
+				},
+				_ => {
+					println!("{} '{}' is not a valid port binding (expected N=TARGET, with N from 0 to 255)", Red.paint("Invalid option:"), binding);
+					abort!();
+				}
+			}
+		}
+	}
+	if let Some(defines) = clap_matches.values_of("define") {
+		for define in defines {
+			let mut parts = define.splitn(2, '=');
+			let name = parts.next().unwrap().to_owned();
+			options.defines.push((name, parts.next().unwrap_or("1").to_owned()));
+		}
+	}
+
 	if clap_matches.is_present("interpret") {
-		if let Err(errno) = loader::run_file(
-				clap_matches.value_of("interpret").unwrap()) {
-			println!("{} {}", Red.paint("Run file failed:"), errno);
-			abort!();
+		match loader::run_file(clap_matches.value_of("interpret").unwrap(), &options) {
+			Ok(exit_code) => exit_with!(exit_code),
+			Err(errno) => {
+				println!("{} {}", Red.paint("Run file failed:"), errno);
+				abort!();
+			}
+		}
+	} else if clap_matches.is_present("duet") {
+		let filenames: Vec<_> = clap_matches.values_of("duet").unwrap().collect();
+		match loader::run_duet(&filenames, &options) {
+			Ok(duet) => {
+				if duet.deadlocked {
+					println!("Deadlock: every program that hasn't ended is waiting on RCV");
+				}
+				for (index, sent) in duet.sent.iter().enumerate() {
+					println!("Program {} ({}) sent {} value{}", index, filenames[index], sent, if *sent == 1 { "" } else { "s" });
+				}
+				exit_with!(duet.exit_code);
+			},
+			Err(errno) => {
+				println!("{} {}", Red.paint("Run files failed:"), errno);
+				abort!();
+			}
 		}
 	} else if clap_matches.is_present("to-bytecode") {
 		// Convert to bytecode
 		let fileinputs: Vec<_> = clap_matches.values_of("to-bytecode").unwrap().collect();
-		if let Err(problem) = loader::convert_to_bytecode(fileinputs[0], fileinputs[1]) {
+		if let Err(problem) = loader::convert_to_bytecode(fileinputs[0], fileinputs[1], &options) {
 			println!("{} {}", Red.paint("Conversion to bytecode failed:"), problem);
 			abort!();
 		}
 	} else if clap_matches.is_present("from-bytecode") {
 		// Run bytecode
-		if let Err(problem) = loader::run_bytecode(clap_matches.value_of("from-bytecode").unwrap()) {
-			println!("{} {}", Red.paint("Execution of bytecode failed:"), problem);
-			abort!();
-			// TODO: a macro for the procedure above, repeated 3 times.
+		match loader::run_bytecode(clap_matches.value_of("from-bytecode").unwrap(), &options) {
+			Ok(exit_code) => exit_with!(exit_code),
+			Err(problem) => {
+				println!("{} {}", Red.paint("Execution of bytecode failed:"), problem);
+				abort!();
+				// TODO: a macro for the procedure above, repeated 3 times.
+			}
 		}
 	} else if !clap_matches.is_present("compile") {
 		// Enter REPL
 		println!("Welcome to the Assembunny-plus REPL.");
 		println!("Use :help for help, :reg for registers and their values, and :unlicense for the unlicense.");
 		println!("At the > prompt, enter your lines of Assembunny-plus.");
-		let mut state = interpret::new_state(0);
-		let mut regs: Vec<String> = Vec::new();
+		let mut state = interpret::new_state(Vec::new(), Vec::new(), &[], Vec::new(), &options);
+		let mut symbols = parser::Symbols::new();
+		symbols.width = options.width;
 		let mut show_raw_token = false;
 		loop {
 			print!("{}::>", state.ip);
@@ -97,7 +237,7 @@ fn main() {
 					":help" => println!("Not available now"),
 					":reg" => {
 						for (index, val) in state.regs.vec.iter().enumerate() {
-							println!("{} => {}", regs[index], val);
+							println!("{} => {}", symbols.regs[index], val);
 						}
 					},
 					":exit" => {
@@ -113,7 +253,7 @@ fn main() {
 				continue;
 			}
 
-			let tokens = match parser::to_tokens(&input, &mut regs) {
+			let tokens = match parser::to_tokens(&input, &mut symbols, &parser::LabelMap::new(), state.ip) {
 				Ok(opttok) => if opttok.is_none() {
 					continue
 				} else {
@@ -126,8 +266,10 @@ fn main() {
 			};
 
 
-			if str_tokens[0].to_lowercase() == "jnz" {
-				println!("{}", Red.paint("This REPL does not support JNZ."));
+			let keyword = str_tokens[0].to_lowercase();
+			if parser::PARAM_RULES.get(keyword.as_str()).map_or(false, |rule| rule.contains('J')) ||
+					["ret", "tgl", "snd", "rcv", "proc", "endp", "return"].contains(&keyword.as_str()) {
+				println!("{}", Red.paint("This REPL does not support jumps, CALL/RET, procedures, TRAP, TGL, or SND/RCV."));
 				continue;
 			}
 
@@ -137,18 +279,24 @@ fn main() {
 
 			// Since the interpreter is optimized for files, we have to dynamically allocate before `def` lines get executed.
 			if str_tokens[0].to_lowercase() == "def" {
-				state.regs.vec.push(0);
+				state.regs.vec.push(value::Value::Small(0));
+				state.regs.types.push(*symbols.reg_types.last().unwrap());
 			}
+			// Same for the register names and string literals, which the tokenizer has just collected
+			state.reg_names = symbols.regs.clone();
+			state.strings = symbols.strings.clone();
 
 			if let Err(errmsg) = interpret::execute(&mut state, &tokens) {
 				println!("{} {}", Red.paint("Failed:"), errmsg);
+			} else if let Some(exit_code) = state.exit_code {
+				exit_with!(exit_code);
 			} else {
 				state.ip += 1;
 			}
 		}
 	} else {
 		match loader::compile_file(
-				clap_matches.value_of("compile").unwrap()) {
+				clap_matches.value_of("compile").unwrap(), &options) {
 
 			Ok(c_code) => println!("{}", c_code),
 			Err(errno) => {
@@ -0,0 +1,185 @@
+// Options that change how an ASMB+ program behaves, shared by the interpreter, the bytecode runner, and the C generator.
+// main.rs fills these in from the command line; every backend has to honor them the same way.
+
+use std::collections::BTreeMap;
+
+/// Default maximum amount of values on the data stack (see PUSH/POP/PEEK in parser.rs)
+pub const DEFAULT_STACK_LIMIT: usize = 65536;
+
+/// Amount of I/O ports of OUTP and INP, which are numbered from 0
+pub const PORT_COUNT: usize = 256;
+
+pub struct Options {
+
+    /// Maximum amount of values on the data stack; pushing one more is a stack overflow error.
+    pub stack_limit: usize,
+
+    /// Names defined with `-D NAME=VAL` on the command line and their values, for %ifdef and %const in the preprocessor.
+    pub defines: Vec<(String, String)>,
+
+    /// Whether ASSERT is checked; `--no-asserts` turns this off for release builds.
+    pub asserts: bool,
+
+    /// Size of a register (and of every other value: literals, stack entries, memory cells), selected with `--width`.
+    pub width: Width,
+
+    /// What happens when the result of an arithmetic instruction does not fit in `width`, selected with `--overflow`.
+    pub overflow: Overflow,
+
+    /// How DIV and MOD round when the quotient is not whole, selected with `--division`.
+    pub division: Division,
+
+    /// Initial state of the generator of RND (see `interpret::next_random`), selected with `--seed`.
+    pub seed: u64,
+
+    /// What each bound I/O port of OUTP and INP is bound to, selected with `--port N=TARGET`. The other ports are unbound.
+    pub ports: BTreeMap<u8, Port>,
+
+    /// Which of "width", "overflow" and "division" were given on the command line, which a bytecode file has to agree with since it records its own.
+    pub given: Vec<&'static str>,
+
+}
+
+/// Integer sizes that registers can have.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Width {
+    I32,
+    I64,
+    I128,
+    /// Arbitrary precision (`--width big`), supported by the interpreter and the bytecode runner only.
+    /// Literals and constant expressions are still limited to 128 bits; only the results of instructions grow past them.
+    Big,
+}
+
+impl Width {
+    /// Parses the number of bits given to `--width`.
+    pub fn from_bits(bits: &str) -> Option<Self> {
+        match bits {
+            "32" => Some(Width::I32),
+            "64" => Some(Width::I64),
+            "128" => Some(Width::I128),
+            "big" => Some(Width::Big),
+            _ => None
+        }
+    }
+
+    /// Number of bits in a register, or in a literal for big registers.
+    pub fn bits(&self) -> u32 {
+        match *self {
+            Width::I32 => 32,
+            Width::I64 => 64,
+            Width::I128 | Width::Big => 128,
+        }
+    }
+
+    pub fn min_value(&self) -> i128 {
+        match *self {
+            Width::I32 => i32::min_value() as i128,
+            Width::I64 => i64::min_value() as i128,
+            Width::I128 | Width::Big => i128::min_value(),
+        }
+    }
+
+    pub fn max_value(&self) -> i128 {
+        match *self {
+            Width::I32 => i32::max_value() as i128,
+            Width::I64 => i64::max_value() as i128,
+            Width::I128 | Width::Big => i128::max_value(),
+        }
+    }
+
+    pub fn contains(&self, val: i128) -> bool {
+        val >= self.min_value() && val <= self.max_value()
+    }
+
+    /// Returns `val` modulo 2^bits, as a signed integer of this width (two's complement wrap-around).
+    pub fn wrap(&self, val: i128) -> i128 {
+        let unused = 128 - self.bits();
+        (val << unused) >> unused
+    }
+}
+
+/// Policies for arithmetic results that do not fit in the register width.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overflow {
+    /// Two's complement wrap-around, like unsigned arithmetic in C
+    Wrap,
+    /// A runtime error that stops the program
+    Trap,
+    /// The closest value that fits (the minimum or the maximum)
+    Saturate,
+}
+
+impl Overflow {
+    /// Parses the policy given to `--overflow`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "wrap" => Some(Overflow::Wrap),
+            "trap" => Some(Overflow::Trap),
+            "saturate" => Some(Overflow::Saturate),
+            _ => None
+        }
+    }
+}
+
+/// Rounding of DIV (and the matching sign of MOD's remainder).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Division {
+    /// Toward zero, like `/` and `%` in C: -7 / 2 is -3 and -7 % 2 is -1
+    Truncate,
+    /// Toward negative infinity, like `//` and `%` in Python: -7 / 2 is -4 and -7 % 2 is 1
+    Floor,
+}
+
+impl Division {
+    /// Parses the rounding given to `--division`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "truncate" => Some(Division::Truncate),
+            "floor" => Some(Division::Floor),
+            _ => None
+        }
+    }
+}
+
+/// Targets that an I/O port can be bound to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Port {
+    Stdin,
+    Stdout,
+    Stderr,
+    /// A file, opened when the port is first used: for reading if that is INP, or for writing (from scratch) if that is OUTP
+    File(String),
+    /// A buffer in memory, which INP reads back in the order that OUTP wrote to it
+    Memory,
+}
+
+impl Port {
+    /// Parses the target given to `--port N=TARGET`; anything that is not the name of another target is the path of a file.
+    pub fn from_target(target: &str) -> Self {
+        match target {
+            "stdin" => Port::Stdin,
+            "stdout" => Port::Stdout,
+            "stderr" => Port::Stderr,
+            "mem" => Port::Memory,
+            path => Port::File(path.to_owned())
+        }
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            stack_limit: DEFAULT_STACK_LIMIT,
+            defines: Vec::new(),
+            asserts: true,
+            width: Width::I32,
+            overflow: Overflow::Wrap,
+            division: Division::Truncate,
+            seed: 0,
+            // Like the file descriptors of a process
+            ports: btreemap!(0 => Port::Stdin, 1 => Port::Stdout, 2 => Port::Stderr),
+            given: Vec::new(),
+        }
+    }
+}
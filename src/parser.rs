@@ -1,11 +1,10 @@
 // Parser of Assembunny code, part of assembunny_extended
 use std::collections::HashMap;
-use std::str::FromStr;
 use std::fmt;
 use regex::Regex;
-use std::io::Cursor;
-use byteorder::{BigEndian, WriteBytesExt, ReadBytesExt};
 use enum_primitive::FromPrimitive;
+use options::Width;
+use value::{RegType, TYPE_NAMES};
 
 /* Available keywords:
 
@@ -13,7 +12,20 @@ use enum_primitive::FromPrimitive;
      Usage: DEF <register name> <value>
      Note: A register name is case-sensitive and should only contain letters, numbers, and underscore; it should not start with a number; and it should not start with two underscores.
            Do not use DEF to set an existing register to a value. use CPY instead.
-           Each register is actually stored as a 32-bit integer.
+           Each register is actually stored as a 32-bit integer, or a 64-bit or 128-bit one with `--width 64|128`.
+           With `--width big`, registers have arbitrary precision and never overflow (the interpreter and the bytecode runner only; literals are still limited to 128 bits).
+           When INC, INCT, DEC, DECT, MUL, DIV, or NEG overflows, the result wraps around by default; `--overflow trap` makes it an error and `--overflow saturate` clamps it to the smallest or largest integer. SHL, SHR, AND, OR, and XOR just drop the bits that do not fit.
+
+     Usage: DEF <register name>:<type> <value>
+     Example:
+       def counter:u8 250
+       inct counter 10
+       ---
+       Register COUNTER now has a value of 4. A register declared with a type (i8, i16, i32, i64, u8, u16, u32, u64, or bool) keeps that type whatever the width,
+       and always wraps around like an assignment in C instead of following the overflow policy; a bool register turns any value other than 0 into 1.
+     Note: The value given to DEF, and the value added to, subtracted from, multiplied with, or copied into a typed register, must fit in its type:
+           `inct counter big` is an error if BIG is an i32, since not every i32 fits in a u8. Registers compared with JEQ, JNE, JLT, JGT, or ASSERT need one type to hold the other.
+           Values from POP, PEEK, LOAD, INN, and INCH are converted to the type of the register without a check.
 
  * INC = Increment register's value (++)
      Usage: INC <register name>
@@ -42,8 +54,44 @@ use enum_primitive::FromPrimitive;
        def mb 52
        div mb 5
        ---
-       Register MB now has a value of `52 / 5`, or 10 when floored.
-     Note: DIV floors results because registers only store 32-bit integers.
+       Register MB now has a value of `52 / 5`, or 10 (registers only store integers).
+     Note: Quotients that are not whole are rounded toward zero by default, like in C, so `-7 / 2` is -3. With `--division floor`, they are rounded down instead, like in Python, so `-7 / 2` is -4.
+           A divisor of zero is a runtime error that names the line. Dividing the smallest integer by -1 overflows (see DEF).
+
+ * MOD = Set register to the remainder of dividing it (%=)
+     Usage: MOD <register name> <divisor>
+     Example:
+       def mb -52
+       mod mb 5
+       ---
+       Register MB now has a value of -2. The remainder has the same sign as the dividend, which matches DIV (-52 = 5 * -10 + -2).
+       With `--division floor`, the remainder has the same sign as the divisor instead, so MB would be 3 (-52 = 5 * -11 + 3).
+     Note: A divisor of zero is a runtime error that names the line. MOD by -1 always results in 0.
+
+ * AND, OR, XOR = Bitwise AND (&=), OR (|=), and XOR (^=) on register
+     Usage: AND <register name> <value>
+     Example:
+       def bits 12
+       and bits 10
+       ---
+       Register BITS now has a value of 8. Negative values are in two's complement, so `and bits -1` does not change BITS.
+
+ * SHL, SHR = Shift register's bits to the left (<<=) or right (>>=)
+     Usage: SHL <register name> <shift amount>
+     Example:
+       def sh -20
+       shr sh 2
+       ---
+       Register SH now has a value of -5. SHR is an arithmetic shift, so the sign is preserved.
+     Note: The shift amount is taken modulo the number of bits of the register (32 by default, or the width given by `--width` or the register's type),
+           so with 32-bit registers `shl sh 33` is the same as `shl sh 1` and `shl sh -1` is the same as `shl sh 31`.
+           With `--width big`, the amount is used as-is and has to be between 0 and 65536.
+           Bits shifted out of SHL are discarded, even if the sign changes as a result.
+
+ * NEG = Negate register's value
+     Usage: NEG <register name>
+     Note: Negating the smallest integer of the register width (such as -2147483648 with 32-bit registers) overflows, since its opposite does not fit;
+           it is handled like any other overflow (see DEF), so by default the result is the smallest integer again.
 
  * CPY = Copy value to register (value can be name of a register)
      Usage: CPY <value> <register>
@@ -52,7 +100,8 @@ use enum_primitive::FromPrimitive;
 
  * JNZ = Jump to instruction relative to itself
      Explanation: This keyword causes a jump to the line that's _Y_ lines away from this instruction *if _X_ is not zero*
-     Usage: JNZ <X> <literal>
+     Usage: JNZ <X> <Y (can be literal, label, or register name)>
+     Note: If _Y_ is a name that is both a label and a register, the label is used. Jumping to before the first line is an error, and jumping past the last line ends the program.
 
      Example:
        125  inc ej
@@ -64,6 +113,92 @@ use enum_primitive::FromPrimitive;
        ---
        In this example, when the program reaches line 130 it jumps to line 128 (or 130 + (-2)) because qr has a value of 14, which is not 0. Once it finishes line 128 it proceeds to line 129 (instead of jumping back to line 131)
 
+ * JZ = Jump to instruction relative to itself if _X_ is zero
+     Usage: JZ <X> <Y (can be literal, label, or register name)>
+     Note: Works just like JNZ, with the condition reversed.
+
+ * JEQ, JNE, JLT, JGT = Jump to instruction relative to itself if _A_ is equal to / not equal to / less than / greater than _B_
+     Usage: JEQ <A> <B> <Y (can be literal, label, or register name)>
+     Example:
+       def i 0
+       loop: inc i
+       jlt i 10 loop
+       outn i
+       ---
+       STDOUT will be: "10\n". Without JLT, this would need a scratch register holding `i - 10` for JNZ.
+     Note: Jump targets follow the same rules as JNZ's.
+
+ * CALL = Call a subroutine: jump to instruction relative to itself, remembering where to RETurn to
+     Usage: CALL <Y (can be literal, label, or register name)> [arguments of a procedure (up to 8, each can be register name or literal)]
+     Note: Jump targets follow the same rules as JNZ's. At most 4096 CALLs can be nested; one more is a stack overflow error.
+           Only a procedure (see PROC) can be called with arguments, and it has to get one argument per parameter.
+           Like the values pushed on the data stack, the arguments have to fit in untyped registers; a parameter with a type converts its argument.
+
+ * RET = Return from a subroutine to the instruction after the innermost CALL that hasn't returned yet
+     Usage: RET
+     Note: RET without a CALL is a stack underflow error.
+     Example:
+       def n 7
+       call print_twice
+       cpy 9 n
+       call print_twice
+       jnz 1 end
+       print_twice: out n
+       outn n
+       ret
+       end:
+       ---
+       STDOUT will be: "7 7\n9 9\n"
+
+ * PUSH = Push value onto the data stack
+     Usage: PUSH <value (can be register name or literal)>
+     Note: The data stack holds at most 65536 values by default (see the --stack-limit option); pushing one more is a stack overflow error.
+
+ * POP = Pop the value on top of the data stack into register
+     Usage: POP <register name>
+     Note: POP on an empty stack is a stack underflow error.
+
+ * PEEK = Copy the value on top of the data stack into register, leaving it on the stack
+     Usage: PEEK <register name>
+     Note: PEEK on an empty stack is a stack underflow error.
+     Example:
+       def a 1
+       def b 2
+       push a
+       push b
+       pop a
+       peek b
+       outn a
+       outn b
+       ---
+       STDOUT will be: "2\n1\n"; A and B were swapped and 1 is still on the stack.
+
+ * MEM = Define *new* memory buffer, an array of integers of the register width (see DEF)
+     Usage: MEM <buffer name> <size (literal)>
+     Note: A buffer name follows the same rules as a register name, and cannot be the name of a register.
+           Every buffer starts out filled with zeros. Executing MEM again fills the buffer with zeros again, just like DEF sets its register to the initial value again.
+
+ * LOAD = Copy value at index of memory buffer to register
+     Usage: LOAD <register name> <buffer name> <index (can be register name or literal)>
+     Note: Indices start at 0. An index outside of the buffer is an error.
+
+ * STORE = Copy value to index of memory buffer
+     Usage: STORE <buffer name> <index (can be register name or literal)> <value (can be register name or literal)>
+     Note: Indices start at 0. An index outside of the buffer is an error.
+     Example:
+       def i 0
+       def sq 0
+       mem squares 10
+       loop: cpy i sq
+       mul sq i
+       store squares i sq
+       inc i
+       jlt i 10 loop
+       load sq squares 7
+       outn sq
+       ---
+       STDOUT will be: "49\n"
+
  * OUT = Write value to STDOUT, with trailing whitespace
      Usage: OUT <value (can be register name or literal)>
      Example:
@@ -99,15 +234,849 @@ use enum_primitive::FromPrimitive;
        ---
        STDOUT will be: "+", since tm's value is 43 and `+` has an ASCII codepoint of 43.
 
+ * OUTS = Write string to STDOUT
+     Usage: OUTS <string literal>
+     Note: A string literal is enclosed in double quotes and may contain spaces.
+           The escape sequences \n (newline), \t (tab), \r (carriage return), \0 (NUL byte), \\ (backslash), \" (double quote), and \' (single quote) are available.
+     Example:
+       outs "HeLLo WoRlD\n"
+       ---
+       STDOUT will be: "HeLLo WoRlD\n"
+
+ * INN = Read an integer from STDIN into register
+     Usage: INN <register name>
+     Note: Whitespace before the integer is skipped, like `scanf("%d")` in C. It is an error if STDIN ends or the input is not an integer that fits in the register width (see DEF).
+     Example:
+       def n 0
+       inn n
+       outn n
+       ---
+       With " -42\n" as STDIN, STDOUT will be: "-42\n"
+
+ * INCH = Read a character (a single byte) from STDIN into register, as its char code (ord)
+     Usage: INCH <register name>
+     Note: The register is set to -1 at the end of STDIN, like `getchar()` in C.
+     Example:
+       def ch 0
+       inch ch
+       outn ch
+       ---
+       With "A" as STDIN, STDOUT will be: "65\n"
+
+ * Literals = Integer values written directly in a parameter
+     Usage: decimal (-42), hexadecimal (0x2A), binary (0b101010), octal (0o52), or character ('*')
+     Note: A character literal is the char code of a single character, which may be one of the escape sequences \n, \t, \r, \0, \\, \", and \'.
+           Hexadecimal, binary, and octal literals may be negative (-0x2A), but must fit in the register width (`--width`) as written.
+     Example:
+       outc 'A'
+       outc 0x0A
+       ---
+       STDOUT will be: "A\n"
+
+ * Constant expressions = Values computed before the program runs
+     Usage: literals and %const names combined with +, -, *, /, %, unary minus, and parentheses, such as 26*4+1 or (WIDTH * HEIGHT)
+     Note: An expression can be used wherever a literal can. It is written without spaces, unless it is enclosed in parentheses.
+           / and % truncate toward zero, whatever `--division` says. Overflow and division by zero are errors that point at the failing part of the expression.
+     Example:
+       %const WIDTH 6
+       def cells (WIDTH * 4)
+       ---
+       Register CELLS now has a value of 24.
+
+ * Labels = Name the instruction after them, so JNZ can jump to it by name
+     Usage: <label name>:
+     Note: A label name follows the same rules as a register name. A label can stand on its own line or precede an instruction on the same line.
+           Labels are resolved to relative offsets before execution, so JNZ with a label behaves exactly like JNZ with the equivalent literal.
+     Example:
+       def c 3
+       loop: dec c
+       jnz c loop
+       ---
+       `jnz c loop` is equivalent to `jnz c -1`.
+
+ * TGL = Toggle the instruction relative to itself (from Advent of Code 2016 Day 23)
+     Usage: TGL <value (can be register name or literal)>
+     Explanation: The instruction that's _X_ lines away from this one is modified as follows:
+       - A one-parameter instruction becomes DEC if it is INC, otherwise it becomes INC.
+       - A two-parameter instruction becomes CPY if it is JNZ, otherwise it becomes JNZ.
+       - Instructions with any other amount of parameters are not affected.
+     Note: Nothing happens if the target is outside of the program.
+           If toggling produces an invalid instruction (such as `cpy 1 2`), that instruction is skipped when reached.
+     Example:
+       def a 0
+       tgl 2
+       inc a
+       inc a
+       outn a
+       ---
+       STDOUT will be: "0\n", since the second `inc a` was toggled to `dec a` before being executed.
+
+ * HLT = Stop the program immediately
+     Usage: HLT [exit status (can be register name or literal, defaults to 0)]
+     Note: The exit status becomes the exit status of the process, in the interpreter, the bytecode runner, and compiled C alike.
+           A program that runs past its last line exits with 0, as if it ended with `hlt`.
+     Example:
+       def a 3
+       jnz a fail
+       hlt
+       fail: outs "a is not zero\n"
+       hlt 1
+       ---
+       STDOUT will be: "a is not zero\n", and the exit status will be 1.
+
+ * ASSERT = Stop the program with an error if a comparison does not hold
+     Usage: ASSERT <value (can be register name or literal)> <operator> <value (can be register name or literal)> [message (string literal)]
+     Note: The operator is one of ==, !=, <, <=, >, and >=.
+           A failed assertion prints the line, the instruction, and the values of the registers to STDERR, and exits with 1.
+           In compiled C, only the registers defined above the ASSERT line are printed, since the others are not declared yet.
+           Assertions are skipped when the `--no-asserts` option is given.
+     Example:
+       def a 3
+       assert a <= 2 "a is too big"
+       ---
+       STDERR will be: "Line 2: Assertion failed: assert a <= 2 "a is too big"" followed by "Registers: a = 3".
+
+ * RND = Set register to a pseudo-random integer from a range
+     Usage: RND <register name> <lowest value> <highest value (both can be register name or literal)>
+     Note: Both ends are included. A range whose lowest value is greater than its highest value is a runtime error.
+           The numbers come from the SplitMix64 generator, whose 64-bit state starts as the `--seed` option (0 by default) and is set with SEED.
+           Each number takes the next 64-bit output modulo the size of the range, or two outputs (the first one as the high half) when the range holds more than 2^64 values.
+           The interpreter, the bytecode runner, and compiled C give the same numbers for the same seed.
+     Example:
+       def die 0
+       seed 2016
+       rnd die 1 6
+       outn die
+       ---
+       STDOUT will be the same number from 1 to 6 every time the program runs.
+
+ * SEED = Restart the numbers of RND from the given seed
+     Usage: SEED <value (can be register name or literal)>
+     Note: The lowest 64 bits of the value (in two's complement) become the state of the generator.
+
+ * OUTP = Write a byte to an I/O port
+     Usage: OUTP <port number> <byte (both can be register name or literal)>
+     Note: Ports are numbered from 0 to 255, and the byte has to be from 0 to 255.
+           `--port N=TARGET` binds port N to stdin, stdout, stderr, mem (a buffer in memory), or a file; ports 0, 1 and 2 start out bound to stdin, stdout and stderr.
+           A file is opened when its port is first used, for writing (replacing its contents) by OUTP or for reading by INP, and the port can then only be used that way.
+           Using a port that is not bound, or writing to a port that can only be read from (or the other way around), is a runtime error.
+     Example:
+       outp 2 'E'
+       outp 3 0xFF
+       ---
+       With `--port 3=out.bin`, STDERR will be: "E", and out.bin will hold the single byte 0xFF.
+
+ * INP = Read a byte from an I/O port into register
+     Usage: INP <register name> <port number (can be register name or literal)>
+     Note: The register is set to -1 at the end of the port, like INCH. A mem port reads back the bytes written to it by OUTP, oldest first.
+     Example:
+       def byte 0
+       outp 4 42
+       inp byte 4
+       outn byte
+       ---
+       With `--port 4=mem`, STDOUT will be: "42\n"
+
+ * SND = Send a value to the next program
+     Usage: SND <value (can be register name or literal)>
+     Note: `--duet` runs several programs together, each one sending to the one given after it, and the last one sending to the first one.
+           A program that runs alone sends to itself. The amount of values sent by each program is reported when `--duet` is done.
+           SND and RCV are not supported by the C compiler.
+
+ * RCV = Receive the oldest value sent to the program into register
+     Usage: RCV <register name>
+     Note: If nothing has been sent yet, the program waits until another program sends something.
+           When every program that hasn't ended is waiting, they are deadlocked: `--duet` reports it and stops, and a program running alone stops with an error.
+     Example (with `--duet file.asmb file.asmb`, where PROGRAM_ID is 0 in the first program and 1 in the second one):
+       def id PROGRAM_ID
+       def other 0
+       snd id
+       rcv other
+       outn other
+       ---
+       STDOUT will be: "0\n1\n" (the first program waits on RCV until the second one has sent its ID), followed by the report of `--duet`
+
+ * PROC = Start a procedure, which lasts until ENDP
+     Usage: PROC <name> [parameter names (up to 8)]
+     Note: A procedure is called with `CALL <name> <arguments>`, which sets its parameters to the arguments.
+           The parameters and the registers defined (DEF) in its body are local: they hide registers with the same name, and every call has its own.
+           Running into a PROC without CALLing it skips the procedure. Procedures cannot be nested, and RET cannot be used inside them.
+           Labels and memory buffers are not local.
+
+ * ENDP = End a procedure, returning from it like RETURN without a value
+     Usage: ENDP
+
+ * RETURN = Return from a procedure, pushing a result onto the data stack (see PUSH)
+     Usage: RETURN [result (can be register name or literal)]
+     Note: The result is taken before the locals of the call go away, and POP gets it after the CALL.
+     Example:
+       proc fact n
+       jgt n 1 recurse
+       return 1
+       recurse: def m n
+       dec m
+       call fact m
+       pop m
+       mul m n
+       return m
+       endp
+       def n 5
+       call fact n
+       pop n
+       outn n
+       ---
+       STDOUT will be: "120\n"
+
+ * TRAP = Hand a kind of runtime errors to a procedure instead of ending the program
+     Usage: TRAP <kind: div, outc, stack, or mem> <name of a procedure with 2 parameters, or 0 for the default>
+     Note: div is division by zero (DIV and MOD), outc is an invalid char code (OUTC), stack is stack underflow (POP and PEEK),
+           and mem is an index out of the bounds of a memory buffer (LOAD and STORE). No other runtime error can be trapped:
+           not stack overflow (PUSH), and not the errors of the call stack, which the handler itself is called on.
+           The failing line calls the procedure with the error code (1 for div, 2 for outc, 3 for stack, 4 for mem) and its line number, without doing anything else.
+           When the procedure returns, the program resumes after the failing line. By default (and after TRAP with 0), a runtime error ends the program.
+           The handler is a procedure rather than a label, so that the error code and the line number arrive as its parameters instead of
+           overwriting registers of the program, and so that ENDP or RETURN knows where to resume.
+     Example:
+       proc report code line
+       outs "error "
+       out code
+       outs "at line "
+       outn line
+       endp
+       trap div report
+       def n 7
+       div n 0
+       outn n
+       ---
+       STDOUT will be: "error 1 at line 9\n7\n"
+
  */
 
 pub const COMMENT_PREFIXES: &'static str = "#/:;\"'";
-pub const KEYWORD_INDEX: [&'static str; 12] = 
-    ["def", "inc", "inct", "dec", "dect", "mul", "div", "cpy", "jnz", "out", "outn", "outc"];
+pub const KEYWORD_INDEX: [&'static str; 48] = 
+    ["def", "inc", "inct", "dec", "dect", "mul", "div", "cpy", "jnz", "out", "outn", "outc", "tgl", "inn", "inch",
+     "mod", "and", "or", "xor", "shl", "shr", "neg", "jz", "jeq", "jne", "jlt", "jgt", "call", "ret", "push", "pop", "peek",
+     "mem", "load", "store", "outs", "hlt", "assert", "rnd", "seed", "outp", "inp", "snd", "rcv",
+     "proc", "endp", "return", "trap"];
 
-/// Tokenizes the given string by whitespaces and returns the tokens in a Vec.
+/// Operators of ASSERT; the value of an OPERATOR token is an index into this.
+pub const COMPARISON_OPERATORS: [&'static str; 6] = ["==", "!=", "<", "<=", ">", ">="];
+
+/// Kinds of runtime errors that TRAP can hand to a procedure: division by zero (DIV and MOD), an invalid char code (OUTC), stack underflow (POP and PEEK), and a memory index out of bounds (LOAD and STORE).
+/// The error code that the procedure receives is the index of the kind in this plus 1, which is also the value of the LITERAL token of the kind.
+pub const TRAP_KINDS: [&'static str; 4] = ["div", "outc", "stack", "mem"];
+
+/// Maximum amount of parameters of a procedure, which is also the amount of optional parameters of CALL in PARAM_RULES.
+pub const PROCEDURE_PARAM_LIMIT: usize = 8;
+
+lazy_static! {
+    /// Parameter rules of each keyword, one character per parameter.
+    /// 'R' means register name, 'L' means literal, 'B' means both (either one is accepted), 'J' means literal, label name, or register name,
+    /// 'M' means memory buffer name, 'S' means string literal, 'O' means comparison operator, and 'K' means kind of runtime error (see TRAP_KINDS).
+    /// A lowercase character is an optional parameter, which can only be followed by other optional parameters.
+    pub static ref PARAM_RULES: HashMap<&'static str, &'static str> = hashmap!(
+        "def" => "RB", "inc" => "R", "inct" => "RB", "dec" => "R", "dect" => "RB",
+        "mul" => "RB", "div" => "RB", "cpy" => "BR", "jnz" => "BJ", "out" => "B",
+        "outn" => "B", "outc" => "B", "tgl" => "B", "inn" => "R", "inch" => "R",
+        "mod" => "RB", "and" => "RB", "or" => "RB", "xor" => "RB", "shl" => "RB", "shr" => "RB", "neg" => "R",
+        "jz" => "BJ", "jeq" => "BBJ", "jne" => "BBJ", "jlt" => "BBJ", "jgt" => "BBJ",
+        "call" => "Jbbbbbbbb", "ret" => "", "push" => "B", "pop" => "R", "peek" => "R",
+        "mem" => "ML", "load" => "RMB", "store" => "MBB", "outs" => "S",
+        "hlt" => "b", "assert" => "BOBs", "rnd" => "RBB", "seed" => "B",
+        "outp" => "BB", "inp" => "RB", "snd" => "B", "rcv" => "R",
+        // The parameters of PROC are what the parser turns `proc <name> <parameters>` into (see `procedure_tokens`)
+        "proc" => "LLLL", "endp" => "", "return" => "b", "trap" => "KJ"
+    );
+}
+
+/// Names defined by the lines of a program converted to tokens so far, which later lines can refer to.
+pub struct Symbols {
+    /// Register names, in the order of their DEFs (the value of a REGISTER token is an index into this)
+    pub regs: Vec<String>,
+    /// Type of each register in `regs` (see `split_type`)
+    pub reg_types: Vec<RegType>,
+    /// Memory buffer names and sizes, in the order of their MEMs (the value of a BUFFER token is an index into this)
+    pub buffers: Vec<(String, u32)>,
+    /// Contents of the string literals, with the escape sequences replaced (the value of a STRING token is an index into this)
+    pub strings: Vec<String>,
+    /// Register width of the program, which every literal has to fit in
+    pub width: Width,
+    /// Where each line of tokens comes from (see `line_origin`), so that runtime errors can name the line
+    pub origins: Vec<String>,
+    /// Procedures of the program (see `procedures`)
+    pub procedures: Vec<Procedure>,
+}
+
+impl Symbols {
+    pub fn new() -> Self {
+        Symbols {
+            regs: Vec::new(),
+            reg_types: Vec::new(),
+            buffers: Vec::new(),
+            strings: Vec::new(),
+            width: Width::I32,
+            origins: Vec::new(),
+            procedures: Vec::new(),
+        }
+    }
+
+    /// Returns the index of the given string in `strings`, adding it if it isn't there yet.
+    pub fn string_index(&mut self, string: String) -> usize {
+        match index_of(&self.strings, &string) {
+            Some(index) => index,
+            None => {
+                self.strings.push(string);
+                self.strings.len() - 1
+            }
+        }
+    }
+
+    /// Returns the type of the register with the given name, if there is one.
+    pub fn reg_type(&self, name: &str) -> Option<RegType> {
+        self.regs.iter().position(|reg| reg == name).map(|index| self.reg_types[index])
+    }
+
+    /// Returns the index of the memory buffer with the given name, if there is one.
+    pub fn buffer_index(&self, name: &str) -> Option<usize> {
+        self.buffers.iter().position(|&(ref buffer, _)| buffer == name)
+    }
+}
+
+/// A procedure (`proc <name> <parameters>` up to `endp`), as found by `procedures`.
+pub struct Procedure {
+    pub name: String,
+    /// Names of the parameters and of the registers defined in the body, parameters first
+    pub locals: Vec<String>,
+    pub param_count: usize,
+    /// Index of the instruction of the PROC line
+    pub entry: u32,
+    /// Index of the instruction of the ENDP line
+    pub exit: u32,
+}
+
+impl Procedure {
+    /// Returns the name that the local register `name` is known by outside the procedure, or None if `name` is not local.
+    /// Such a name cannot clash with the name of a global register, since it contains a dot.
+    pub fn local_name(&self, name: &str) -> Option<String> {
+        if self.locals.iter().any(|local| local == name) {
+            Some(format!("{}.{}", self.name, name))
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the procedure whose PROC line, body, or ENDP line is instruction `ip`, if there is one.
+pub fn procedure_at(procedures: &[Procedure], ip: u32) -> Option<&Procedure> {
+    procedures.iter().find(|procedure| procedure.entry <= ip && ip <= procedure.exit)
+}
+
+/// Maps label names to the index of the instruction they precede.
+pub type LabelMap = HashMap<String, u32>;
+
+/// Splits the given line into tokens at whitespace.
+/// A token starting with a double (or single) quote is a string (or character) literal that lasts until the closing quote, so it may contain whitespace; the quotes are kept.
+/// Example: tokenize_line("outs \"Hi there\"") returns ["outs", "\"Hi there\""]
+/// Example: tokenize_line("outc ' '") returns ["outc", "' '"]
+/// Example: tokenize_line("cpy (W * H) n") returns ["cpy", "(W * H)", "n"]
 pub fn tokenize_line(line: &str) -> Vec<&str> {
-    line.split_whitespace().collect::<Vec<_>>()
+    let mut toks: Vec<&str> = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, first)) = chars.next() {
+        if first.is_whitespace() {
+            continue;
+        }
+        if first == '"' || first == '\'' {
+            let mut escaped = false;
+            while let Some((_, ch)) = chars.next() {
+                if ch == first && !escaped {
+                    break;
+                }
+                escaped = ch == '\\' && !escaped;
+            }
+        }
+        // Anything right after the closing quote of a string literal belongs to the same (invalid) token
+        // Whitespace inside parentheses does not end a token, so a constant expression like (W * H) stays in one piece
+        let mut depth = if first == '(' { 1 } else { 0 };
+        let mut end = line.len();
+        while let Some(&(index, ch)) = chars.peek() {
+            if ch.is_whitespace() && depth <= 0 {
+                end = index;
+                break;
+            }
+            match ch {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            chars.next();
+        }
+        toks.push(&line[start..end]);
+    }
+    toks
+}
+
+/// Splits a label definition ("name:") off the front of the given tokens.
+/// Returns the label name, if there is one, and the tokens of the rest of the line.
+/// Example: split_label(["loop:", "dec", "c"]) returns (Some("loop"), ["dec", "c"])
+pub fn split_label<'a>(toks: &Vec<&'a str>) -> (Option<&'a str>, Vec<&'a str>) {
+    match toks.first() {
+        Some(first) if first.len() > 1 && first.ends_with(':') && worth_execution(toks).is_ok() =>
+            (Some(&first[..first.len() - 1]), toks[1..].to_vec()),
+        _ => (None, toks.clone())
+    }
+}
+
+/// Splits the type off a register name given to DEF ("name:type"); a register declared without a type is an `Int` one.
+/// Example: split_type("counter:u8") returns ("counter", RegType::U8), split_type("n") returns ("n", RegType::Int)
+pub fn split_type(tok: &str) -> Result<(&str, RegType), String> {
+    match tok.find(':') {
+        None => Ok((tok, RegType::Int)),
+        Some(colon) => match RegType::from_name(&tok[colon + 1..]) {
+            Some(reg_type) => Ok((&tok[..colon], reg_type)),
+            None => Err(format!("Unknown register type '{}' (the types are {})", &tok[colon + 1..], TYPE_NAMES.join(", ")))
+        }
+    }
+}
+
+/// Checks the rules for mixing register types in the given line (with its label split off):
+/// a value written to a register (or pushed or stored, since the data stack and memory buffers hold `Int`s) has to fit in the register's type,
+/// and of two values compared with each other, one has to fit in the type of the other.
+/// Values that are read from the data stack, memory buffers, or STDIN are converted to the register's type instead (see `RegType::convert`).
+/// `reg_type` returns the type of a register by its name; unknown registers are left for the other checks.
+pub fn types_valid<F>(toks: &Vec<&str>, reg_type: F, width: Width) -> Result<(), String>
+        where F: Fn(&str) -> Option<RegType> {
+    // Describes where a value goes, for the error messages
+    let describe = |name: &str, type_: RegType| format!("register {} ({})", name, type_.name(width));
+    let fits = |tok: &str, dest: RegType, dest_name: String| -> Result<(), String> {
+        if let Ok(val) = is_literal(tok) {
+            if !dest.contains(val, width) {
+                return Err(format!("Literal {} does not fit in {}", tok, dest_name));
+            }
+        } else if let Some(type_) = reg_type(tok) {
+            if !type_.fits_in(dest, width) {
+                return Err(format!("Register {} ({}) does not fit in {}", tok, type_.name(width), dest_name));
+            }
+        }
+        Ok(())
+    };
+    let comparable = |left: &str, right: &str| -> Result<(), String> {
+        match (reg_type(left), reg_type(right)) {
+            (Some(left_type), Some(right_type)) if !left_type.fits_in(right_type, width) && !right_type.fits_in(left_type, width) =>
+                Err(format!("Registers {} ({}) and {} ({}) cannot be compared, since neither type holds the other",
+                    left, left_type.name(width), right, right_type.name(width))),
+            (Some(left_type), None) => fits(right, left_type, describe(left, left_type)),
+            (None, Some(right_type)) => fits(left, right_type, describe(right, right_type)),
+            _ => Ok(())
+        }
+    };
+    let dest = |name: &str| reg_type(name).map(|type_| (type_, describe(name, type_)));
+    if worth_execution(toks).is_err() || toks.len() < 2 {
+        return Ok(());
+    }
+    match toks[0].to_lowercase().as_str() {
+        "def" if toks.len() > 2 => {
+            let (name, type_) = try!(split_type(toks[1]));
+            fits(toks[2], type_, describe(name, type_))
+        },
+        "inct" | "dect" | "mul" | "div" | "mod" | "and" | "or" | "xor" if toks.len() > 2 => match dest(toks[1]) {
+            Some((type_, dest_name)) => fits(toks[2], type_, dest_name),
+            None => Ok(())
+        },
+        "cpy" if toks.len() > 2 => match dest(toks[2]) {
+            Some((type_, dest_name)) => fits(toks[1], type_, dest_name),
+            None => Ok(())
+        },
+        "rnd" if toks.len() > 3 => match dest(toks[1]) {
+            Some((type_, dest_name)) => fits(toks[2], type_, dest_name.clone()).and_then(|_| fits(toks[3], type_, dest_name)),
+            None => Ok(())
+        },
+        "push" => fits(toks[1], RegType::Int, format!("the data stack ({})", RegType::Int.name(width))),
+        "call" => toks[2..].iter().map(|arg| fits(arg, RegType::Int, format!("the arguments of a procedure ({})", RegType::Int.name(width)))).collect(),
+        "store" if toks.len() > 3 => fits(toks[3], RegType::Int, format!("memory buffer {} ({})", toks[1], RegType::Int.name(width))),
+        "jeq" | "jne" | "jlt" | "jgt" if toks.len() > 2 => comparable(toks[1], toks[2]),
+        "assert" if toks.len() > 3 => comparable(toks[1], toks[3]),
+        _ => Ok(())
+    }
+}
+
+/// Returns the name of line `index` of a program for error messages: its entry in `origins` if there is one, or "Line N".
+/// `origins` describes where each line comes from when the lines were preprocessed (see `preprocess::Source`), and may be empty.
+pub fn line_origin(origins: &[String], index: usize) -> String {
+    match origins.get(index) {
+        Some(origin) => origin.clone(),
+        None => format!("Line {}", index + 1)
+    }
+}
+
+/// Returns the number of the line that `origin` names (see `line_origin`), which is what the handlers of TRAP receive.
+/// Lines inside a macro are numbered where the macro defines them.
+/// Example: origin_line("Line 12 of lib.asmb") returns 12
+pub fn origin_line(origin: &str) -> u32 {
+    origin.split_whitespace().nth(1).and_then(|number| number.parse().ok()).unwrap_or(0)
+}
+
+/// Collects the labels defined in the given lines of ASMB+ along with the index of the instruction each one names.
+/// Fails if a label name is invalid or defined twice.
+pub fn collect_labels(lines: &[&str], origins: &[String]) -> Result<LabelMap, String> {
+    let mut labels = LabelMap::new();
+    let mut ip = 0u32;
+    for (linenum, line) in lines.iter().enumerate() {
+        let (label, rest) = split_label(&tokenize_line(line));
+        if let Some(name) = label {
+            if let Err(problem) = regname_valid(name) {
+                return Err(format!("{}: Invalid label name: {}", line_origin(origins, linenum), problem));
+            }
+            if labels.insert(name.to_owned(), ip).is_some() {
+                return Err(format!("{}: Label '{}' is already defined", line_origin(origins, linenum), name));
+            }
+        }
+        // The name of a procedure is a label for its PROC line, which CALL jumps to
+        if rest.len() > 1 && rest[0].to_lowercase() == "proc" {
+            if let Err(problem) = regname_valid(rest[1]) {
+                return Err(format!("{}: Invalid procedure name: {}", line_origin(origins, linenum), problem));
+            }
+            if labels.insert(rest[1].to_owned(), ip).is_some() {
+                return Err(format!("{}: Label '{}' is already defined", line_origin(origins, linenum), rest[1]));
+            }
+        }
+        if worth_execution(&rest).is_ok() {
+            ip += 1;
+        }
+    }
+    Ok(labels)
+}
+
+/// Finds the procedures of the given lines of ASMB+, checking that every PROC is closed by an ENDP and that RET and RETURN are used on the right side of them.
+pub fn procedures(lines: &[&str], origins: &[String]) -> Result<Vec<Procedure>, String> {
+    let mut procedures: Vec<Procedure> = Vec::new();
+    let mut open: Option<Procedure> = None;
+    let mut ip = 0u32;
+    for (linenum, line) in lines.iter().enumerate() {
+        let toks = split_label(&tokenize_line(line)).1;
+        if worth_execution(&toks).is_err() {
+            continue;
+        }
+        let origin = line_origin(origins, linenum);
+        match (toks[0].to_lowercase().as_str(), open.is_some()) {
+            ("proc", true) => return Err(format!("{}: Procedures cannot be nested", origin)),
+            ("proc", false) => {
+                if toks.len() < 2 {
+                    return Err(format!("{}: PROC needs the name of the procedure", origin));
+                }
+                let params: Vec<String> = toks[2..].iter().map(|param| param.to_string()).collect();
+                if params.len() > PROCEDURE_PARAM_LIMIT {
+                    return Err(format!("{}: A procedure can have at most {} parameters", origin, PROCEDURE_PARAM_LIMIT));
+                }
+                for (index, param) in params.iter().enumerate() {
+                    if let Err(problem) = regname_valid(param) {
+                        return Err(format!("{}: Invalid parameter name: {}", origin, problem));
+                    }
+                    if params[..index].contains(param) {
+                        return Err(format!("{}: Parameter '{}' is given twice", origin, param));
+                    }
+                }
+                open = Some(Procedure {
+                    name: toks[1].to_owned(),
+                    param_count: params.len(),
+                    locals: params,
+                    entry: ip,
+                    exit: ip,
+                });
+            },
+            ("endp", true) => {
+                let mut procedure = open.take().unwrap();
+                procedure.exit = ip;
+                procedures.push(procedure);
+            },
+            ("endp", false) => return Err(format!("{}: ENDP without PROC", origin)),
+            ("return", false) => return Err(format!("{}: RETURN outside of a procedure", origin)),
+            ("ret", true) => return Err(format!("{}: RET cannot be used inside a procedure (use RETURN instead)", origin)),
+            ("def", true) if toks.len() > 1 => {
+                // The type (if any) is checked along with the rest of the line
+                let name = toks[1].split(':').next().unwrap().to_owned();
+                open.as_mut().unwrap().locals.push(name);
+            },
+            _ => {}
+        }
+        ip += 1;
+    }
+    match open {
+        Some(procedure) => Err(format!("Procedure '{}' is never closed with endp", procedure.name)),
+        None => Ok(procedures)
+    }
+}
+
+/// Returns what the PROC line of `procedure` is converted to before it is checked by `line_valid`:
+/// PROC <offset of the line after ENDP> <index of the first local register> <amount of locals> <amount of parameters>.
+/// The locals are registered in the order of `Procedure::locals`, starting at `first_reg`.
+pub fn procedure_tokens(procedure: &Procedure, first_reg: usize) -> Vec<String> {
+    vec!["proc".to_owned(), (procedure.exit + 1 - procedure.entry).to_string(), first_reg.to_string(),
+         procedure.locals.len().to_string(), procedure.param_count.to_string()]
+}
+
+/// Checks that a CALL only gives arguments to a procedure, one per parameter.
+/// The line is expected to have its label definition split off, and its jump target not resolved yet.
+pub fn call_valid(toks: &Vec<&str>, procedures: &[Procedure]) -> Result<(), String> {
+    if toks.len() < 2 || toks[0].to_lowercase() != "call" {
+        return Ok(());
+    }
+    let arg_count = toks.len() - 2;
+    match procedures.iter().find(|procedure| procedure.name == toks[1]) {
+        Some(procedure) if procedure.param_count != arg_count =>
+            Err(format!("Procedure '{}' expects {} argument(s), got {}", procedure.name, procedure.param_count, arg_count)),
+        None if arg_count > 0 => Err(format!("Only procedures can be called with arguments, and '{}' is not one", toks[1])),
+        _ => Ok(())
+    }
+}
+
+/// Checks that a TRAP hands its kind of runtime errors to a procedure with 2 parameters (the error code and the line number), or gives them back to the default (0).
+/// The line is expected to have its label definition split off, and its jump target not resolved yet.
+pub fn trap_valid(toks: &Vec<&str>, procedures: &[Procedure]) -> Result<(), String> {
+    if toks.len() < 3 || toks[0].to_lowercase() != "trap" || is_literal(toks[2]) == Ok(0) {
+        return Ok(());
+    }
+    match procedures.iter().find(|procedure| procedure.name == toks[2]) {
+        Some(procedure) if procedure.param_count == 2 => Ok(()),
+        Some(procedure) => Err(format!("Procedure '{}' cannot handle runtime errors, since it takes {} parameter(s) instead of 2 (the error code and the line number)",
+                                       procedure.name, procedure.param_count)),
+        None => Err(format!("TRAP expects a procedure with 2 parameters (the error code and the line number), or 0 for the default, but '{}' is not one", toks[2]))
+    }
+}
+
+/// Replaces the names of the local registers of `procedure` (see `Procedure::local_name`) in the given line, which is in the procedure.
+/// The line is expected to have its labels resolved, so that jump targets aren't mistaken for registers, which jumps can be relative to as well.
+pub fn qualify_locals(toks: Vec<String>, procedure: Option<&Procedure>) -> Vec<String> {
+    let procedure = match procedure {
+        Some(procedure) if worth_execution(&toks.iter().map(|tok| tok.as_str()).collect()).is_ok() => procedure,
+        _ => return toks
+    };
+    let param_rule = PARAM_RULES.get(toks[0].to_lowercase().as_str()).unwrap().to_uppercase();
+    let mut output = vec![toks[0].clone()];
+    for (rule, tok) in param_rule.chars().zip(toks[1..].iter()) {
+        // The name of a register defined by DEF may be followed by its type
+        let (name, suffix) = match tok.find(':') {
+            Some(colon) => tok.split_at(colon),
+            None => (tok.as_str(), "")
+        };
+        match procedure.local_name(name) {
+            Some(local) if rule == 'R' || rule == 'B' || rule == 'J' => output.push(local + suffix),
+            _ => output.push(tok.clone())
+        }
+    }
+    output
+}
+
+/// Replaces the labels used as jump targets in the given line with their offsets relative to instruction `ip`.
+/// A label takes precedence over a register with the same name.
+/// The line is expected to be passed by `line_valid` and have its label definition split off.
+pub fn resolve_labels(toks: &Vec<&str>, labels: &LabelMap, ip: u32) -> Result<Vec<String>, String> {
+    let param_rule = match toks.first() {
+        Some(kw) if worth_execution(toks).is_ok() => PARAM_RULES.get(kw.to_lowercase().as_str()).unwrap().as_bytes(),
+        _ => return Ok(toks.iter().map(|tok| tok.to_string()).collect())
+    };
+    let mut output = vec![toks[0].to_owned()];
+    for (index, tok) in toks[1..].iter().enumerate() {
+        match labels.get(*tok) {
+            // Names that aren't labels are left as-is, as they may be registers
+            Some(target) if param_rule[index] == b'J' => output.push((*target as i64 - ip as i64).to_string()),
+            _ => output.push(tok.to_string())
+        }
+    }
+    Ok(output)
+}
+
+/// Checks that every register parameter of the given line, whose labels are resolved, names a register for which `defined` holds.
+/// A jump target that is neither a literal nor a register was most likely meant to be a label, which the error says.
+pub fn registers_defined<F>(toks: &Vec<&str>, defined: F) -> Result<(), String>
+        where F: Fn(&str) -> bool {
+    if worth_execution(toks).is_err() {
+        return Ok(());
+    }
+    let keyword = toks[0].to_lowercase();
+    let param_rule = PARAM_RULES.get(keyword.as_str()).unwrap().as_bytes();
+    for (index, tok) in toks.iter().enumerate().skip(1) {
+        // DEF defines its register, and the parameters of PROC are literals (see `procedure_tokens`)
+        if (keyword == "def" && index == 1) || is_literal(tok).is_ok() || defined(tok) {
+            continue;
+        }
+        match param_rule[index - 1].to_ascii_uppercase() {
+            b'J' => return Err(format!("Undefined label or register '{}'", tok)),
+            b'R' | b'B' => return Err(format!("Register name unknown: {}", tok)),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Checks whether the given token is a constant expression, such as `26*4+1` or `(W * H)`, rather than a single literal or name.
+pub fn is_expression(tok: &str) -> bool {
+    !tok.starts_with('"') && is_literal(tok).is_err() && tok.contains(|ch| "()+-*/%".contains(ch))
+}
+
+/// Replaces every constant expression among the parameters of the given line with its value.
+/// Every value along the way has to fit in `width`.
+/// Example: fold_constants(["def", "size", "26*4+1"], Width::I32) returns ["def", "size", "105"]
+pub fn fold_constants(toks: &Vec<&str>, width: Width) -> Result<Vec<String>, String> {
+    let mut output: Vec<String> = toks.iter().map(|tok| tok.to_string()).collect();
+    if worth_execution(toks).is_err() {
+        return Ok(output);
+    }
+    for index in 1..toks.len() {
+        if is_expression(toks[index]) {
+            output[index] = try!(evaluate_expression(toks[index], width)).to_string();
+        }
+    }
+    Ok(output)
+}
+
+/// Evaluates a constant expression made of literals, parentheses, unary minus, and the operators +, -, *, / and %.
+/// / and % truncate toward zero, as DIV and MOD do by default. Overflow and division by zero are errors, which point at the part of the expression that failed.
+/// Example: evaluate_expression("(0x10 + 'A') * 2", Width::I32) returns 162
+pub fn evaluate_expression(expr: &str, width: Width) -> Result<i128, String> {
+    let mut parser = ExpressionParser { expr: expr, pos: 0, width: width };
+    let val = try!(parser.sum());
+    parser.skip_whitespace();
+    if parser.pos < expr.len() {
+        return Err(parser.error(parser.pos, expr.len(), "Unexpected characters"));
+    }
+    Ok(val)
+}
+
+/// Recursive descent parser for `evaluate_expression`. `pos` is the byte offset of the next character to read.
+struct ExpressionParser<'a> {
+    expr: &'a str,
+    pos: usize,
+    /// Width that every value has to fit in
+    width: Width,
+}
+
+impl<'a> ExpressionParser<'a> {
+    fn error(&self, start: usize, end: usize, problem: &str) -> String {
+        format!("{} at `{}` (characters {} to {} of constant expression `{}`)",
+            problem, &self.expr[start..end], start + 1, end, self.expr)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(ch) = self.expr[self.pos..].chars().next() {
+            if !ch.is_whitespace() {
+                break;
+            }
+            self.pos += ch.len_utf8();
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.expr[self.pos..].chars().next()
+    }
+
+    /// Applies a binary operator to the operands that span from `start` to the current position.
+    fn apply(&self, start: usize, left: i128, op: char, right: i128) -> Result<i128, String> {
+        if (op == '/' || op == '%') && right == 0 {
+            return Err(self.error(start, self.pos, "Division by zero"));
+        }
+        let result = match op {
+            '+' => left.checked_add(right),
+            '-' => left.checked_sub(right),
+            '*' => left.checked_mul(right),
+            '/' => left.checked_div(right),
+            _ => left.checked_rem(right)
+        };
+        self.fit(start, result)
+    }
+
+    /// Checks that the value of the part of the expression from `start` to the current position fits in the width.
+    fn fit(&self, start: usize, val: Option<i128>) -> Result<i128, String> {
+        match val {
+            Some(val) if self.width.contains(val) => Ok(val),
+            _ => Err(self.error(start, self.pos, &format!("Overflow of {}-bit integers", self.width.bits())))
+        }
+    }
+
+    // sum = product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<i128, String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let mut val = try!(self.product());
+        while let Some(op) = self.peek() {
+            if op != '+' && op != '-' {
+                break;
+            }
+            self.pos += 1;
+            let right = try!(self.product());
+            val = try!(self.apply(start, val, op, right));
+        }
+        Ok(val)
+    }
+
+    // product = unary (('*' | '/' | '%') unary)*
+    fn product(&mut self) -> Result<i128, String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let mut val = try!(self.unary());
+        while let Some(op) = self.peek() {
+            if op != '*' && op != '/' && op != '%' {
+                break;
+            }
+            self.pos += 1;
+            let right = try!(self.unary());
+            val = try!(self.apply(start, val, op, right));
+        }
+        Ok(val)
+    }
+
+    // unary = '-' unary | '(' sum ')' | literal
+    fn unary(&mut self) -> Result<i128, String> {
+        let start = self.pos;
+        match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                let val = try!(self.unary());
+                self.fit(start, val.checked_neg())
+            },
+            Some('(') => {
+                self.pos += 1;
+                let val = try!(self.sum());
+                if self.peek() != Some(')') {
+                    return Err(self.error(start, self.pos, "Missing closing parenthesis"));
+                }
+                self.pos += 1;
+                Ok(val)
+            },
+            Some(_) => self.literal(),
+            None => Err(self.error(start, self.pos, "Expected a value"))
+        }
+    }
+
+    fn literal(&mut self) -> Result<i128, String> {
+        let start = self.pos;
+        let rest = &self.expr[start..];
+        let len = if rest.starts_with('\'') {
+            // Up to the closing quote, which may be escaped
+            let mut escaped = false;
+            match rest.char_indices().skip(1).find(|&(_, ch)| {
+                let closing = ch == '\'' && !escaped;
+                escaped = ch == '\\' && !escaped;
+                closing
+            }) {
+                Some((index, _)) => index + 1,
+                None => rest.len()
+            }
+        } else {
+            rest.find(|ch: char| !(ch.is_alphanumeric() || ch == '_')).unwrap_or(rest.len())
+        };
+        self.pos += len;
+        let tok = &rest[..len];
+        if tok.is_empty() {
+            return Err(self.error(start, ::std::cmp::min(start + 1, self.expr.len()), "Expected a value"));
+        }
+        match is_literal(tok) {
+            Ok(val) => self.fit(start, Some(val)),
+            Err(_) if regname_valid(tok).is_ok() => Err(self.error(start, self.pos, "Only literals and constants are allowed")),
+            Err(_) => Err(self.error(start, self.pos, "Invalid literal"))
+        }
+    }
 }
 
 /// Checks if the given register name is valid.
@@ -134,26 +1103,90 @@ pub fn regname_valid(name: &str) -> Result<(), String> {
     Ok(())
 }
 
-/// Checks whether the given token is an integer literal by attempting to convert it to an i32.
+/// Checks whether the given token is an integer literal by attempting to convert it to an i128.
+/// Besides decimal, a literal can be hexadecimal (0xFF), binary (0b101), octal (0o17), or a character ('A', '\n').
 /// If it is, return Ok(integer value of token)
 /// Otherwise return Err()
-pub fn is_literal(tok: &str) -> Result<i32, ()> {
-    match tok.parse::<i32>() {
-        Ok(val) => Ok(val),
-        Err(_) => Err(())
+/// Any literal that fits in i128 is accepted; whether it fits in the register width is up to the caller (see `literals_fit`).
+pub fn is_literal(tok: &str) -> Result<i128, ()> {
+    if tok.len() >= 3 && tok.starts_with('\'') && tok.ends_with('\'') {
+        let contents = try_failsafe!(unescape(tok, '\''), ());
+        let mut chars = contents.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(ch), None) => Ok(ch as i128),
+            _ => Err(())
+        };
+    }
+    let (sign, unsigned) = match tok.chars().next() {
+        Some('-') => ("-", &tok[1..]),
+        _ => ("", tok)
+    };
+    let (radix, digits) = match unsigned.get(..2).map(|prefix| prefix.to_lowercase()) {
+        Some(ref prefix) if prefix == "0x" => (16, &unsigned[2..]),
+        Some(ref prefix) if prefix == "0b" => (2, &unsigned[2..]),
+        Some(ref prefix) if prefix == "0o" => (8, &unsigned[2..]),
+        _ => return tok.parse::<i128>().map_err(|_| ())
+    };
+    // from_str_radix would also accept a second sign after the prefix
+    if digits.starts_with('-') || digits.starts_with('+') {
+        return Err(());
+    }
+    i128::from_str_radix(&format!("{}{}", sign, digits), radix).map_err(|_| ())
+}
+
+/// Checks that every literal among the parameters of the given line fits in registers of the given width.
+pub fn literals_fit(toks: &Vec<&str>, width: Width) -> Result<(), String> {
+    if worth_execution(toks).is_err() {
+        return Ok(());
+    }
+    for tok in &toks[1..] {
+        if let Ok(val) = is_literal(tok) {
+            if !width.contains(val) {
+                return Err(format!("Literal {} does not fit in {}-bit registers", tok, width.bits()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks if the given token is a string literal (such as "Hello\n"), and returns its contents with the escape sequences replaced.
+pub fn string_literal(tok: &str) -> Result<String, String> {
+    if tok.len() < 2 || !tok.starts_with('"') || !tok.ends_with('"') {
+        return Err(format!("{} is not a string literal enclosed in double quotes", tok));
+    }
+    unescape(tok, '"')
+}
+
+/// Returns the contents of the given string or character literal, which is enclosed in `quote`s, with the escape sequences replaced.
+fn unescape(tok: &str, quote: char) -> Result<String, String> {
+    let mut output = String::new();
+    let mut chars = tok[1..tok.len() - 1].chars();
+    while let Some(ch) = chars.next() {
+        if ch == quote {
+            return Err(format!("{}: Quotes inside a literal should be escaped", tok));
+        }
+        if ch != '\\' {
+            output.push(ch);
+            continue;
+        }
+        output.push(match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some(other) => return Err(format!("{}: Unknown escape sequence \\{}", tok, other)),
+            None => return Err(format!("{}: The closing quote is escaped", tok))
+        });
     }
+    Ok(output)
 }
 
 /// Checks if the given line of ASMB is valid.
 /// This function checks the keyword, parameter count, and parameter types (literal/register name)
 pub fn line_valid(toks: &Vec<&str>) -> Result<(), String> {
-	lazy_static! {
-		static ref KEYWORDS: HashMap<&'static str, &'static str> = hashmap!(
-		    "def" => "RB", "inc" => "R", "inct" => "RB", "dec" => "R", "dect" => "RB",
-		    "mul" => "RB", "div" => "RB", "cpy" => "BR", "jnz" => "BL", "out" => "B",
-		    "outn" => "B", "outc" => "B"
-		);
-	}
     // Empty?
     if toks.len() == 0 {
         return Ok(());
@@ -164,21 +1197,50 @@ pub fn line_valid(toks: &Vec<&str>) -> Result<(), String> {
     }
     let kw = toks[0].to_lowercase();
     // Check 1: keyword
-    if !KEYWORDS.contains_key(&kw.as_str()) {
+    if !PARAM_RULES.contains_key(&kw.as_str()) {
         return Err(format!("Unknown keyword '{}'", kw));
     }
-    let param_rule = KEYWORDS.get(&kw.as_str()).unwrap();
+    let param_rule = PARAM_RULES.get(&kw.as_str()).unwrap();
     // Check 2: param count
-    if param_rule.len() != toks.len() - 1 {
+    if !param_count_valid(param_rule, toks.len() - 1) {
+        let required = param_rule.chars().filter(|rule| rule.is_uppercase()).count();
+        if required == param_rule.len() {
+            return Err(format!(
+                "Expected {} parameter(s), received {}", param_rule.len(), toks.len() - 1));
+        }
         return Err(format!(
-            "Expected {} parameter(s), received {}", param_rule.len(), toks.len() - 1));
+            "Expected {} to {} parameter(s), received {}", required, param_rule.len(), toks.len() - 1));
     }
     // Check 3: param type
-    for (index, rule) in param_rule.chars().enumerate() {
+    for (index, rule) in param_rule.to_uppercase().chars().take(toks.len() - 1).enumerate() {
         // index+1!
-        // rule can be 'R', 'L', or 'B'
+        // rule can be 'R', 'L', 'B', 'J', 'M', 'S', 'O', or 'K'
+        if rule == 'O' {
+            if !COMPARISON_OPERATORS.contains(&toks[index+1]) {
+                return Err(format!("Parameter '{}' of keyword '{}' should be one of the operators {}",
+                    toks[index+1], toks[0], COMPARISON_OPERATORS.join(" ")));
+            }
+            continue;
+        }
+        if rule == 'K' {
+            if !TRAP_KINDS.contains(&toks[index+1]) {
+                return Err(format!("Parameter '{}' of keyword '{}' should be one of the kinds of runtime errors {} (no other runtime error can be trapped)",
+                    toks[index+1], toks[0], TRAP_KINDS.join(" ")));
+            }
+            continue;
+        }
+        if rule == 'S' || toks[index+1].starts_with('"') {
+            if let Err(problem) = string_literal(toks[index+1]) {
+                return Err(format!("Parameter {} of keyword '{}' should be a string literal: {}", index + 1, toks[0], problem));
+            }
+            if rule != 'S' {
+                return Err(format!("Parameter {} of keyword '{}' should not be a string literal", index + 1, toks[0]));
+            }
+            continue;
+        }
         let is_litparam = is_literal(toks[index+1]).is_ok();
-        if (is_litparam && rule == 'R') || (!is_litparam && rule == 'L') {
+        if (is_litparam && (rule == 'R' || rule == 'M')) || (!is_litparam && rule == 'L') ||
+                (!is_litparam && (rule == 'J' || rule == 'M') && regname_valid(toks[index+1]).is_err()) {
             return Err(format!(
                 "Parameter '{}' does not comply with the parameter rules of keyword '{}' ({})",
                 toks[index+1], toks[0], rule));
@@ -187,14 +1249,84 @@ pub fn line_valid(toks: &Vec<&str>) -> Result<(), String> {
     Ok(())
 }
 
+/// Checks if `count` parameters are accepted by the given parameter rule (see `PARAM_RULES`), which may end with optional parameters.
+pub fn param_count_valid(param_rule: &str, count: usize) -> bool {
+    count <= param_rule.len() && count >= param_rule.chars().filter(|rule| rule.is_uppercase()).count()
+}
+
+/// Returns the keyword that a line starting with `keyword` turns into when it is toggled by TGL.
+/// `param_count` is the amount of parameters of the line, since it can differ between lines with the same keyword (such as HLT).
+/// Lines with a parameter count other than 1 or 2 are returned unchanged.
+/// Example: toggled_keyword("inc", 1) returns "dec", toggled_keyword("outn", 1) returns "inc"
+pub fn toggled_keyword(keyword: &str, param_count: usize) -> &'static str {
+    let kw = index_of(&KEYWORD_INDEX, &keyword).expect("toggled_keyword called with unknown keyword");
+    match (keyword, param_count) {
+        ("inc", 1) => "dec",
+        (_, 1) => "inc",
+        ("jnz", 2) => "cpy",
+        (_, 2) => "jnz",
+        _ => KEYWORD_INDEX[kw]
+    }
+}
+
+/// Checks if the given line of tokens can be executed.
+/// Parser-generated lines always can, but TGL may turn a line into one that writes to a literal (such as `cpy 1 2`).
+pub fn tokens_valid(toks: &Vec<Token>) -> bool {
+    let param_rule = PARAM_RULES.get(KEYWORD_INDEX[toks[0].val as usize]).unwrap();
+    param_count_valid(param_rule, toks.len() - 1) &&
+        param_rule.to_uppercase().chars().zip(toks[1..].iter()).all(|(rule, tok)| match rule {
+            'R' => tok.type_ == TokenType::REGISTER,
+            'L' => tok.type_ == TokenType::LITERAL,
+            'M' => tok.type_ == TokenType::BUFFER,
+            'S' => tok.type_ == TokenType::STRING,
+            'O' => tok.type_ == TokenType::OPERATOR,
+            'K' => tok.type_ == TokenType::LITERAL,
+            _ => tok.type_ == TokenType::REGISTER || tok.type_ == TokenType::LITERAL
+        })
+}
+
+/// Checks if the given line (with its label split off) can be executed, like `tokens_valid` does for lines converted to tokens.
+/// `buffers` holds the names of the program's memory buffers.
+/// Example: params_valid(["cpy", "1", "2"], []) returns false, params_valid(["jnz", "buf", "2"], ["buf"]) returns false
+pub fn params_valid(toks: &Vec<&str>, buffers: &[&str]) -> bool {
+    let param_rule = PARAM_RULES.get(toks[0].to_lowercase().as_str()).unwrap();
+    param_count_valid(param_rule, toks.len() - 1) &&
+        param_rule.to_uppercase().chars().zip(toks[1..].iter()).all(|(rule, tok)| match rule {
+            'R' => is_register_name(tok) && !buffers.contains(tok),
+            'L' => is_literal(tok).is_ok(),
+            'M' => buffers.contains(tok),
+            'S' => string_literal(tok).is_ok(),
+            'O' => COMPARISON_OPERATORS.contains(tok),
+            'K' => TRAP_KINDS.contains(tok),
+            _ => (is_literal(tok).is_ok() || is_register_name(tok)) && !buffers.contains(tok)
+        })
+}
+
+/// Checks whether the given token can be the name of a register, including the local names of `Procedure::local_name`.
+fn is_register_name(tok: &str) -> bool {
+    tok.splitn(2, '.').all(|part| regname_valid(part).is_ok())
+}
+
+/// Returns whether `left` and `right` compare as the operator at `operator` in COMPARISON_OPERATORS says.
+pub fn compare<T: Ord>(left: T, operator: usize, right: T) -> bool {
+    match COMPARISON_OPERATORS[operator] {
+        "==" => left == right,
+        "!=" => left != right,
+        "<" => left < right,
+        "<=" => left <= right,
+        ">" => left > right,
+        _ => left >= right
+    }
+}
+
 /// Attempts to evaluate the given token and return the numeric value.
 /// Also borrows the registers HashMap for lookups.
 /// Example: evaluate_val("mny", {"t5" => 42, "mny" => -3}) returns -3
 /// Example: evaluate_val("-41", {"irr" => 0}) returns -41
 /// Note: For interpreter only
-pub fn evaluate_val(tok: &str, regs: &HashMap<String, i32>)
-                    -> Result<i32, String> {
-    match i32::from_str(tok) {
+pub fn evaluate_val(tok: &str, regs: &HashMap<String, i128>)
+                    -> Result<i128, String> {
+    match is_literal(tok) {
         Ok(literal) => Ok(literal),
         Err(_) => {
             let validate_result = regname_valid(tok);
@@ -231,34 +1363,54 @@ pub fn worth_execution(toks: &Vec<&str>) -> Result<(), &'static str> {
 
 /// Describes a generated token from the source file.
 /// Token types are in the `TokenType` enum.
-/// `val` can represent a literal, a register index, or a keyword index.
+/// `val` can represent a literal, a register index, a memory buffer index, a string index, an operator index, or a keyword index.
 pub struct Token {
     pub type_: TokenType,
-    pub val: i32,
+    pub val: i128,
 }
 
 impl Token {
-    fn new(type_: TokenType, val: i32) -> Self {
+    fn new(type_: TokenType, val: i128) -> Self {
         Token {
             type_: type_,
             val: val,
         }
     }
 
-    pub fn to_bytearray(self) -> Vec<u8> {
+    /// Amount of bytes after the type byte in the bytecode blob of a token of the given type (see bytecode.rs).
+    /// The data of a LITERAL is as wide as the registers, or empty for big registers, whose literals follow the blob;
+    /// everything else is an index that fits in 4 bytes.
+    pub fn data_size(type_: &TokenType, width: Width) -> usize {
+        if *type_ == TokenType::LITERAL {
+            if width == Width::Big { 0 } else { width.bits() as usize / 8 }
+        } else {
+            4
+        }
+    }
+
+    pub fn to_bytearray(self, width: Width) -> Vec<u8> {
+        let size = Token::data_size(&self.type_, width);
         let mut output: Vec<u8> = vec![self.type_ as i32 as u8];
-        // Appears to use Two's Complement to represent the signed number (at least on macOS Sierra, Intel x86-64)
-        output.write_i32::<BigEndian>(self.val).unwrap();
-        assert_eq!(output.len(), 5);
+        // Big endian two's complement, truncated to the data size
+        for byte in (0..size).rev() {
+            output.push((self.val >> (byte * 8)) as u8);
+        }
         output
     }
 
-    pub fn from_bytearray(barray: &[u8]) -> Result<Self, String> {
+    /// Converts a blob back to a token. `barray` has to be as long as the blob, which depends on the type in its first byte (see `data_size`).
+    pub fn from_bytearray(barray: &[u8], width: Width) -> Result<Self, String> {
         let token_type = try_opt!(TokenType::from_i32(barray[0] as i32),
                                   "Failed to read TokenType value from Token byte-array".to_owned());
-        let mut valreader = Cursor::new(&barray[1..5]);
-        let value = try_failsafe!(valreader.read_i32::<BigEndian>(),
-                                      "Failed to read i32 value from Token byte-array".to_owned());
+        let data = &barray[1..];
+        if data.len() != Token::data_size(&token_type, width) {
+            return Err(format!("Token byte-array of type {} has {} bytes of data", token_type, data.len()));
+        }
+        // Sign-extended from the first byte
+        let mut value: i128 = if data.first().map_or(false, |&byte| byte & 0x80 != 0) { -1 } else { 0 };
+        for &byte in data {
+            value = (value << 8) | byte as i128;
+        }
         Ok(Self::new(token_type, value))
     }
 }
@@ -276,6 +1428,9 @@ pub enum TokenType {
     KEYWORD = 0,
     REGISTER = 1,
     LITERAL = 2,
+    BUFFER = 3,
+    STRING = 4,
+    OPERATOR = 5,
 }}
 
 impl fmt::Display for TokenType {
@@ -288,44 +1443,130 @@ pub fn index_of<T: PartialEq>(slice: &[T], item: &T) -> Option<usize> {
     slice.iter().position(|elem| elem == item)
 }
 
-pub fn to_tokens(line: &str, existing_regs: &mut Vec<String>) -> Result<Option<Vec<Token>>, String> {
-    let str_toks = tokenize_line(line);
-    let keyword = str_toks[0].to_lowercase();
+/// Converts the given line of ASMB+ to tokens, with jump targets resolved using `labels` as if this line was instruction `ip`.
+/// Returns None if the line has nothing to execute.
+pub fn to_tokens(line: &str, symbols: &mut Symbols, labels: &LabelMap, ip: u32) -> Result<Option<Vec<Token>>, String> {
+    let (_, unfolded) = split_label(&tokenize_line(line));
+    try!(call_valid(&unfolded, &symbols.procedures));
+    try!(trap_valid(&unfolded, &symbols.procedures));
+    let procedure_toks: Vec<String>;
+    let unfolded = if unfolded.first().map_or(false, |kw| kw.to_lowercase() == "proc") {
+        // The parameters are the first locals of the procedure, whose registers start here
+        let (toks, params) = {
+            let procedure = try_opt!(procedure_at(&symbols.procedures, ip), "PROC can only be used in a file".to_owned());
+            (procedure_tokens(procedure, symbols.regs.len()),
+             procedure.locals[..procedure.param_count].iter().map(|param| procedure.local_name(param).unwrap()).collect::<Vec<_>>())
+        };
+        for param in params {
+            symbols.regs.push(param);
+            symbols.reg_types.push(RegType::Int);
+        }
+        procedure_toks = toks;
+        procedure_toks.iter().map(|tok| tok.as_str()).collect()
+    } else {
+        unfolded
+    };
+    let folded = try!(fold_constants(&unfolded, symbols.width));
+    let unresolved: Vec<&str> = folded.iter().map(|tok| tok.as_str()).collect();
+    try!(literals_fit(&unresolved, symbols.width));
 
-    if let Err(problem) = line_valid(&str_toks) {
+    if let Err(problem) = line_valid(&unresolved) {
         return Err(format!("Line invalid: {}", problem));
     }
 
-    if worth_execution(&str_toks).is_err() {
+    if worth_execution(&unresolved).is_err() {
         return Ok(None);
     }
+    let resolved = qualify_locals(try!(resolve_labels(&unresolved, labels, ip)), procedure_at(&symbols.procedures, ip));
+    let mut str_toks: Vec<&str> = resolved.iter().map(|tok| tok.as_str()).collect();
+    let keyword = str_toks[0].to_lowercase();
+    try!(types_valid(&str_toks, |name| symbols.reg_type(name), symbols.width));
+    try!(registers_defined(&str_toks, |name| symbols.regs.iter().any(|reg| reg == name)));
 
-    // If keyword is "def", add the defined register to `existing_regs` because the existence of this register will be checked later
-    if keyword == "def" {
-        if existing_regs.contains(&str_toks[1].to_owned()) {
-            return Err(format!("def {}: Register name already exists", str_toks[1]));
+    // If keyword is "def" or "mem", add the defined name to `symbols` because the existence of this name will be checked later
+    if keyword == "def" || keyword == "mem" {
+        let (name, reg_type) = if keyword == "def" { try!(split_type(str_toks[1])) } else { (str_toks[1], RegType::Int) };
+        str_toks[1] = name;
+        let name = name.to_owned();
+        if symbols.regs.contains(&name) || symbols.buffer_index(&name).is_some() {
+            return Err(format!("{} {}: Register or memory buffer name already exists", keyword, name));
+        }
+        if keyword == "def" {
+            symbols.regs.push(name);
+            symbols.reg_types.push(reg_type);
+        } else {
+            let size = is_literal(str_toks[2]).unwrap();
+            if size <= 0 || size > u32::max_value() as i128 {
+                return Err(format!("mem {}: Memory buffer size should be between 1 and {}", name, u32::max_value()));
+            }
+            symbols.buffers.push((name, size as u32));
         }
-        existing_regs.push(str_toks[1].to_owned());
     }
 
     let mut output: Vec<Token> = vec![Token::new(TokenType::KEYWORD, 
-        KEYWORD_INDEX.iter().position(|kw| kw.to_owned() == keyword).unwrap() as i32)];
+        KEYWORD_INDEX.iter().position(|kw| kw.to_owned() == keyword).unwrap() as i128)];
+    let param_rule = PARAM_RULES.get(keyword.as_str()).unwrap().as_bytes();
     
     for index in 1..str_toks.len() {
-        if let Ok(val) = is_literal(str_toks[index]) {
+        if keyword == "assert" && index == 4 {
+            // Replaced by the description below
+        } else if param_rule[index - 1] == b'O' {
+            output.push(Token::new(TokenType::OPERATOR, index_of(&COMPARISON_OPERATORS, &str_toks[index]).unwrap() as i128));
+        } else if param_rule[index - 1] == b'K' {
+            output.push(Token::new(TokenType::LITERAL, index_of(&TRAP_KINDS, &str_toks[index]).unwrap() as i128 + 1));
+        } else if param_rule[index - 1] == b'S' {
+            let string = string_literal(str_toks[index]).unwrap();
+            output.push(Token::new(TokenType::STRING, symbols.string_index(string) as i128));
+        } else if param_rule[index - 1] == b'M' {
+            match symbols.buffer_index(str_toks[index]) {
+                Some(buffer) => output.push(Token::new(TokenType::BUFFER, buffer as i128)),
+                None => return Err(format!("Memory buffer name unknown: {}", str_toks[index]))
+            }
+        } else if let Ok(val) = is_literal(str_toks[index]) {
             output.push(Token::new(TokenType::LITERAL, val));
-        } else if !existing_regs.contains(&str_toks[index].to_owned()) {
+        } else if !symbols.regs.contains(&str_toks[index].to_owned()) {
             return Err(format!("Register name unknown: {}", str_toks[index]));
         } else {
-            output.push(Token::new(TokenType::REGISTER, index_of(existing_regs, &str_toks[index].to_owned()).unwrap() as i32));
+            output.push(Token::new(TokenType::REGISTER, index_of(&symbols.regs, &str_toks[index].to_owned()).unwrap() as i128));
         }
     }
+    // ASSERT always gets a message, which describes the line including the message given in the source (if any)
+    if keyword == "assert" {
+        let description = assert_description(&unresolved);
+        output.push(Token::new(TokenType::STRING, symbols.string_index(description) as i128));
+    }
     Ok(Some(output))
 }
 
+/// Returns the text that a failed ASSERT shows for the given line, which is the line with its label split off and its constants folded.
+/// It is taken before labels are resolved and locals are renamed, so that the interpreter and the C compiler show the same text.
+pub fn assert_description(toks: &Vec<&str>) -> String {
+    toks.join(" ")
+}
+
+/// Converts an entire ASMB+ program to lines of tokens, skipping lines that have nothing to execute.
+/// `symbols` is filled with the registers, memory buffers, and strings of the program.
+pub fn to_program(lines: &[&str], origins: &[String], symbols: &mut Symbols) -> Result<Vec<Vec<Token>>, String> {
+    let labels = try!(collect_labels(lines, origins));
+    symbols.procedures = try!(procedures(lines, origins));
+    let mut program: Vec<Vec<Token>> = Vec::new();
+    for (linenum, line) in lines.iter().enumerate() {
+        match to_tokens(line, symbols, &labels, program.len() as u32) {
+            Ok(Some(tokens)) => {
+                program.push(tokens);
+                symbols.origins.push(line_origin(origins, linenum));
+            },
+            Ok(None) => {},
+            Err(problem) => return Err(format!("{}: {}", line_origin(origins, linenum), problem))
+        }
+    }
+    Ok(program)
+}
+
 #[cfg(test)]
 mod parse_test {
     use parser::*;
+    use options::Width;
     
     #[test]
     fn tokenize() {
@@ -333,6 +1574,45 @@ mod parse_test {
         assert_eq!(tokens, vec!["Hello", "world", "I'm", "test\\", "12345"]);
     }
 
+    #[test]
+    fn strings() {
+        assert_eq!(tokenize_line("outs \"Hello, world\\\" \" "), vec!["outs", "\"Hello, world\\\" \""]);
+        assert_eq!(string_literal("\"a\\tb\\n\\\"\"").unwrap(), "a\tb\n\"");
+        assert!(string_literal("\"a\\qb\"").is_err());
+        assert!(line_valid(&vec!["outs", "\"unterminated"]).is_err());
+        assert!(line_valid(&vec!["out", "\"text\""]).is_err());
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(is_literal("-0x1F"), Ok(-31));
+        assert_eq!(is_literal("0b101"), Ok(5));
+        assert_eq!(is_literal("0o17"), Ok(15));
+        assert_eq!(is_literal("-0x80000000"), Ok(i32::min_value() as i128));
+        assert_eq!(is_literal("0xFFFFFFFF"), Ok(4294967295));
+        assert!(literals_fit(&vec!["def", "a", "0xFFFFFFFF"], Width::I32).is_err());
+        assert!(literals_fit(&vec!["def", "a", "0xFFFFFFFF"], Width::I64).is_ok());
+        assert!(is_literal("0x-1").is_err());
+        assert_eq!(tokenize_line("outc ' '"), vec!["outc", "' '"]);
+        assert_eq!(is_literal("'A'"), Ok(65));
+        assert_eq!(is_literal("'\\n'"), Ok(10));
+        assert_eq!(is_literal("'\\''"), Ok(39));
+        assert!(is_literal("'ab'").is_err());
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(tokenize_line("cpy (2 * 3) n"), vec!["cpy", "(2 * 3)", "n"]);
+        assert_eq!(evaluate_expression("26*4+1", Width::I32), Ok(105));
+        assert_eq!(evaluate_expression("(0x10 + 'A') * -2", Width::I32), Ok(-162));
+        assert_eq!(evaluate_expression("-7/2 - -7%2", Width::I32), Ok(-2));
+        assert!(evaluate_expression("2147483647+1", Width::I32).unwrap_err().starts_with("Overflow of 32-bit integers at `2147483647+1`"));
+        assert_eq!(evaluate_expression("2147483647+1", Width::I64), Ok(2147483648));
+        assert!(evaluate_expression("1/(2-2)", Width::I32).is_err());
+        assert!(evaluate_expression("(1+2", Width::I32).is_err());
+        assert_eq!(fold_constants(&vec!["def", "a", "2*3"], Width::I32).unwrap(), vec!["def", "a", "6"]);
+    }
+
     #[test]
     fn regname_check() {
         assert!(regname_valid("AValidRegister").is_ok());
@@ -345,11 +1625,72 @@ mod parse_test {
     #[test]
     fn line_check() {
         // Target 1: empty / comment
-        assert!(line_valid(vec![]).is_ok());
-        assert!(line_valid(vec!["#", "this line is a comment"]).is_ok());
+        assert!(line_valid(&vec![]).is_ok());
+        assert!(line_valid(&vec!["#", "this line is a comment"]).is_ok());
 
         // Target 2: unknown keyword
-        assert!(line_valid(vec!["mykw", "342", "412"]).is_err());
+        assert!(line_valid(&vec!["mykw", "342", "412"]).is_err());
         // TODO: unfinished
     }
+
+    #[test]
+    fn labels() {
+        let mut symbols = Symbols::new();
+        let program = to_program(&vec!["def c 3", "loop:", "# comment", "dec c", "jnz c loop", "jnz 1 end", "end: outn c"], &[], &mut symbols).unwrap();
+        assert_eq!(program.len(), 5);
+        assert_eq!(program[2][2].val, -1);
+        assert_eq!(program[3][2].val, 1);
+
+        assert!(collect_labels(&vec!["a:", "inc b", "a: dec b"], &[]).is_err());
+        // Jump targets that are neither labels nor registers are reported as such, whichever keyword jumps
+        for line in &["jnz c nowhere", "jeq c 1 nowhere", "call nowhere"] {
+            assert_eq!(to_program(&vec!["def c 1", line], &[], &mut Symbols::new()).err(), Some("Line 2: Undefined label or register 'nowhere'".to_owned()));
+        }
+        assert!(to_program(&vec!["def c 1", "jnz c c"], &[], &mut Symbols::new()).is_ok());
+    }
+
+    #[test]
+    fn register_types() {
+        let mut symbols = Symbols::new();
+        to_program(&vec!["def small:u8 255", "def flag:bool 1", "def n 0", "cpy flag small", "inct n small"], &[], &mut symbols).unwrap();
+        assert_eq!(symbols.reg_types, vec![RegType::U8, RegType::Bool, RegType::Int]);
+
+        assert!(to_program(&vec!["def small:u8 256"], &[], &mut Symbols::new()).is_err());
+        assert!(to_program(&vec!["def small:u8 0", "def n 0", "cpy n small"], &[], &mut Symbols::new()).is_err());
+        assert!(to_program(&vec!["def small:u9 0"], &[], &mut Symbols::new()).is_err());
+    }
+
+    #[test]
+    fn procedures() {
+        let mut symbols = Symbols::new();
+        let program = to_program(&vec!["def n 2", "proc twice n", "def m n", "inct m n", "return m", "endp", "call twice n"], &[], &mut symbols).unwrap();
+        assert_eq!(symbols.regs, vec!["n", "twice.n", "twice.m"]);
+        assert_eq!(symbols.procedures[0].locals, vec!["n", "m"]);
+        assert_eq!(program[2][1].val, 2);
+
+        assert!(to_program(&vec!["proc f a", "proc g", "endp", "endp"], &[], &mut Symbols::new()).is_err());
+        assert!(to_program(&vec!["proc f a a", "endp"], &[], &mut Symbols::new()).is_err());
+        assert!(to_program(&vec!["proc f a", "endp", "call f"], &[], &mut Symbols::new()).is_err());
+        assert!(to_program(&vec!["proc f", "ret", "endp"], &[], &mut Symbols::new()).is_err());
+        assert!(to_program(&vec!["return 1"], &[], &mut Symbols::new()).is_err());
+
+        // Locals are qualified wherever registers can be, jump offsets included
+        let mut symbols = Symbols::new();
+        let program = to_program(&vec!["proc skip n", "jnz 1 n", "endp", "call skip 2"], &[], &mut symbols).unwrap();
+        assert!(program[1][2].type_ == TokenType::REGISTER && symbols.regs[program[1][2].val as usize] == "skip.n");
+        assert!(to_program(&vec!["def big:u64 0", "proc f n", "endp", "call f big"], &[], &mut Symbols::new()).is_err());
+    }
+
+    #[test]
+    fn traps() {
+        let program = to_program(&vec!["proc h code line", "endp", "trap stack h", "trap div 0"], &[], &mut Symbols::new()).unwrap();
+        assert_eq!((program[2][1].val, program[2][2].val), (3, -2));
+        assert_eq!(program[3][1].val, 1);
+        assert_eq!(to_program(&vec!["trap mem 0"], &[], &mut Symbols::new()).unwrap()[0][1].val, 4);
+        assert_eq!(origin_line("Line 12 of lib.asmb"), 12);
+
+        assert!(to_program(&vec!["proc h code", "endp", "trap div h"], &[], &mut Symbols::new()).is_err());
+        assert!(to_program(&vec!["loop: trap div loop"], &[], &mut Symbols::new()).is_err());
+        assert!(to_program(&vec!["trap overflow 0"], &[], &mut Symbols::new()).is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,99 @@
+// I/O ports of OUTP and INP for the interpreter, bound to streams, files, and memory buffers by `--port N=TARGET` (see options::Port).
+// Every port reads and writes single bytes. gen_c::prelude emits the same model in C, with a table of FILE pointers.
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use interpret;
+use options::{Port, PORT_COUNT};
+
+/// State of one port while a program runs.
+enum Channel {
+    Unbound,
+    Stdin,
+    Stdout,
+    Stderr,
+    /// A file that neither INP nor OUTP has used yet
+    Unopened(String),
+    Reader(BufReader<File>),
+    Writer(BufWriter<File>),
+    Memory(VecDeque<u8>),
+}
+
+pub struct Ports {
+    /// Channel of each port, indexed by the port's number
+    channels: Vec<Channel>,
+}
+
+impl Ports {
+    /// Binds the ports as `bindings` says; the other ports are unbound.
+    pub fn new<'a, I>(bindings: I) -> Self where I: IntoIterator<Item = (&'a u8, &'a Port)> {
+        let mut channels: Vec<Channel> = (0..PORT_COUNT).map(|_| Channel::Unbound).collect();
+        for (&port, target) in bindings {
+            channels[port as usize] = match *target {
+                Port::Stdin => Channel::Stdin,
+                Port::Stdout => Channel::Stdout,
+                Port::Stderr => Channel::Stderr,
+                Port::File(ref path) => Channel::Unopened(path.clone()),
+                Port::Memory => Channel::Memory(VecDeque::new()),
+            };
+        }
+        Ports { channels: channels }
+    }
+
+    /// Opens the file of `port` for reading or writing if it hasn't been used yet.
+    fn open(&mut self, port: u8, writing: bool) -> Result<(), String> {
+        let channel = &mut self.channels[port as usize];
+        let opened = if let Channel::Unopened(ref path) = *channel {
+            let direction = if writing { "writing" } else { "reading" };
+            let file = try_failsafe!(if writing { File::create(path) } else { File::open(path) },
+                                     format!("Cannot open '{}' for {} (port {})", path, direction, port));
+            if writing { Channel::Writer(BufWriter::new(file)) } else { Channel::Reader(BufReader::new(file)) }
+        } else {
+            return Ok(());
+        };
+        *channel = opened;
+        Ok(())
+    }
+
+    /// Writes `byte` to `port`.
+    pub fn write(&mut self, port: u8, byte: u8) -> Result<(), String> {
+        try!(self.open(port, true));
+        let written = match self.channels[port as usize] {
+            Channel::Unbound => return Err(format!("Port {} is not bound to anything (see --port)", port)),
+            Channel::Stdout => io::stdout().write_all(&[byte]),
+            Channel::Stderr => io::stderr().write_all(&[byte]),
+            Channel::Writer(ref mut file) => file.write_all(&[byte]),
+            Channel::Memory(ref mut buffer) => {
+                buffer.push_back(byte);
+                Ok(())
+            },
+            _ => return Err(format!("Port {} cannot be written to", port))
+        };
+        try_failsafe!(written, format!("Failed to write to port {}", port));
+        Ok(())
+    }
+
+    /// Reads a byte from `port`, or None at its end.
+    pub fn read(&mut self, port: u8) -> Result<Option<u8>, String> {
+        try!(self.open(port, false));
+        match self.channels[port as usize] {
+            Channel::Unbound => Err(format!("Port {} is not bound to anything (see --port)", port)),
+            // Shares the buffer of STDIN with INN and INCH
+            Channel::Stdin => {
+                let stdin = io::stdin();
+                interpret::stdin_byte(&mut stdin.lock(), true)
+            },
+            Channel::Reader(ref mut file) => {
+                let mut byte = [0u8];
+                match file.read(&mut byte) {
+                    Ok(0) => Ok(None),
+                    Ok(_) => Ok(Some(byte[0])),
+                    Err(_) => Err(format!("Failed to read from port {}", port))
+                }
+            },
+            Channel::Memory(ref mut buffer) => Ok(buffer.pop_front()),
+            _ => Err(format!("Port {} cannot be read from", port))
+        }
+    }
+}
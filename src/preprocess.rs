@@ -0,0 +1,407 @@
+// The preprocessor of Assembunny+, which runs in front of the parser for every file loaded by loader.rs.
+// Lines starting with '%' are directives:
+//
+//   %include "file.asmb"   Inserts the lines of another file (relative to the including file) in place of this line
+//   %const NAME 42         Replaces every later token NAME with 42 (the value may be a constant expression such as (OTHER * 2))
+//   %ifdef NAME            Keeps the following lines only if NAME was defined with %const or `-D NAME=VAL` on the command line...
+//   %else                  ...or only if it wasn't...
+//   %endif                 ...up to here. %ifdef blocks can be nested.
+//
+// The preprocessor also expands macros, which are defined with `macro NAME PARAM1 PARAM2 ...`, followed by the body lines and `endm`.
+// A line whose keyword is the name of a macro is replaced by the body, with every token that is the name of a parameter replaced by the corresponding argument.
+// Labels and registers (or memory buffers) defined in the body are local to each expansion: they get renamed to `_mN_name`, where N counts the expansions.
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use parser;
+use options::Width;
+
+/// Lines of ASMB+ after preprocessing, ready for `parser::to_program`.
+pub struct Source {
+    /// Lines that are left after preprocessing, with the constants replaced
+    pub lines: Vec<String>,
+    /// Where each line of `lines` comes from, such as "Line 3" or "Line 3 of lib.asmb", for error messages
+    pub origins: Vec<String>,
+}
+
+impl Source {
+    pub fn as_strs(&self) -> Vec<&str> {
+        self.lines.iter().map(|line| line.as_str()).collect()
+    }
+}
+
+/// A macro defined with `macro NAME PARAM1 PARAM2 ...` and `endm`.
+#[derive(Clone)]
+struct Macro {
+    params: Vec<String>,
+    /// Lines between `macro` and `endm`, and where each of them is
+    body: Vec<(String, String)>,
+}
+
+/// Constants and macros defined so far.
+struct Definitions {
+    consts: HashMap<String, String>,
+    macros: HashMap<String, Macro>,
+    /// Amount of macro expansions so far, used to give the local names of each expansion a unique prefix
+    expansions: usize,
+}
+
+/// State of an %ifdef block that hasn't reached its %endif yet.
+struct Conditional {
+    /// Whether the name after %ifdef is defined
+    defined: bool,
+    /// Whether %else was reached
+    in_else: bool,
+    /// Where the %ifdef is, for the error when it is never closed
+    origin: String,
+}
+
+/// Preprocesses the file at `path`. `defines` holds the names and values defined on the command line.
+pub fn preprocess(path: &str, defines: &[(String, String)]) -> Result<Source, String> {
+    let mut defs = Definitions {
+        consts: HashMap::new(),
+        macros: HashMap::new(),
+        expansions: 0,
+    };
+    for &(ref name, ref val) in defines {
+        try_err_fallthru!(define(&mut defs.consts, name, val), "-D: ");
+    }
+    let mut source = Source {
+        lines: Vec::new(),
+        origins: Vec::new(),
+    };
+    try!(include(Path::new(path), &mut Vec::new(), &mut defs, &mut source));
+    Ok(source)
+}
+
+/// Defines a constant, checking its name and value.
+/// The value may be a constant expression involving the constants defined before, which is folded right away.
+/// Its range is only checked against the widest registers here, since the register width is up to the backend.
+fn define(consts: &mut HashMap<String, String>, name: &str, val: &str) -> Result<(), String> {
+    try!(name_valid(name));
+    let val = substitute_token(val, consts);
+    let val = if parser::is_expression(&val) {
+        try_err_fallthru!(parser::evaluate_expression(&val, Width::I128), format!("Value of constant '{}': ", name)).to_string()
+    } else if parser::is_literal(&val).is_ok() {
+        val
+    } else {
+        return Err(format!("Value '{}' of constant '{}' is not a literal", val, name));
+    };
+    if consts.insert(name.to_owned(), val).is_some() {
+        return Err(format!("Constant '{}' is already defined", name));
+    }
+    Ok(())
+}
+
+/// Checks the name of a new constant or macro.
+fn name_valid(name: &str) -> Result<(), String> {
+    if let Err(problem) = parser::regname_valid(name) {
+        return Err(format!("Invalid name: {}", problem));
+    }
+    if parser::PARAM_RULES.contains_key(name.to_lowercase().as_str()) {
+        return Err(format!("Name '{}' is a keyword", name));
+    }
+    Ok(())
+}
+
+/// Appends the preprocessed lines of the file at `path` to `source`.
+/// `includers` holds the files that (directly or indirectly) include this one, outermost first, so include cycles can be detected.
+fn include(path: &Path, includers: &mut Vec<PathBuf>, defs: &mut Definitions, source: &mut Source) -> Result<(), String> {
+    let canonical = try_failsafe!(path.canonicalize(), format!("File not found for path {:?}", path));
+    if let Some(start) = includers.iter().position(|includer| *includer == canonical) {
+        let cycle: Vec<String> = includers[start..].iter().chain(Some(&canonical)).map(|file| file.display().to_string()).collect();
+        return Err(format!("Include cycle: {}", cycle.join(" -> ")));
+    }
+    let contents = file_to_string!(path);
+    let is_root = includers.is_empty();
+    includers.push(canonical);
+
+    let mut conditionals: Vec<Conditional> = Vec::new();
+    // Name, definition, and origin of the macro whose body is being read, if any
+    let mut recording: Option<(String, Macro, String)> = None;
+    for (index, line) in contents.lines().enumerate() {
+        let origin = if is_root {
+            format!("Line {}", index + 1)
+        } else {
+            format!("Line {} of {}", index + 1, path.display())
+        };
+        let toks = parser::tokenize_line(line);
+        let first = toks.first().map(|tok| tok.to_lowercase()).unwrap_or(String::new());
+
+        if recording.is_some() {
+            if first.starts_with('%') || first == "macro" {
+                return Err(format!("{}: Macro bodies cannot contain directives or macro definitions", origin));
+            }
+            if first == "endm" {
+                let (name, mac, _) = recording.take().unwrap();
+                defs.macros.insert(name, mac);
+            } else {
+                recording.as_mut().unwrap().1.body.push((line.to_owned(), origin));
+            }
+            continue;
+        }
+
+        let live = conditionals.iter().all(|cond| cond.defined != cond.in_else);
+        if !first.starts_with('%') {
+            if !live {
+                continue;
+            }
+            if first == "macro" {
+                if toks.len() < 2 {
+                    return Err(format!("{}: macro expects a name", origin));
+                }
+                try_err_fallthru!(name_valid(toks[1]), format!("{}: ", origin));
+                if defs.macros.contains_key(toks[1]) {
+                    return Err(format!("{}: Macro '{}' is already defined", origin, toks[1]));
+                }
+                for param in &toks[2..] {
+                    try_err_fallthru!(parser::regname_valid(param), format!("{}: Invalid parameter name: ", origin));
+                }
+                recording = Some((toks[1].to_owned(), Macro {
+                    params: toks[2..].iter().map(|param| param.to_string()).collect(),
+                    body: Vec::new(),
+                }, origin));
+            } else if first == "endm" {
+                return Err(format!("{}: endm without macro", origin));
+            } else {
+                try!(emit(line, origin, defs, source, &mut Vec::new()));
+            }
+            continue;
+        }
+
+        let directive = first;
+        let args = &toks[1..];
+        let expected_args = match directive.as_str() {
+            "%include" | "%ifdef" => 1,
+            "%const" => 2,
+            "%else" | "%endif" => 0,
+            _ => return Err(format!("{}: Unknown directive '{}'", origin, directive))
+        };
+        if args.len() != expected_args {
+            return Err(format!("{}: {} expects {} parameter(s), got {}", origin, directive, expected_args, args.len()));
+        }
+        match directive.as_str() {
+            "%ifdef" => conditionals.push(Conditional {
+                defined: defs.consts.contains_key(args[0]),
+                in_else: false,
+                origin: origin,
+            }),
+            "%else" => match conditionals.last_mut() {
+                Some(ref mut cond) if !cond.in_else => cond.in_else = true,
+                _ => return Err(format!("{}: %else without %ifdef", origin))
+            },
+            "%endif" => if conditionals.pop().is_none() {
+                return Err(format!("{}: %endif without %ifdef", origin));
+            },
+            "%const" if live => try_err_fallthru!(define(&mut defs.consts, args[0], args[1]), format!("{}: ", origin)),
+            "%include" if live => {
+                let relative = try_err_fallthru!(parser::string_literal(args[0]), format!("{}: %include expects a file name in double quotes: ", origin));
+                let target = path.parent().unwrap_or(Path::new("")).join(relative);
+                try_err_fallthru!(include(&target, includers, defs, source), format!("{}: ", origin));
+            },
+            _ => {}
+        }
+    }
+    if let Some((name, _, origin)) = recording {
+        return Err(format!("{}: Macro '{}' is never closed with endm", origin, name));
+    }
+    if let Some(cond) = conditionals.last() {
+        return Err(format!("{}: %ifdef is never closed with %endif", cond.origin));
+    }
+    includers.pop();
+    Ok(())
+}
+
+/// Appends the given line to `source`, or its expansion if it invokes a macro.
+/// `expanding` holds the names of the macros being expanded, outermost first, so a macro that (directly or indirectly) invokes itself can be detected.
+fn emit(line: &str, origin: String, defs: &mut Definitions, source: &mut Source, expanding: &mut Vec<String>) -> Result<(), String> {
+    let toks = parser::tokenize_line(line);
+    let (label, rest) = parser::split_label(&toks);
+    let (name, mac) = match rest.first().and_then(|kw| defs.macros.get(*kw).map(|mac| (kw.to_string(), mac.clone()))) {
+        Some(found) => found,
+        None => {
+            source.lines.push(substitute(line, &toks, &defs.consts));
+            source.origins.push(origin);
+            return Ok(());
+        }
+    };
+    if expanding.contains(&name) {
+        return Err(format!("{}: Macro '{}' expands itself", origin, name));
+    }
+    let args = &rest[1..];
+    if args.len() != mac.params.len() {
+        return Err(format!("{}: Macro '{}' expects {} argument(s), got {}", origin, name, mac.params.len(), args.len()));
+    }
+    if let Some(label) = label {
+        source.lines.push(format!("{}:", label));
+        source.origins.push(origin.clone());
+    }
+
+    defs.expansions += 1;
+    let prefix = format!("_m{}_", defs.expansions);
+    // Labels and registers defined in the body
+    let mut locals: Vec<&str> = Vec::new();
+    let body_toks: Vec<Vec<&str>> = mac.body.iter().map(|&(ref body_line, _)| parser::tokenize_line(body_line)).collect();
+    for btoks in &body_toks {
+        let (body_label, body_rest) = parser::split_label(btoks);
+        if let Some(body_label) = body_label {
+            locals.push(body_label);
+        }
+        if body_rest.len() > 1 && ["def", "mem"].contains(&body_rest[0].to_lowercase().as_str()) {
+            // DEF may give the register a type, as in `def tmp:u8 0`
+            locals.push(body_rest[1].split(':').next().unwrap());
+        }
+    }
+
+    expanding.push(name.clone());
+    for (btoks, &(_, ref body_origin)) in body_toks.iter().zip(mac.body.iter()) {
+        if parser::worth_execution(btoks).is_err() {
+            continue;
+        }
+        let expanded: Vec<String> = btoks.iter().map(|tok| {
+            if let Some(param) = mac.params.iter().position(|param| param == tok) {
+                args[param].to_owned()
+            } else if locals.contains(tok) {
+                format!("{}{}", prefix, tok)
+            } else if tok.find(':').map_or(false, |colon| locals.contains(&&tok[..colon])) {
+                // Labels, and registers defined with a type
+                format!("{}{}", prefix, tok)
+            } else {
+                tok.to_string()
+            }
+        }).collect();
+        try!(emit(&expanded.join(" "), format!("{} (in macro '{}', expanded at {})", body_origin, name, origin), defs, source, expanding));
+    }
+    expanding.pop();
+    Ok(())
+}
+
+/// Returns `line` with every token that is the name of a constant replaced by the constant's value.
+/// Names inside constant expressions are replaced too, so the parser can fold them (see `parser::fold_constants`).
+/// `toks` are the tokens of `line`.
+fn substitute(line: &str, toks: &Vec<&str>, consts: &HashMap<String, String>) -> String {
+    if parser::worth_execution(toks).is_err() {
+        return line.to_owned();
+    }
+    let substituted: Vec<String> = toks.iter().map(|tok| substitute_token(tok, consts)).collect();
+    if substituted.iter().zip(toks.iter()).all(|(new, old)| new == old) {
+        return line.to_owned();
+    }
+    substituted.join(" ")
+}
+
+/// Returns the given token, or the value of the constant it names, or (for a constant expression) the expression with every constant name in it replaced.
+fn substitute_token(tok: &str, consts: &HashMap<String, String>) -> String {
+    if let Some(val) = consts.get(tok) {
+        return val.clone();
+    }
+    if !parser::is_expression(tok) {
+        return tok.to_owned();
+    }
+    let mut output = String::new();
+    let mut name = String::new();
+    let mut in_char_literal = false;
+    let mut escaped = false;
+    for ch in tok.chars().chain(Some(' ')) {
+        if in_char_literal {
+            in_char_literal = ch != '\'' || escaped;
+            escaped = ch == '\\' && !escaped;
+            output.push(ch);
+        } else if ch.is_alphanumeric() || ch == '_' {
+            name.push(ch);
+        } else {
+            // Names start with a letter or an underscore, unlike literals such as 0xFF
+            match consts.get(&name) {
+                Some(val) if !name.starts_with(|first: char| first.is_digit(10)) => output.push_str(&format!("({})", val)),
+                _ => output.push_str(&name)
+            }
+            name.clear();
+            in_char_literal = ch == '\'';
+            output.push(ch);
+        }
+    }
+    output.pop();
+    output
+}
+
+#[cfg(test)]
+mod preprocess_test {
+    use preprocess::*;
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use std::process;
+
+    /// Writes the given files (name and contents) to a directory of their own, then preprocesses the first one.
+    fn preprocess_files(test: &str, files: &[(&str, &str)], defines: &[(String, String)]) -> Result<Source, String> {
+        let dir = env::temp_dir().join(format!("asmb_preprocess_{}_{}", test, process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for &(name, contents) in files {
+            fs::File::create(dir.join(name)).unwrap().write_all(contents.as_bytes()).unwrap();
+        }
+        let source = preprocess(dir.join(files[0].0).to_str().unwrap(), defines);
+        fs::remove_dir_all(&dir).unwrap();
+        source
+    }
+
+    #[test]
+    fn constants() {
+        let source = preprocess_files("constants", &[("main.asmb", "%const SIZE 4\n%const AREA (SIZE * SIZE)\ndef a SIZE\ncpy AREA a\ncpy (AREA+1) a\nouts \"SIZE\"\n")], &[]).unwrap();
+        assert_eq!(source.lines, vec!["def a 4", "cpy 16 a", "cpy ((16)+1) a", "outs \"SIZE\""]);
+        assert_eq!(source.origins, vec!["Line 3", "Line 4", "Line 5", "Line 6"]);
+
+        let defined = preprocess_files("constants_defined", &[("main.asmb", "def a WIDTH\n")], &[("WIDTH".to_owned(), "8".to_owned())]).unwrap();
+        assert_eq!(defined.lines, vec!["def a 8"]);
+        assert!(preprocess_files("constants_twice", &[("main.asmb", "%const A 1\n%const A 2\n")], &[]).is_err());
+        assert!(preprocess_files("constants_keyword", &[("main.asmb", "%const inc 1\n")], &[]).is_err());
+    }
+
+    #[test]
+    fn conditionals() {
+        let lines = "%const OUTER 1\n%ifdef OUTER\nouts \"a\"\n%ifdef INNER\nouts \"b\"\n%else\nouts \"c\"\n%endif\n%else\nouts \"d\"\n%ifdef OUTER\nouts \"e\"\n%endif\n%endif\n";
+        let source = preprocess_files("conditionals", &[("main.asmb", lines)], &[]).unwrap();
+        assert_eq!(source.lines, vec!["outs \"a\"", "outs \"c\""]);
+        let defined = preprocess_files("conditionals_defined", &[("main.asmb", lines)], &[("INNER".to_owned(), "1".to_owned())]).unwrap();
+        assert_eq!(defined.lines, vec!["outs \"a\"", "outs \"b\""]);
+
+        assert!(preprocess_files("conditionals_unclosed", &[("main.asmb", "%ifdef A\n%ifdef B\n%endif\n")], &[]).err().unwrap().starts_with("Line 1: "));
+        assert!(preprocess_files("conditionals_else", &[("main.asmb", "%ifdef A\n%else\n%else\n%endif\n")], &[]).is_err());
+        assert!(preprocess_files("conditionals_endif", &[("main.asmb", "%endif\n")], &[]).is_err());
+    }
+
+    #[test]
+    fn includes() {
+        let source = preprocess_files("includes", &[("main.asmb", "def a 0\n%include \"lib.asmb\"\nouts \"done\"\n"), ("lib.asmb", "%const STEP 2\ninct a STEP\n")], &[]).unwrap();
+        assert_eq!(source.lines, vec!["def a 0", "inct a 2", "outs \"done\""]);
+        assert!(source.origins[1].starts_with("Line 2 of ") && source.origins[1].ends_with("lib.asmb"));
+
+        let cycle = preprocess_files("includes_cycle", &[("main.asmb", "%include \"a.asmb\"\n"), ("a.asmb", "%include \"b.asmb\"\n"), ("b.asmb", "%include \"a.asmb\"\n")], &[]).err().unwrap();
+        assert!(cycle.contains("Include cycle: "), "{}", cycle);
+        assert!(cycle.contains("a.asmb -> ") && cycle.contains("b.asmb -> "), "{}", cycle);
+        assert!(preprocess_files("includes_self", &[("main.asmb", "%include \"main.asmb\"\n")], &[]).err().unwrap().contains("Include cycle: "));
+        assert!(preprocess_files("includes_missing", &[("main.asmb", "%include \"missing.asmb\"\n")], &[]).is_err());
+    }
+
+    #[test]
+    fn macros() {
+        let lines = "macro count r\nloop: def t r\ndec r\njnz r loop\nendm\ndef a 2\ncount a\ncount a\n";
+        let source = preprocess_files("macros", &[("main.asmb", lines)], &[]).unwrap();
+        assert_eq!(source.lines, vec!["def a 2", "_m1_loop: def _m1_t a", "dec a", "jnz a _m1_loop", "_m2_loop: def _m2_t a", "dec a", "jnz a _m2_loop"]);
+        assert_eq!(source.origins[1], "Line 2 (in macro 'count', expanded at Line 7)");
+        assert_eq!(source.origins[4], "Line 2 (in macro 'count', expanded at Line 8)");
+
+        // Only the name of a register defined with a type is renamed, wherever it is used
+        let typed = "macro wrap r\ndef tmp:u8 250\ninct tmp r\ncpy tmp r\nendm\ndef a:u8 10\nwrap a\n";
+        let source = preprocess_files("macros_typed", &[("main.asmb", typed)], &[]).unwrap();
+        assert_eq!(source.lines, vec!["def a:u8 10", "def _m1_tmp:u8 250", "inct _m1_tmp a", "cpy _m1_tmp a"]);
+
+        // Errors in an expansion name the line of the macro and the line that expands it
+        let nested = "macro inner a b\ninct a b\nendm\nmacro outer r\ninner r\nendm\ndef x 0\nouter x\n";
+        assert_eq!(preprocess_files("macros_nested", &[("main.asmb", nested)], &[]).err().unwrap(),
+                   "Line 5 (in macro 'outer', expanded at Line 8): Macro 'inner' expects 2 argument(s), got 1");
+        assert_eq!(preprocess_files("macros_recursive", &[("main.asmb", "macro again\nagain\nendm\nagain\n")], &[]).err().unwrap(),
+                   "Line 2 (in macro 'again', expanded at Line 4): Macro 'again' expands itself");
+        assert!(preprocess_files("macros_unclosed", &[("main.asmb", "macro open\ninc a\n")], &[]).err().unwrap().starts_with("Line 1: "));
+    }
+}
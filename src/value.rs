@@ -0,0 +1,216 @@
+// Values held by registers, the data stack and memory buffers while a program runs (see interpret.rs).
+// Fixed-width registers (see options::Width) always hold i128s; only big registers (`--width big`) grow past them.
+// Registers can also be declared with a type of their own (see `RegType`).
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Signed, Zero};
+use options::Width;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A value is `Small` whenever it fits in an i128, so that arithmetic only allocates once a big register outgrows 128 bits.
+/// Every function here keeps it that way, which makes the derived equality correct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Small(i128),
+    Big(BigInt),
+}
+
+impl Value {
+    /// Converts `val` to a Value, which is `Small` if possible.
+    pub fn from_big(val: BigInt) -> Self {
+        match val.to_i128() {
+            Some(small) => Value::Small(small),
+            None => Value::Big(val)
+        }
+    }
+
+    pub fn to_big(&self) -> BigInt {
+        match *self {
+            Value::Small(val) => BigInt::from(val),
+            Value::Big(ref val) => val.clone()
+        }
+    }
+
+    /// Returns the value if it fits in an i128.
+    #[inline]
+    pub fn small(&self) -> Option<i128> {
+        match *self {
+            Value::Small(val) => Some(val),
+            Value::Big(_) => None
+        }
+    }
+
+    /// Returns the value, or the closest i128 (the minimum or the maximum) if it does not fit in one.
+    /// Useful for jump offsets and indices, which are out of range either way.
+    pub fn saturate(&self) -> i128 {
+        match *self {
+            Value::Small(val) => val,
+            Value::Big(ref val) if val.is_negative() => i128::min_value(),
+            Value::Big(_) => i128::max_value()
+        }
+    }
+
+    /// Returns the lowest 128 bits of the value in two's complement, like a cast in C.
+    pub fn wrapping_i128(&self) -> i128 {
+        match *self {
+            Value::Small(val) => val,
+            Value::Big(ref val) => {
+                let bytes = val.to_signed_bytes_le();
+                let mut low: i128 = 0;
+                for (index, &byte) in bytes.iter().take(16).enumerate() {
+                    low |= (byte as i128) << (index * 8);
+                }
+                low
+            }
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match *self {
+            Value::Small(val) => val == 0,
+            Value::Big(ref val) => val.is_zero()
+        }
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (&Value::Small(left), &Value::Small(right)) => left.cmp(&right),
+            _ => self.to_big().cmp(&other.to_big())
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Small(val) => write!(fmtr, "{}", val),
+            Value::Big(ref val) => write!(fmtr, "{}", val)
+        }
+    }
+}
+
+enum_from_primitive! {
+/// Types that a register can be declared with (`def name:type value`), numbered as in the register table of bytecode files.
+/// Registers declared without a type are `Int`, which has the register width (see `options::Width`) and follows the overflow policy.
+/// The other types always wrap around, like assignments in C, and `Bool` turns every value other than 0 into 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RegType {
+    Int = 0,
+    I8 = 1,
+    I16 = 2,
+    I32 = 3,
+    I64 = 4,
+    U8 = 5,
+    U16 = 6,
+    U32 = 7,
+    U64 = 8,
+    Bool = 9,
+}
+}
+
+/// Names of the types that can be given to DEF, in the order of `RegType` (after `Int`)
+pub const TYPE_NAMES: [&'static str; 9] = ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "bool"];
+
+impl RegType {
+    /// Parses the name of a type given to DEF.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "i8" => Some(RegType::I8),
+            "i16" => Some(RegType::I16),
+            "i32" => Some(RegType::I32),
+            "i64" => Some(RegType::I64),
+            "u8" => Some(RegType::U8),
+            "u16" => Some(RegType::U16),
+            "u32" => Some(RegType::U32),
+            "u64" => Some(RegType::U64),
+            "bool" => Some(RegType::Bool),
+            _ => None
+        }
+    }
+
+    /// Returns the name of the type for error messages; `Int` is named after the register width.
+    pub fn name(&self, width: Width) -> &'static str {
+        match *self {
+            RegType::Int => match width {
+                Width::I32 => "i32",
+                Width::I64 => "i64",
+                Width::I128 => "i128",
+                Width::Big => "big"
+            },
+            _ => TYPE_NAMES[*self as usize - 1]
+        }
+    }
+
+    /// Number of bits of a value of this type (1 for `Bool`), which is also what masks the amount of a shift.
+    pub fn bits(&self, width: Width) -> u32 {
+        match *self {
+            RegType::Int => width.bits(),
+            RegType::I8 | RegType::U8 => 8,
+            RegType::I16 | RegType::U16 => 16,
+            RegType::I32 | RegType::U32 => 32,
+            RegType::I64 | RegType::U64 => 64,
+            RegType::Bool => 1
+        }
+    }
+
+    pub fn signed(&self) -> bool {
+        match *self {
+            RegType::Int | RegType::I8 | RegType::I16 | RegType::I32 | RegType::I64 => true,
+            _ => false
+        }
+    }
+
+    /// Returns the smallest and the largest value of this type, or None if it has no limits (`Int` with big registers).
+    pub fn range(&self, width: Width) -> Option<(i128, i128)> {
+        match *self {
+            RegType::Int if width == Width::Big => None,
+            RegType::Int => Some((width.min_value(), width.max_value())),
+            RegType::Bool => Some((0, 1)),
+            _ if self.signed() => Some((-1 << (self.bits(width) - 1), (1 << (self.bits(width) - 1)) - 1)),
+            _ => Some((0, (1 << self.bits(width)) - 1))
+        }
+    }
+
+    /// Returns whether `val` is a value of this type.
+    pub fn contains(&self, val: i128, width: Width) -> bool {
+        match self.range(width) {
+            Some((min, max)) => val >= min && val <= max,
+            None => true
+        }
+    }
+
+    /// Returns whether every value of this type is also a value of `other`.
+    pub fn fits_in(&self, other: RegType, width: Width) -> bool {
+        match (self.range(width), other.range(width)) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some((min, max)), Some((other_min, other_max))) => min >= other_min && max <= other_max
+        }
+    }
+
+    /// Converts `val` to this type the way C converts integers: modulo 2^bits, or to 0 or 1 for `Bool`.
+    /// `Int` is returned as-is, since the overflow policy takes care of it.
+    pub fn convert(&self, val: i128) -> i128 {
+        match *self {
+            RegType::Int => val,
+            RegType::Bool => (val != 0) as i128,
+            _ => {
+                // The width only matters for Int
+                let unused = 128 - self.bits(Width::I32);
+                if self.signed() {
+                    (val << unused) >> unused
+                } else {
+                    ((val << unused) as u128 >> unused) as i128
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+static inline void __asmb_outc(uint32_t code) {
+	if (code < 0x80) {
+		putchar((int) code);
+	} else if (code < 0x800) {
+		putchar((int) (0xC0 | code >> 6));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else if (code < 0x10000) {
+		putchar((int) (0xE0 | code >> 12));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else {
+		putchar((int) (0xF0 | code >> 18));
+		putchar((int) (0x80 | (code >> 12 & 0x3F)));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	}
+}
+
+int main(void) {
+__asmb_line_1:;
+	__asmb_int __asmb_reg_i = 10;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_sum = 0;
+__asmb_line_3:;
+	(void) __builtin_add_overflow(__asmb_reg_sum, __asmb_reg_i, &__asmb_reg_sum);
+__asmb_line_4:;
+	(void) __builtin_sub_overflow(__asmb_reg_i, 1, &__asmb_reg_i);
+__asmb_line_5:;
+	if (__asmb_reg_i != 0) goto __asmb_line_3;
+__asmb_line_6:;
+	if (!(__asmb_reg_i == 0)) { fflush(stdout); fputs("Line 7: Assertion failed: assert i == 0\n", stderr); fflush(stdout); fprintf(stderr, "Registers: i = %d, sum = %d\n", __asmb_reg_i, __asmb_reg_sum); return 1; }
+__asmb_line_7:;
+	if (!(__asmb_reg_sum == 55)) { fflush(stdout); fputs("Line 8: Assertion failed: assert sum == 55 \"sum of 1 to 10\"\n", stderr); fflush(stdout); fprintf(stderr, "Registers: i = %d, sum = %d\n", __asmb_reg_i, __asmb_reg_sum); return 1; }
+__asmb_line_8:;
+	printf("%d\n", __asmb_reg_sum);
+__asmb_line_9:;
+	{ __asmb_int __asmb_c = 10; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 10: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 10: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_10:;
+	if (!(__asmb_reg_sum < 50)) { fflush(stdout); fputs("Line 11: Assertion failed: assert sum < 50\n", stderr); fflush(stdout); fprintf(stderr, "Registers: i = %d, sum = %d\n", __asmb_reg_i, __asmb_reg_sum); return 1; }
+__asmb_line_11:;
+	fwrite("not reached unless assertions are skipped\n", 1, 42, stdout);
+__asmb_line_12:;
+return 0;
+}
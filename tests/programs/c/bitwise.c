@@ -0,0 +1,69 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+__asmb_line_1:;
+	__asmb_int __asmb_reg_x = -52;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_y = 0;
+__asmb_line_3:;
+	__asmb_reg_y = __asmb_reg_x;
+__asmb_line_4:;
+	{ __asmb_int __asmb_divisor = 5; if (__asmb_divisor == 0) { fflush(stdout); fputs("Line 5: Modulo by zero\n", stderr); return 1; } __asmb_reg_y = __asmb_divisor == -1 ? 0 : __asmb_reg_y % __asmb_divisor; }
+__asmb_line_5:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_6:;
+	__asmb_reg_y = __asmb_reg_x;
+__asmb_line_7:;
+	{ __asmb_int __asmb_divisor = -1; if (__asmb_divisor == 0) { fflush(stdout); fputs("Line 8: Modulo by zero\n", stderr); return 1; } __asmb_reg_y = __asmb_divisor == -1 ? 0 : __asmb_reg_y % __asmb_divisor; }
+__asmb_line_8:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_9:;
+	__asmb_reg_y = 12;
+__asmb_line_10:;
+	__asmb_reg_y &= 10;
+__asmb_line_11:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_12:;
+	__asmb_reg_y |= 3;
+__asmb_line_13:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_14:;
+	__asmb_reg_y ^= -1;
+__asmb_line_15:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_16:;
+	__asmb_reg_y = __asmb_reg_x;
+__asmb_line_17:;
+	{ __asmb_int __asmb_amount = 2 & 31; __asmb_reg_y = __asmb_reg_y < 0 ? ~(~__asmb_reg_y >> __asmb_amount) : __asmb_reg_y >> __asmb_amount; }
+__asmb_line_18:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_19:;
+	__asmb_reg_y = (__asmb_int) ((__asmb_uint) __asmb_reg_y << (33 & 31));
+__asmb_line_20:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_21:;
+	__asmb_reg_y = (__asmb_int) ((__asmb_uint) __asmb_reg_y << (-1 & 31));
+__asmb_line_22:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_23:;
+	(void) __builtin_sub_overflow(0, __asmb_reg_y, &__asmb_reg_y);
+__asmb_line_24:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_25:;
+	__asmb_reg_y = INT32_MIN;
+__asmb_line_26:;
+	(void) __builtin_sub_overflow(0, __asmb_reg_y, &__asmb_reg_y);
+__asmb_line_27:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_28:;
+	{ __asmb_int __asmb_divisor = -1; if (__asmb_divisor == 0) { fflush(stdout); fputs("Line 29: Modulo by zero\n", stderr); return 1; } __asmb_reg_y = __asmb_divisor == -1 ? 0 : __asmb_reg_y % __asmb_divisor; }
+__asmb_line_29:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_30:;
+return 0;
+}
@@ -0,0 +1,65 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+	int64_t __asmb_target = 0;
+__asmb_line_1:;
+	__asmb_int __asmb_reg_i = 0;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_off = 2;
+__asmb_line_3:;
+	(void) __builtin_add_overflow(__asmb_reg_i, 1, &__asmb_reg_i);
+__asmb_line_4:;
+	printf("%d ", __asmb_reg_i);
+__asmb_line_5:;
+	if (__asmb_reg_i < 5) goto __asmb_line_8;
+__asmb_line_6:;
+	if (__asmb_reg_i == 5) goto __asmb_line_10;
+__asmb_line_7:;
+	if (__asmb_reg_i > 5) goto __asmb_line_12;
+__asmb_line_8:;
+	printf("%d\n", -1);
+__asmb_line_9:;
+	if (1 != 0) goto __asmb_line_13;
+__asmb_line_10:;
+	printf("%d\n", 0);
+__asmb_line_11:;
+	if (1 != 0) goto __asmb_line_13;
+__asmb_line_12:;
+	printf("%d\n", 1);
+__asmb_line_13:;
+	if (__asmb_reg_i != 10) goto __asmb_line_3;
+__asmb_line_14:;
+	if (0 == 0) { __asmb_target = 14 + __asmb_reg_off; if (__asmb_target < 1) { fflush(stdout); fprintf(stderr, "Line 16: Jump target %lld is before the start of the program\n", (long long) (__asmb_target - 1)); return 1; } goto __asmb_jump_table; }
+__asmb_line_15:;
+	printf("%d\n", 999);
+__asmb_line_16:;
+	printf("%d\n", __asmb_reg_i);
+	goto __asmb_line_17;
+__asmb_jump_table:
+	switch (__asmb_target) {
+	case 1: goto __asmb_line_1;
+	case 2: goto __asmb_line_2;
+	case 3: goto __asmb_line_3;
+	case 4: goto __asmb_line_4;
+	case 5: goto __asmb_line_5;
+	case 6: goto __asmb_line_6;
+	case 7: goto __asmb_line_7;
+	case 8: goto __asmb_line_8;
+	case 9: goto __asmb_line_9;
+	case 10: goto __asmb_line_10;
+	case 11: goto __asmb_line_11;
+	case 12: goto __asmb_line_12;
+	case 13: goto __asmb_line_13;
+	case 14: goto __asmb_line_14;
+	case 15: goto __asmb_line_15;
+	case 16: goto __asmb_line_16;
+	default: goto __asmb_line_17;
+	}
+__asmb_line_17:;
+return 0;
+}
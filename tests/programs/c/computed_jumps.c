@@ -0,0 +1,50 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+	int64_t __asmb_target = 0;
+__asmb_line_1:;
+	__asmb_int __asmb_reg_a = 0;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_off = 2;
+__asmb_line_3:;
+	if (1 != 0) { __asmb_target = 3 + __asmb_reg_off; if (__asmb_target < 1) { fflush(stdout); fprintf(stderr, "Line 5: Jump target %lld is before the start of the program\n", (long long) (__asmb_target - 1)); return 1; } goto __asmb_jump_table; }
+__asmb_line_4:;
+	printf("%d\n", 111);
+__asmb_line_5:;
+	(void) __builtin_add_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+__asmb_line_6:;
+	printf("%d\n", __asmb_reg_a);
+__asmb_line_7:;
+	__asmb_reg_off = __asmb_reg_a;
+__asmb_line_8:;
+	(void) __builtin_sub_overflow(__asmb_reg_off, 3, &__asmb_reg_off);
+__asmb_line_9:;
+	if (__asmb_reg_off != 0) goto __asmb_line_5;
+__asmb_line_10:;
+	__asmb_reg_off = -11;
+__asmb_line_11:;
+	if (__asmb_reg_a != 0) { __asmb_target = 11 + __asmb_reg_off; if (__asmb_target < 1) { fflush(stdout); fprintf(stderr, "Line 15: Jump target %lld is before the start of the program\n", (long long) (__asmb_target - 1)); return 1; } goto __asmb_jump_table; }
+	goto __asmb_line_12;
+__asmb_jump_table:
+	switch (__asmb_target) {
+	case 1: goto __asmb_line_1;
+	case 2: goto __asmb_line_2;
+	case 3: goto __asmb_line_3;
+	case 4: goto __asmb_line_4;
+	case 5: goto __asmb_line_5;
+	case 6: goto __asmb_line_6;
+	case 7: goto __asmb_line_7;
+	case 8: goto __asmb_line_8;
+	case 9: goto __asmb_line_9;
+	case 10: goto __asmb_line_10;
+	case 11: goto __asmb_line_11;
+	default: goto __asmb_line_12;
+	}
+__asmb_line_12:;
+return 0;
+}
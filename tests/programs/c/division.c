@@ -0,0 +1,95 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+	static __asmb_int __asmb_stack[65536];
+	int64_t __asmb_stack_size = 0;
+__asmb_line_1:;
+	__asmb_int __asmb_reg_a = 0;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_b = 0;
+__asmb_line_3:;
+	__asmb_int __asmb_reg_q = 0;
+__asmb_line_4:;
+	__asmb_int __asmb_reg_r = 0;
+__asmb_line_5:;
+	__asmb_int __asmb_reg_i = 0;
+__asmb_line_6:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 8: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = 7;
+__asmb_line_7:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 9: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = 2;
+__asmb_line_8:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 10: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = -7;
+__asmb_line_9:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 11: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = 2;
+__asmb_line_10:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 12: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = 7;
+__asmb_line_11:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 13: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = -2;
+__asmb_line_12:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 14: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = -7;
+__asmb_line_13:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 15: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = -2;
+__asmb_line_14:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 16: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = -8;
+__asmb_line_15:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 17: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = 2;
+__asmb_line_16:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 18: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = 2147483647;
+__asmb_line_17:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 19: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = -3;
+__asmb_line_18:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 20: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_reg_b = __asmb_stack[--__asmb_stack_size];
+__asmb_line_19:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 21: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_reg_a = __asmb_stack[--__asmb_stack_size];
+__asmb_line_20:;
+	__asmb_reg_q = __asmb_reg_a;
+__asmb_line_21:;
+	{ __asmb_int __asmb_a = __asmb_reg_q, __asmb_b = __asmb_reg_b; if (__asmb_b == 0) { fflush(stdout); fputs("Line 23: Division by zero\n", stderr); return 1; } if (__asmb_b == -1 && __asmb_a == INT32_MIN) __asmb_reg_q = __asmb_a; else __asmb_reg_q = __asmb_a / __asmb_b; }
+__asmb_line_22:;
+	__asmb_reg_r = __asmb_reg_a;
+__asmb_line_23:;
+	{ __asmb_int __asmb_divisor = __asmb_reg_b; if (__asmb_divisor == 0) { fflush(stdout); fputs("Line 25: Modulo by zero\n", stderr); return 1; } __asmb_reg_r = __asmb_divisor == -1 ? 0 : __asmb_reg_r % __asmb_divisor; }
+__asmb_line_24:;
+	printf("%d ", __asmb_reg_a);
+__asmb_line_25:;
+	printf("%d ", __asmb_reg_b);
+__asmb_line_26:;
+	printf("%d ", __asmb_reg_q);
+__asmb_line_27:;
+	printf("%d\n", __asmb_reg_r);
+__asmb_line_28:;
+	(void) __builtin_add_overflow(__asmb_reg_i, 1, &__asmb_reg_i);
+__asmb_line_29:;
+	if (__asmb_reg_i < 6) goto __asmb_line_18;
+__asmb_line_30:;
+	int8_t __asmb_reg_small = -128;
+__asmb_line_31:;
+	{ int64_t __asmb_a = __asmb_reg_small, __asmb_b = -1; if (__asmb_b == 0) { fflush(stdout); fputs("Line 33: Division by zero\n", stderr); return 1; } __asmb_reg_small = (int8_t) (__asmb_b == -1 ? 0 - (uint64_t) __asmb_a : (uint64_t) (__asmb_a / __asmb_b)); }
+__asmb_line_32:;
+	printf("%lld\n", (long long) __asmb_reg_small);
+__asmb_line_33:;
+	int8_t __asmb_reg_byte = -7;
+__asmb_line_34:;
+	{ int64_t __asmb_a = __asmb_reg_byte, __asmb_b = 2; if (__asmb_b == 0) { fflush(stdout); fputs("Line 36: Division by zero\n", stderr); return 1; } __asmb_reg_byte = (int8_t) (__asmb_b == -1 ? 0 - (uint64_t) __asmb_a : (uint64_t) (__asmb_a / __asmb_b)); }
+__asmb_line_35:;
+	printf("%lld ", (long long) __asmb_reg_byte);
+__asmb_line_36:;
+	int8_t __asmb_reg_rem = -7;
+__asmb_line_37:;
+	{ int64_t __asmb_divisor = 2; if (__asmb_divisor == 0) { fflush(stdout); fputs("Line 39: Modulo by zero\n", stderr); return 1; } __asmb_reg_rem = __asmb_divisor == -1 ? 0 : __asmb_reg_rem % __asmb_divisor; }
+__asmb_line_38:;
+	printf("%lld\n", (long long) __asmb_reg_rem);
+__asmb_line_39:;
+	uint8_t __asmb_reg_u = 7;
+__asmb_line_40:;
+	{ uint64_t __asmb_a = __asmb_reg_u, __asmb_b = 2; if (__asmb_b == 0) { fflush(stdout); fputs("Line 42: Division by zero\n", stderr); return 1; } __asmb_reg_u = (uint8_t) (__asmb_a / __asmb_b); }
+__asmb_line_41:;
+	printf("%lld\n", (long long) __asmb_reg_u);
+__asmb_line_42:;
+return 0;
+}
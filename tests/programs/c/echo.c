@@ -0,0 +1,70 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+static inline int __asmb_read(__asmb_int *out) {
+	int ch, negative = 0, digits = 0;
+	__asmb_int val = 0;
+	fflush(stdout);
+	do ch = getchar(); while (ch == ' ' || (ch >= '\t' && ch <= '\r'));
+	if (ch == '-' || ch == '+') {
+		negative = ch == '-';
+		ch = getchar();
+	}
+	for (; ch >= '0' && ch <= '9'; ch = getchar(), ++digits) {
+		if (__builtin_mul_overflow(val, 10, &val) || (negative ? __builtin_sub_overflow(val, ch - '0', &val) : __builtin_add_overflow(val, ch - '0', &val))) return 0;
+	}
+	if (ch != EOF) ungetc(ch, stdin);
+	*out = val;
+	return digits > 0;
+}
+
+static inline void __asmb_outc(uint32_t code) {
+	if (code < 0x80) {
+		putchar((int) code);
+	} else if (code < 0x800) {
+		putchar((int) (0xC0 | code >> 6));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else if (code < 0x10000) {
+		putchar((int) (0xE0 | code >> 12));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else {
+		putchar((int) (0xF0 | code >> 18));
+		putchar((int) (0x80 | (code >> 12 & 0x3F)));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	}
+}
+
+int main(void) {
+__asmb_line_1:;
+	__asmb_int __asmb_reg_n = 0;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_ch = 0;
+__asmb_line_3:;
+	if (!__asmb_read(&__asmb_reg_n)) { fflush(stdout); fputs("Line 5: Expected an integer from STDIN\n", stderr); return 1; }
+__asmb_line_4:;
+	(void) __builtin_mul_overflow(__asmb_reg_n, 2, &__asmb_reg_n);
+__asmb_line_5:;
+	printf("%d\n", __asmb_reg_n);
+__asmb_line_6:;
+	__asmb_reg_ch = getchar();
+__asmb_line_7:;
+	__asmb_reg_n = __asmb_reg_ch;
+__asmb_line_8:;
+	(void) __builtin_add_overflow(__asmb_reg_n, 1, &__asmb_reg_n);
+__asmb_line_9:;
+	if (__asmb_reg_n != 0) goto __asmb_line_11;
+__asmb_line_10:;
+	if (1 != 0) goto __asmb_line_13;
+__asmb_line_11:;
+	{ __asmb_int __asmb_c = __asmb_reg_ch; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 13: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 13: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_12:;
+	if (1 != 0) goto __asmb_line_6;
+__asmb_line_13:;
+return 0;
+}
@@ -0,0 +1,52 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+static inline void __asmb_outc(uint32_t code) {
+	if (code < 0x80) {
+		putchar((int) code);
+	} else if (code < 0x800) {
+		putchar((int) (0xC0 | code >> 6));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else if (code < 0x10000) {
+		putchar((int) (0xE0 | code >> 12));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else {
+		putchar((int) (0xF0 | code >> 18));
+		putchar((int) (0x80 | (code >> 12 & 0x3F)));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	}
+}
+
+int main(void) {
+	static __asmb_int __asmb_mem_grid[25];
+__asmb_line_1:;
+	__asmb_int __asmb_reg_size = 105;
+__asmb_line_2:;
+	printf("%d\n", __asmb_reg_size);
+__asmb_line_3:;
+	memset(__asmb_mem_grid, 0, sizeof __asmb_mem_grid);
+__asmb_line_4:;
+	{ __asmb_int __asmb_index = 24; if (__asmb_index < 0 || __asmb_index >= (__asmb_int) (sizeof __asmb_mem_grid / sizeof __asmb_mem_grid[0])) { fflush(stdout); fprintf(stderr, "Line 8: Index %d is out of bounds for a memory buffer of size %d\n", __asmb_index, (__asmb_int) (sizeof __asmb_mem_grid / sizeof __asmb_mem_grid[0])); return 1; } __asmb_mem_grid[__asmb_index] = 66; }
+__asmb_line_5:;
+	__asmb_int __asmb_reg_x = 0;
+__asmb_line_6:;
+	{ __asmb_int __asmb_index = 24; if (__asmb_index < 0 || __asmb_index >= (__asmb_int) (sizeof __asmb_mem_grid / sizeof __asmb_mem_grid[0])) { fflush(stdout); fprintf(stderr, "Line 10: Index %d is out of bounds for a memory buffer of size %d\n", __asmb_index, (__asmb_int) (sizeof __asmb_mem_grid / sizeof __asmb_mem_grid[0])); return 1; } __asmb_reg_x = __asmb_mem_grid[__asmb_index]; }
+__asmb_line_7:;
+	{ __asmb_int __asmb_c = __asmb_reg_x; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 11: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 11: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_8:;
+	{ __asmb_int __asmb_c = 10; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 12: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 12: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_9:;
+	printf("%d\n", 4);
+__asmb_line_10:;
+	printf("%d\n", -1);
+__asmb_line_11:;
+	printf("%d\n", 10);
+__asmb_line_12:;
+return 0;
+}
@@ -0,0 +1,53 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+__asmb_line_1:;
+	__asmb_int __asmb_reg_n = 1;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_f = 1;
+__asmb_line_3:;
+	(void) __builtin_mul_overflow(__asmb_reg_f, __asmb_reg_n, &__asmb_reg_f);
+__asmb_line_4:;
+	printf("%d ", __asmb_reg_n);
+__asmb_line_5:;
+	printf("%d\n", __asmb_reg_f);
+__asmb_line_6:;
+	(void) __builtin_add_overflow(__asmb_reg_n, 1, &__asmb_reg_n);
+__asmb_line_7:;
+	if (__asmb_reg_n < 31) goto __asmb_line_3;
+__asmb_line_8:;
+	__asmb_int __asmb_reg_zeros = 0;
+__asmb_line_9:;
+	__asmb_int __asmb_reg_digit = 0;
+__asmb_line_10:;
+	__asmb_reg_digit = __asmb_reg_f;
+__asmb_line_11:;
+	{ __asmb_int __asmb_divisor = 10; if (__asmb_divisor == 0) { fflush(stdout); fputs("Line 13: Modulo by zero\n", stderr); return 1; } __asmb_reg_digit = __asmb_divisor == -1 ? 0 : __asmb_reg_digit % __asmb_divisor; }
+__asmb_line_12:;
+	if (__asmb_reg_digit != 0) goto __asmb_line_16;
+__asmb_line_13:;
+	{ __asmb_int __asmb_a = __asmb_reg_f, __asmb_b = 10; if (__asmb_b == 0) { fflush(stdout); fputs("Line 15: Division by zero\n", stderr); return 1; } if (__asmb_b == -1 && __asmb_a == INT32_MIN) __asmb_reg_f = __asmb_a; else __asmb_reg_f = __asmb_a / __asmb_b; }
+__asmb_line_14:;
+	(void) __builtin_add_overflow(__asmb_reg_zeros, 1, &__asmb_reg_zeros);
+__asmb_line_15:;
+	if (1 != 0) goto __asmb_line_10;
+__asmb_line_16:;
+	printf("%d\n", __asmb_reg_zeros);
+__asmb_line_17:;
+	__asmb_int __asmb_reg_p = -1;
+__asmb_line_18:;
+	__asmb_reg_p = (__asmb_int) ((__asmb_uint) __asmb_reg_p << (100 & 31));
+__asmb_line_19:;
+	printf("%d\n", __asmb_reg_p);
+__asmb_line_20:;
+	{ __asmb_int __asmb_amount = 98 & 31; __asmb_reg_p = __asmb_reg_p < 0 ? ~(~__asmb_reg_p >> __asmb_amount) : __asmb_reg_p >> __asmb_amount; }
+__asmb_line_21:;
+	printf("%d\n", __asmb_reg_p);
+__asmb_line_22:;
+return 0;
+}
@@ -1,24 +1,29 @@
 #include <stdio.h>
 #include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
 
 int main(void) {
 __asmb_line_1:;
-	int32_t __asmb_reg_i = 20;
+	__asmb_int __asmb_reg_i = 20;
 __asmb_line_2:;
-	int32_t __asmb_reg_a = 1;
+	__asmb_int __asmb_reg_a = 1;
 __asmb_line_3:;
-	int32_t __asmb_reg_b = 1;
+	__asmb_int __asmb_reg_b = 1;
 __asmb_line_4:;
 	printf("%d ", __asmb_reg_a);
 __asmb_line_5:;
 	printf("%d ", __asmb_reg_b);
 __asmb_line_6:;
-	__asmb_reg_a += __asmb_reg_b;
+	(void) __builtin_add_overflow(__asmb_reg_a, __asmb_reg_b, &__asmb_reg_a);
 __asmb_line_7:;
-	__asmb_reg_b += __asmb_reg_a;
+	(void) __builtin_add_overflow(__asmb_reg_b, __asmb_reg_a, &__asmb_reg_b);
 __asmb_line_8:;
-	--__asmb_reg_i;
+	(void) __builtin_sub_overflow(__asmb_reg_i, 1, &__asmb_reg_i);
 __asmb_line_9:;
 	if (__asmb_reg_i != 0) goto __asmb_line_4;
+__asmb_line_10:;
 return 0;
 }
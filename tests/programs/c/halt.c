@@ -0,0 +1,29 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+__asmb_line_1:;
+	__asmb_int __asmb_reg_i = 5;
+__asmb_line_2:;
+	printf("%d\n", __asmb_reg_i);
+__asmb_line_3:;
+	if (__asmb_reg_i == 2) goto __asmb_line_6;
+__asmb_line_4:;
+	(void) __builtin_sub_overflow(__asmb_reg_i, 1, &__asmb_reg_i);
+__asmb_line_5:;
+	if (1 != 0) goto __asmb_line_2;
+__asmb_line_6:;
+	__asmb_reg_i = 3;
+__asmb_line_7:;
+	return (int) __asmb_reg_i;
+__asmb_line_8:;
+	fwrite("unreachable\n", 1, 12, stdout);
+__asmb_line_9:;
+	return (int) 0;
+__asmb_line_10:;
+return 0;
+}
@@ -1,56 +1,79 @@
 #include <stdio.h>
 #include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+static inline void __asmb_outc(uint32_t code) {
+	if (code < 0x80) {
+		putchar((int) code);
+	} else if (code < 0x800) {
+		putchar((int) (0xC0 | code >> 6));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else if (code < 0x10000) {
+		putchar((int) (0xE0 | code >> 12));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else {
+		putchar((int) (0xF0 | code >> 18));
+		putchar((int) (0x80 | (code >> 12 & 0x3F)));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	}
+}
 
 int main(void) {
 __asmb_line_1:;
-	int32_t __asmb_reg_diff = 32;
+	__asmb_int __asmb_reg_diff = 32;
 __asmb_line_2:;
-	int32_t __asmb_reg_seq0 = 72;
+	__asmb_int __asmb_reg_seq0 = 72;
 __asmb_line_3:;
-	int32_t __asmb_reg_seq1 = 69;
+	__asmb_int __asmb_reg_seq1 = 69;
 __asmb_line_4:;
-	int32_t __asmb_reg_seq23 = 76;
+	__asmb_int __asmb_reg_seq23 = 76;
 __asmb_line_5:;
-	int32_t __asmb_reg_seq4 = 79;
+	__asmb_int __asmb_reg_seq4 = 79;
 __asmb_line_6:;
-	int32_t __asmb_reg_seq5 = 87;
+	__asmb_int __asmb_reg_seq5 = 87;
 __asmb_line_7:;
-	int32_t __asmb_reg_seq6 = __asmb_reg_seq4;
+	__asmb_int __asmb_reg_seq6 = __asmb_reg_seq4;
 __asmb_line_8:;
-	int32_t __asmb_reg_seq7 = 82;
+	__asmb_int __asmb_reg_seq7 = 82;
 __asmb_line_9:;
-	int32_t __asmb_reg_seq8 = 76;
+	__asmb_int __asmb_reg_seq8 = 76;
 __asmb_line_10:;
-	int32_t __asmb_reg_seq9 = 68;
+	__asmb_int __asmb_reg_seq9 = 68;
 __asmb_line_11:;
-	printf("%c", __asmb_reg_seq0);
+	{ __asmb_int __asmb_c = __asmb_reg_seq0; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 13: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 13: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
 __asmb_line_12:;
-	__asmb_reg_seq1 += __asmb_reg_diff;
+	(void) __builtin_add_overflow(__asmb_reg_seq1, __asmb_reg_diff, &__asmb_reg_seq1);
 __asmb_line_13:;
-	printf("%c", __asmb_reg_seq1);
+	{ __asmb_int __asmb_c = __asmb_reg_seq1; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 15: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 15: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
 __asmb_line_14:;
-	printf("%c", __asmb_reg_seq23);
+	{ __asmb_int __asmb_c = __asmb_reg_seq23; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 16: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 16: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
 __asmb_line_15:;
-	printf("%c", __asmb_reg_seq23);
+	{ __asmb_int __asmb_c = __asmb_reg_seq23; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 17: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 17: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
 __asmb_line_16:;
-	__asmb_reg_seq4 += __asmb_reg_diff;
+	(void) __builtin_add_overflow(__asmb_reg_seq4, __asmb_reg_diff, &__asmb_reg_seq4);
 __asmb_line_17:;
-	printf("%c", __asmb_reg_seq4);
+	{ __asmb_int __asmb_c = __asmb_reg_seq4; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 19: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 19: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
 __asmb_line_18:;
-	printf("%c", 32);
+	{ __asmb_int __asmb_c = 32; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 20: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 20: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
 __asmb_line_19:;
-	printf("%c", __asmb_reg_seq5);
+	{ __asmb_int __asmb_c = __asmb_reg_seq5; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 21: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 21: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
 __asmb_line_20:;
-	__asmb_reg_seq6 += __asmb_reg_diff;
+	(void) __builtin_add_overflow(__asmb_reg_seq6, __asmb_reg_diff, &__asmb_reg_seq6);
 __asmb_line_21:;
-	printf("%c", __asmb_reg_seq6);
+	{ __asmb_int __asmb_c = __asmb_reg_seq6; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 23: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 23: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
 __asmb_line_22:;
-	printf("%c", __asmb_reg_seq7);
+	{ __asmb_int __asmb_c = __asmb_reg_seq7; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 24: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 24: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
 __asmb_line_23:;
-	__asmb_reg_seq8 += __asmb_reg_diff;
+	(void) __builtin_add_overflow(__asmb_reg_seq8, __asmb_reg_diff, &__asmb_reg_seq8);
 __asmb_line_24:;
-	printf("%c", __asmb_reg_seq8);
+	{ __asmb_int __asmb_c = __asmb_reg_seq8; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 26: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 26: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
 __asmb_line_25:;
-	printf("%c", __asmb_reg_seq9);
+	{ __asmb_int __asmb_c = __asmb_reg_seq9; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 27: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 27: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_26:;
 return 0;
 }
@@ -0,0 +1,61 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+static inline void __asmb_outc(uint32_t code) {
+	if (code < 0x80) {
+		putchar((int) code);
+	} else if (code < 0x800) {
+		putchar((int) (0xC0 | code >> 6));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else if (code < 0x10000) {
+		putchar((int) (0xE0 | code >> 12));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else {
+		putchar((int) (0xF0 | code >> 18));
+		putchar((int) (0x80 | (code >> 12 & 0x3F)));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	}
+}
+
+int main(void) {
+__asmb_line_1:;
+	{ __asmb_int __asmb_c = 72; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 2: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 2: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_2:;
+	{ __asmb_int __asmb_c = 105; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 3: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 3: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_3:;
+	{ __asmb_int __asmb_c = 33; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 4: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 4: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_4:;
+	{ __asmb_int __asmb_c = 10; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 5: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 5: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_5:;
+	__asmb_int __asmb_reg_mask = 255;
+__asmb_line_6:;
+	__asmb_reg_mask &= 10;
+__asmb_line_7:;
+	printf("%d\n", __asmb_reg_mask);
+__asmb_line_8:;
+	__asmb_int __asmb_reg_perm = 493;
+__asmb_line_9:;
+	printf("%d\n", __asmb_reg_perm);
+__asmb_line_10:;
+	__asmb_int __asmb_reg_neg = -16;
+__asmb_line_11:;
+	printf("%d\n", __asmb_reg_neg);
+__asmb_line_12:;
+	if (1 != 0) goto __asmb_line_14;
+__asmb_line_13:;
+	printf("%d\n", 0);
+__asmb_line_14:;
+	{ __asmb_int __asmb_c = 32; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 15: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 15: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_15:;
+	{ __asmb_int __asmb_c = 39; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 16: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 16: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_16:;
+	{ __asmb_int __asmb_c = 10; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 17: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 17: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_17:;
+return 0;
+}
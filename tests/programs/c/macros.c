@@ -0,0 +1,63 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+__asmb_line_1:;
+	__asmb_int __asmb_reg_x = 1;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_y = 2;
+__asmb_line_3:;
+	__asmb_int __asmb_reg__m1_tmp = 0;
+__asmb_line_4:;
+	__asmb_reg__m1_tmp = __asmb_reg_x;
+__asmb_line_5:;
+	__asmb_reg_x = __asmb_reg_y;
+__asmb_line_6:;
+	__asmb_reg_y = __asmb_reg__m1_tmp;
+__asmb_line_7:;
+	printf("%d ", __asmb_reg_x);
+__asmb_line_8:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_9:;
+	__asmb_int __asmb_reg__m2_left = 0;
+__asmb_line_10:;
+	__asmb_reg__m2_left = 3;
+__asmb_line_11:;
+	printf("%d ", __asmb_reg__m2_left);
+__asmb_line_12:;
+	(void) __builtin_sub_overflow(__asmb_reg__m2_left, 1, &__asmb_reg__m2_left);
+__asmb_line_13:;
+	if (__asmb_reg__m2_left != 0) goto __asmb_line_11;
+__asmb_line_14:;
+	fwrite("\n", 1, 1, stdout);
+__asmb_line_15:;
+	__asmb_int __asmb_reg__m3_left = 0;
+__asmb_line_16:;
+	__asmb_reg__m3_left = __asmb_reg_y;
+__asmb_line_17:;
+	printf("%d ", __asmb_reg__m3_left);
+__asmb_line_18:;
+	(void) __builtin_sub_overflow(__asmb_reg__m3_left, 1, &__asmb_reg__m3_left);
+__asmb_line_19:;
+	if (__asmb_reg__m3_left != 0) goto __asmb_line_17;
+__asmb_line_20:;
+	fwrite("\n", 1, 1, stdout);
+__asmb_line_21:;
+	__asmb_int __asmb_reg__m4_tmp = 0;
+__asmb_line_22:;
+	__asmb_reg__m4_tmp = __asmb_reg_x;
+__asmb_line_23:;
+	__asmb_reg_x = __asmb_reg_y;
+__asmb_line_24:;
+	__asmb_reg_y = __asmb_reg__m4_tmp;
+__asmb_line_25:;
+	printf("%d ", __asmb_reg_x);
+__asmb_line_26:;
+	printf("%d\n", __asmb_reg_y);
+__asmb_line_27:;
+return 0;
+}
@@ -0,0 +1,45 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+__asmb_line_1:;
+	__asmb_int __asmb_reg_x = 1;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_i = 0;
+__asmb_line_3:;
+	(void) __builtin_mul_overflow(__asmb_reg_x, 3, &__asmb_reg_x);
+__asmb_line_4:;
+	(void) __builtin_add_overflow(__asmb_reg_i, 1, &__asmb_reg_i);
+__asmb_line_5:;
+	if (__asmb_reg_i < 100) goto __asmb_line_3;
+__asmb_line_6:;
+	printf("%d\n", __asmb_reg_x);
+__asmb_line_7:;
+	__asmb_int __asmb_reg_m = 0;
+__asmb_line_8:;
+	(void) __builtin_sub_overflow(__asmb_reg_m, 2147483647, &__asmb_reg_m);
+__asmb_line_9:;
+	(void) __builtin_sub_overflow(__asmb_reg_m, 1, &__asmb_reg_m);
+__asmb_line_10:;
+	printf("%d\n", __asmb_reg_m);
+__asmb_line_11:;
+	(void) __builtin_sub_overflow(0, __asmb_reg_m, &__asmb_reg_m);
+__asmb_line_12:;
+	printf("%d\n", __asmb_reg_m);
+__asmb_line_13:;
+	__asmb_int __asmb_reg_s = -5;
+__asmb_line_14:;
+	__asmb_reg_s = (__asmb_int) ((__asmb_uint) __asmb_reg_s << (31 & 31));
+__asmb_line_15:;
+	printf("%d\n", __asmb_reg_s);
+__asmb_line_16:;
+	{ __asmb_int __asmb_a = __asmb_reg_m, __asmb_b = -1; if (__asmb_b == 0) { fflush(stdout); fputs("Line 17: Division by zero\n", stderr); return 1; } if (__asmb_b == -1 && __asmb_a == INT32_MIN) __asmb_reg_m = __asmb_a; else __asmb_reg_m = __asmb_a / __asmb_b; }
+__asmb_line_17:;
+	printf("%d\n", __asmb_reg_m);
+__asmb_line_18:;
+return 0;
+}
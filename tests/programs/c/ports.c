@@ -0,0 +1,111 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+static const unsigned char __asmb_port_kinds[256] = { [0] = 1, [1] = 2, [2] = 3 };
+static const char *const __asmb_port_paths[256] = { 0 };
+static FILE *__asmb_port_files[256];
+static int __asmb_port_writing[256];
+
+static FILE *__asmb_port_stream(const char *origin, int port, int writing) {
+	FILE *stream = NULL;
+	switch (__asmb_port_kinds[port]) {
+	case 0:
+		fflush(stdout);
+		fprintf(stderr, "%s: Port %d is not bound to anything (see --port)\n", origin, port);
+		return NULL;
+	case 1:
+		stream = writing ? NULL : stdin;
+		break;
+	case 2:
+		stream = writing ? stdout : NULL;
+		break;
+	case 3:
+		stream = writing ? stderr : NULL;
+		break;
+	case 4:
+		if (!__asmb_port_files[port]) {
+			__asmb_port_files[port] = fopen(__asmb_port_paths[port], writing ? "wb" : "rb");
+			if (!__asmb_port_files[port]) {
+				fflush(stdout);
+				fprintf(stderr, "%s: Cannot open '%s' for %s (port %d)\n", origin, __asmb_port_paths[port], writing ? "writing" : "reading", port);
+				return NULL;
+			}
+			__asmb_port_writing[port] = writing;
+		}
+		if (__asmb_port_writing[port] == writing) stream = __asmb_port_files[port];
+		break;
+	default:
+		if (!__asmb_port_files[port] && !(__asmb_port_files[port] = tmpfile())) {
+			fflush(stdout);
+			fprintf(stderr, "%s: Failed to %s port %d\n", origin, writing ? "write to" : "read from", port);
+			return NULL;
+		}
+		return __asmb_port_files[port];
+	}
+	if (!stream) {
+		fflush(stdout);
+		fprintf(stderr, "%s: Port %d cannot be %s\n", origin, port, writing ? "written to" : "read from");
+	}
+	return stream;
+}
+
+static int __asmb_outp(const char *origin, int port, int byte) {
+	FILE *stream = __asmb_port_stream(origin, port, 1);
+	if (!stream) return 0;
+	if (__asmb_port_kinds[port] == 5) fseek(stream, 0, SEEK_END);
+	if (fputc(byte, stream) == EOF) {
+		fflush(stdout);
+		fprintf(stderr, "%s: Failed to write to port %d\n", origin, port);
+		return 0;
+	}
+	return 1;
+}
+
+static long __asmb_port_read_pos[256];
+
+static int __asmb_inp(const char *origin, int port, int *byte) {
+	FILE *stream = __asmb_port_stream(origin, port, 0);
+	if (!stream) return 0;
+	if (__asmb_port_kinds[port] == 5) fseek(stream, __asmb_port_read_pos[port], SEEK_SET);
+	*byte = fgetc(stream);
+	if (*byte == EOF && ferror(stream)) {
+		fflush(stdout);
+		fprintf(stderr, "%s: Failed to read from port %d\n", origin, port);
+		return 0;
+	}
+	if (*byte != EOF && __asmb_port_kinds[port] == 5) ++__asmb_port_read_pos[port];
+	return 1;
+}
+
+int main(void) {
+__asmb_line_1:;
+	__asmb_int __asmb_reg_byte = 0;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_count = 0;
+__asmb_line_3:;
+	{ __asmb_int __asmb_port = 0; if (__asmb_port < 0 || __asmb_port > 255) { fflush(stdout); fprintf(stderr, "Line 4: Port %d does not exist (ports are numbered from 0 to 255)\n", __asmb_port); return 1; } int __asmb_input; if (!__asmb_inp("Line 4", (int) __asmb_port, &__asmb_input)) return 1; __asmb_reg_byte = __asmb_input; }
+__asmb_line_4:;
+	if (__asmb_reg_byte < 0) goto __asmb_line_9;
+__asmb_line_5:;
+	if (__asmb_reg_byte == 32) goto __asmb_line_9;
+__asmb_line_6:;
+	{ __asmb_int __asmb_port = 1; if (__asmb_port < 0 || __asmb_port > 255) { fflush(stdout); fprintf(stderr, "Line 7: Port %d does not exist (ports are numbered from 0 to 255)\n", __asmb_port); return 1; } __asmb_int __asmb_byte = __asmb_reg_byte; if (__asmb_byte < 0 || __asmb_byte > 255) { fflush(stdout); fprintf(stderr, "Line 7: Value %d does not fit in a byte\n", __asmb_byte); return 1; } if (!__asmb_outp("Line 7", (int) __asmb_port, (int) __asmb_byte)) return 1; }
+__asmb_line_7:;
+	(void) __builtin_add_overflow(__asmb_reg_count, 1, &__asmb_reg_count);
+__asmb_line_8:;
+	if (1 != 0) goto __asmb_line_3;
+__asmb_line_9:;
+	{ __asmb_int __asmb_port = 1; if (__asmb_port < 0 || __asmb_port > 255) { fflush(stdout); fprintf(stderr, "Line 10: Port %d does not exist (ports are numbered from 0 to 255)\n", __asmb_port); return 1; } __asmb_int __asmb_byte = 10; if (__asmb_byte < 0 || __asmb_byte > 255) { fflush(stdout); fprintf(stderr, "Line 10: Value %d does not fit in a byte\n", __asmb_byte); return 1; } if (!__asmb_outp("Line 10", (int) __asmb_port, (int) __asmb_byte)) return 1; }
+__asmb_line_10:;
+	{ __asmb_int __asmb_port = 2; if (__asmb_port < 0 || __asmb_port > 255) { fflush(stdout); fprintf(stderr, "Line 11: Port %d does not exist (ports are numbered from 0 to 255)\n", __asmb_port); return 1; } __asmb_int __asmb_byte = 35; if (__asmb_byte < 0 || __asmb_byte > 255) { fflush(stdout); fprintf(stderr, "Line 11: Value %d does not fit in a byte\n", __asmb_byte); return 1; } if (!__asmb_outp("Line 11", (int) __asmb_port, (int) __asmb_byte)) return 1; }
+__asmb_line_11:;
+	{ __asmb_int __asmb_port = 2; if (__asmb_port < 0 || __asmb_port > 255) { fflush(stdout); fprintf(stderr, "Line 12: Port %d does not exist (ports are numbered from 0 to 255)\n", __asmb_port); return 1; } __asmb_int __asmb_byte = 10; if (__asmb_byte < 0 || __asmb_byte > 255) { fflush(stdout); fprintf(stderr, "Line 12: Value %d does not fit in a byte\n", __asmb_byte); return 1; } if (!__asmb_outp("Line 12", (int) __asmb_port, (int) __asmb_byte)) return 1; }
+__asmb_line_12:;
+	printf("%d\n", __asmb_reg_count);
+__asmb_line_13:;
+return 0;
+}
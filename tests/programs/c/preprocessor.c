@@ -0,0 +1,58 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+	int64_t __asmb_calls[4096];
+	int32_t __asmb_call_depth = 0;
+	int64_t __asmb_target = 0;
+__asmb_line_1:;
+	__asmb_int __asmb_reg_x = 0;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_i = 1;
+__asmb_line_3:;
+	__asmb_int __asmb_reg_sum = 0;
+__asmb_line_4:;
+	if (1 != 0) goto __asmb_line_7;
+__asmb_line_5:;
+	(void) __builtin_mul_overflow(__asmb_reg_x, __asmb_reg_x, &__asmb_reg_x);
+__asmb_line_6:;
+	{ if (__asmb_call_depth == 0) { fflush(stdout); fputs("Line 4 of src/include/square.asmb: Call stack underflow (RET without CALL)\n", stderr); return 1; } __asmb_target = __asmb_calls[--__asmb_call_depth]; goto __asmb_jump_table; }
+__asmb_line_7:;
+	__asmb_reg_x = __asmb_reg_i;
+__asmb_line_8:;
+	{ if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 14: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 9; goto __asmb_line_5; }
+__asmb_line_9:;
+	(void) __builtin_add_overflow(__asmb_reg_sum, __asmb_reg_x, &__asmb_reg_sum);
+__asmb_line_10:;
+	(void) __builtin_add_overflow(__asmb_reg_i, 1, &__asmb_reg_i);
+__asmb_line_11:;
+	if (__asmb_reg_i > 5) goto __asmb_line_13;
+__asmb_line_12:;
+	if (1 != 0) goto __asmb_line_7;
+__asmb_line_13:;
+	printf("%d\n", __asmb_reg_sum);
+	goto __asmb_line_14;
+__asmb_jump_table:
+	switch (__asmb_target) {
+	case 1: goto __asmb_line_1;
+	case 2: goto __asmb_line_2;
+	case 3: goto __asmb_line_3;
+	case 4: goto __asmb_line_4;
+	case 5: goto __asmb_line_5;
+	case 6: goto __asmb_line_6;
+	case 7: goto __asmb_line_7;
+	case 8: goto __asmb_line_8;
+	case 9: goto __asmb_line_9;
+	case 10: goto __asmb_line_10;
+	case 11: goto __asmb_line_11;
+	case 12: goto __asmb_line_12;
+	case 13: goto __asmb_line_13;
+	default: goto __asmb_line_14;
+	}
+__asmb_line_14:;
+return 0;
+}
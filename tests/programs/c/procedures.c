@@ -0,0 +1,156 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+static inline void __asmb_outc(uint32_t code) {
+	if (code < 0x80) {
+		putchar((int) code);
+	} else if (code < 0x800) {
+		putchar((int) (0xC0 | code >> 6));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else if (code < 0x10000) {
+		putchar((int) (0xE0 | code >> 12));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else {
+		putchar((int) (0xF0 | code >> 18));
+		putchar((int) (0x80 | (code >> 12 & 0x3F)));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	}
+}
+
+int main(void) {
+	int64_t __asmb_calls[4096];
+	int32_t __asmb_call_depth = 0;
+	static __asmb_int __asmb_stack[65536];
+	int64_t __asmb_stack_size = 0;
+	__asmb_int __asmb_args[8];
+	int __asmb_arg_count = 0;
+	int64_t __asmb_callee = 0;
+	__asmb_int __asmb_local_3_gcd_a = 0;
+	static __asmb_int __asmb_frames_0_0[4096];
+	__asmb_int __asmb_local_3_gcd_b = 0;
+	static __asmb_int __asmb_frames_0_1[4096];
+	__asmb_int __asmb_local_3_gcd_r = 0;
+	static __asmb_int __asmb_frames_0_2[4096];
+	int32_t __asmb_frame_depth_0 = 0;
+	__asmb_int __asmb_local_3_fib_n = 0;
+	static __asmb_int __asmb_frames_1_0[4096];
+	__asmb_int __asmb_local_3_fib_x = 0;
+	static __asmb_int __asmb_frames_1_1[4096];
+	int32_t __asmb_frame_depth_1 = 0;
+	int64_t __asmb_target = 0;
+__asmb_line_1:;
+	if (__asmb_callee == 1) { __asmb_callee = 0; if (__asmb_arg_count != 2) { fflush(stdout); fprintf(stderr, "Line 3: Procedure expects 2 argument(s), got %lld\n", (long long) __asmb_arg_count); return 1; } if (__asmb_frame_depth_0 == 4096) { fflush(stdout); fputs("Line 3: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_frames_0_0[__asmb_frame_depth_0] = __asmb_local_3_gcd_a; __asmb_frames_0_1[__asmb_frame_depth_0] = __asmb_local_3_gcd_b; __asmb_frames_0_2[__asmb_frame_depth_0] = __asmb_local_3_gcd_r; ++__asmb_frame_depth_0; __asmb_local_3_gcd_a = __asmb_args[0]; __asmb_local_3_gcd_b = __asmb_args[1]; __asmb_local_3_gcd_r = 0; } else goto __asmb_line_10;
+__asmb_line_2:;
+	if (__asmb_local_3_gcd_b == 0) goto __asmb_line_8;
+__asmb_line_3:;
+	__asmb_local_3_gcd_r = __asmb_local_3_gcd_a;
+__asmb_line_4:;
+	{ __asmb_int __asmb_divisor = __asmb_local_3_gcd_b; if (__asmb_divisor == 0) { fflush(stdout); fputs("Line 6: Modulo by zero\n", stderr); return 1; } __asmb_local_3_gcd_r = __asmb_divisor == -1 ? 0 : __asmb_local_3_gcd_r % __asmb_divisor; }
+__asmb_line_5:;
+	{ __asmb_args[0] = __asmb_local_3_gcd_b; __asmb_args[1] = __asmb_local_3_gcd_r; __asmb_arg_count = 2; __asmb_callee = 5 + -4; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 7: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 6; goto __asmb_line_1; }
+__asmb_line_6:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 8: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_local_3_gcd_r = __asmb_stack[--__asmb_stack_size];
+__asmb_line_7:;
+	{ if (__asmb_frame_depth_0 == 0) { fflush(stdout); fputs("Line 9: RETURN or ENDP without a call of a procedure\n", stderr); return 1; } if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 9: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = __asmb_local_3_gcd_r; --__asmb_frame_depth_0; __asmb_local_3_gcd_a = __asmb_frames_0_0[__asmb_frame_depth_0]; __asmb_local_3_gcd_b = __asmb_frames_0_1[__asmb_frame_depth_0]; __asmb_local_3_gcd_r = __asmb_frames_0_2[__asmb_frame_depth_0]; { if (__asmb_call_depth == 0) { fflush(stdout); fputs("Line 9: Call stack underflow (RET without CALL)\n", stderr); return 1; } __asmb_target = __asmb_calls[--__asmb_call_depth]; goto __asmb_jump_table; } }
+__asmb_line_8:;
+	{ if (__asmb_frame_depth_0 == 0) { fflush(stdout); fputs("Line 10: RETURN or ENDP without a call of a procedure\n", stderr); return 1; } if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 10: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = __asmb_local_3_gcd_a; --__asmb_frame_depth_0; __asmb_local_3_gcd_a = __asmb_frames_0_0[__asmb_frame_depth_0]; __asmb_local_3_gcd_b = __asmb_frames_0_1[__asmb_frame_depth_0]; __asmb_local_3_gcd_r = __asmb_frames_0_2[__asmb_frame_depth_0]; { if (__asmb_call_depth == 0) { fflush(stdout); fputs("Line 10: Call stack underflow (RET without CALL)\n", stderr); return 1; } __asmb_target = __asmb_calls[--__asmb_call_depth]; goto __asmb_jump_table; } }
+__asmb_line_9:;
+	{ if (__asmb_frame_depth_0 == 0) { fflush(stdout); fputs("Line 11: RETURN or ENDP without a call of a procedure\n", stderr); return 1; } --__asmb_frame_depth_0; __asmb_local_3_gcd_a = __asmb_frames_0_0[__asmb_frame_depth_0]; __asmb_local_3_gcd_b = __asmb_frames_0_1[__asmb_frame_depth_0]; __asmb_local_3_gcd_r = __asmb_frames_0_2[__asmb_frame_depth_0]; { if (__asmb_call_depth == 0) { fflush(stdout); fputs("Line 11: Call stack underflow (RET without CALL)\n", stderr); return 1; } __asmb_target = __asmb_calls[--__asmb_call_depth]; goto __asmb_jump_table; } }
+__asmb_line_10:;
+	if (__asmb_callee == 10) { __asmb_callee = 0; if (__asmb_arg_count != 1) { fflush(stdout); fprintf(stderr, "Line 13: Procedure expects 1 argument(s), got %lld\n", (long long) __asmb_arg_count); return 1; } if (__asmb_frame_depth_1 == 4096) { fflush(stdout); fputs("Line 13: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_frames_1_0[__asmb_frame_depth_1] = __asmb_local_3_fib_n; __asmb_frames_1_1[__asmb_frame_depth_1] = __asmb_local_3_fib_x; ++__asmb_frame_depth_1; __asmb_local_3_fib_n = __asmb_args[0]; __asmb_local_3_fib_x = 0; } else goto __asmb_line_24;
+__asmb_line_11:;
+	if (__asmb_local_3_fib_n > 1) goto __asmb_line_13;
+__asmb_line_12:;
+	{ if (__asmb_frame_depth_1 == 0) { fflush(stdout); fputs("Line 15: RETURN or ENDP without a call of a procedure\n", stderr); return 1; } if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 15: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = __asmb_local_3_fib_n; --__asmb_frame_depth_1; __asmb_local_3_fib_n = __asmb_frames_1_0[__asmb_frame_depth_1]; __asmb_local_3_fib_x = __asmb_frames_1_1[__asmb_frame_depth_1]; { if (__asmb_call_depth == 0) { fflush(stdout); fputs("Line 15: Call stack underflow (RET without CALL)\n", stderr); return 1; } __asmb_target = __asmb_calls[--__asmb_call_depth]; goto __asmb_jump_table; } }
+__asmb_line_13:;
+	__asmb_local_3_fib_x = __asmb_local_3_fib_n;
+__asmb_line_14:;
+	(void) __builtin_sub_overflow(__asmb_local_3_fib_x, 1, &__asmb_local_3_fib_x);
+__asmb_line_15:;
+	{ __asmb_args[0] = __asmb_local_3_fib_x; __asmb_arg_count = 1; __asmb_callee = 15 + -5; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 18: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 16; goto __asmb_line_10; }
+__asmb_line_16:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 19: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_local_3_fib_x = __asmb_stack[--__asmb_stack_size];
+__asmb_line_17:;
+	(void) __builtin_sub_overflow(__asmb_local_3_fib_n, 1, &__asmb_local_3_fib_n);
+__asmb_line_18:;
+	(void) __builtin_sub_overflow(__asmb_local_3_fib_n, 1, &__asmb_local_3_fib_n);
+__asmb_line_19:;
+	{ __asmb_args[0] = __asmb_local_3_fib_n; __asmb_arg_count = 1; __asmb_callee = 19 + -9; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 22: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 20; goto __asmb_line_10; }
+__asmb_line_20:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 23: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_local_3_fib_n = __asmb_stack[--__asmb_stack_size];
+__asmb_line_21:;
+	(void) __builtin_add_overflow(__asmb_local_3_fib_x, __asmb_local_3_fib_n, &__asmb_local_3_fib_x);
+__asmb_line_22:;
+	{ if (__asmb_frame_depth_1 == 0) { fflush(stdout); fputs("Line 25: RETURN or ENDP without a call of a procedure\n", stderr); return 1; } if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 25: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = __asmb_local_3_fib_x; --__asmb_frame_depth_1; __asmb_local_3_fib_n = __asmb_frames_1_0[__asmb_frame_depth_1]; __asmb_local_3_fib_x = __asmb_frames_1_1[__asmb_frame_depth_1]; { if (__asmb_call_depth == 0) { fflush(stdout); fputs("Line 25: Call stack underflow (RET without CALL)\n", stderr); return 1; } __asmb_target = __asmb_calls[--__asmb_call_depth]; goto __asmb_jump_table; } }
+__asmb_line_23:;
+	{ if (__asmb_frame_depth_1 == 0) { fflush(stdout); fputs("Line 26: RETURN or ENDP without a call of a procedure\n", stderr); return 1; } --__asmb_frame_depth_1; __asmb_local_3_fib_n = __asmb_frames_1_0[__asmb_frame_depth_1]; __asmb_local_3_fib_x = __asmb_frames_1_1[__asmb_frame_depth_1]; { if (__asmb_call_depth == 0) { fflush(stdout); fputs("Line 26: Call stack underflow (RET without CALL)\n", stderr); return 1; } __asmb_target = __asmb_calls[--__asmb_call_depth]; goto __asmb_jump_table; } }
+__asmb_line_24:;
+	{ __asmb_args[0] = 1071; __asmb_args[1] = 462; __asmb_arg_count = 2; __asmb_callee = 24 + -23; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 28: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 25; goto __asmb_line_1; }
+__asmb_line_25:;
+	__asmb_int __asmb_reg_g = 0;
+__asmb_line_26:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 30: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_reg_g = __asmb_stack[--__asmb_stack_size];
+__asmb_line_27:;
+	printf("%d\n", __asmb_reg_g);
+__asmb_line_28:;
+	__asmb_int __asmb_reg_n = 0;
+__asmb_line_29:;
+	{ __asmb_args[0] = __asmb_reg_n; __asmb_arg_count = 1; __asmb_callee = 29 + -19; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 34: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 30; goto __asmb_line_10; }
+__asmb_line_30:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 35: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_reg_g = __asmb_stack[--__asmb_stack_size];
+__asmb_line_31:;
+	printf("%d ", __asmb_reg_g);
+__asmb_line_32:;
+	(void) __builtin_add_overflow(__asmb_reg_n, 1, &__asmb_reg_n);
+__asmb_line_33:;
+	if (__asmb_reg_n < 12) goto __asmb_line_29;
+__asmb_line_34:;
+	{ __asmb_int __asmb_c = 10; if (__asmb_c < 0) { fflush(stdout); fprintf(stderr, "Line 39: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { fflush(stdout); fprintf(stderr, "Line 39: Char code (%d) is invalid\n", __asmb_c); return 1; } __asmb_outc((uint32_t) __asmb_c); }
+	goto __asmb_line_35;
+__asmb_jump_table:
+	switch (__asmb_target) {
+	case 1: goto __asmb_line_1;
+	case 2: goto __asmb_line_2;
+	case 3: goto __asmb_line_3;
+	case 4: goto __asmb_line_4;
+	case 5: goto __asmb_line_5;
+	case 6: goto __asmb_line_6;
+	case 7: goto __asmb_line_7;
+	case 8: goto __asmb_line_8;
+	case 9: goto __asmb_line_9;
+	case 10: goto __asmb_line_10;
+	case 11: goto __asmb_line_11;
+	case 12: goto __asmb_line_12;
+	case 13: goto __asmb_line_13;
+	case 14: goto __asmb_line_14;
+	case 15: goto __asmb_line_15;
+	case 16: goto __asmb_line_16;
+	case 17: goto __asmb_line_17;
+	case 18: goto __asmb_line_18;
+	case 19: goto __asmb_line_19;
+	case 20: goto __asmb_line_20;
+	case 21: goto __asmb_line_21;
+	case 22: goto __asmb_line_22;
+	case 23: goto __asmb_line_23;
+	case 24: goto __asmb_line_24;
+	case 25: goto __asmb_line_25;
+	case 26: goto __asmb_line_26;
+	case 27: goto __asmb_line_27;
+	case 28: goto __asmb_line_28;
+	case 29: goto __asmb_line_29;
+	case 30: goto __asmb_line_30;
+	case 31: goto __asmb_line_31;
+	case 32: goto __asmb_line_32;
+	case 33: goto __asmb_line_33;
+	case 34: goto __asmb_line_34;
+	default: goto __asmb_line_35;
+	}
+__asmb_line_35:;
+return 0;
+}
@@ -0,0 +1,69 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+static uint64_t __asmb_seed = 0ULL;
+
+static inline uint64_t __asmb_next(void) {
+	uint64_t z = (__asmb_seed += 0x9E3779B97F4A7C15ULL);
+	z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ULL;
+	z = (z ^ (z >> 27)) * 0x94D049BB133111EBULL;
+	return z ^ (z >> 31);
+}
+
+static inline uint64_t __asmb_random(uint64_t range) {
+	uint64_t draw = __asmb_next();
+	return range == UINT64_MAX ? draw : draw % (range + 1);
+}
+
+int main(void) {
+__asmb_line_1:;
+	__asmb_int __asmb_reg_die = 0;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_i = 0;
+__asmb_line_3:;
+	{ __asmb_int __asmb_low = 1, __asmb_high = 6; if (__asmb_low > __asmb_high) { fflush(stdout); fprintf(stderr, "Line 4: Empty range of RND (%d is greater than %d)\n", __asmb_low, __asmb_high); return 1; } __asmb_reg_die = (__asmb_int) ((__asmb_uint) __asmb_low + (__asmb_uint) __asmb_random((__asmb_uint) __asmb_high - (__asmb_uint) __asmb_low)); }
+__asmb_line_4:;
+	printf("%d ", __asmb_reg_die);
+__asmb_line_5:;
+	(void) __builtin_add_overflow(__asmb_reg_i, 1, &__asmb_reg_i);
+__asmb_line_6:;
+	if (__asmb_reg_i < 10) goto __asmb_line_3;
+__asmb_line_7:;
+	printf("%d\n", __asmb_reg_i);
+__asmb_line_8:;
+	__asmb_seed = (uint64_t) 2016;
+__asmb_line_9:;
+	{ __asmb_int __asmb_low = -1000, __asmb_high = 1000; if (__asmb_low > __asmb_high) { fflush(stdout); fprintf(stderr, "Line 10: Empty range of RND (%d is greater than %d)\n", __asmb_low, __asmb_high); return 1; } __asmb_reg_die = (__asmb_int) ((__asmb_uint) __asmb_low + (__asmb_uint) __asmb_random((__asmb_uint) __asmb_high - (__asmb_uint) __asmb_low)); }
+__asmb_line_10:;
+	printf("%d\n", __asmb_reg_die);
+__asmb_line_11:;
+	uint8_t __asmb_reg_byte = 0;
+__asmb_line_12:;
+	{ uint64_t __asmb_low = 0, __asmb_high = 255; if (__asmb_low > __asmb_high) { fflush(stdout); fprintf(stderr, "Line 13: Empty range of RND (%llu is greater than %llu)\n", (unsigned long long) __asmb_low, (unsigned long long) __asmb_high); return 1; } __asmb_reg_byte = (uint8_t) ((uint64_t) __asmb_low + (uint64_t) __asmb_random((uint64_t) __asmb_high - (uint64_t) __asmb_low)); }
+__asmb_line_13:;
+	printf("%lld\n", (long long) __asmb_reg_byte);
+__asmb_line_14:;
+	int16_t __asmb_reg_small = 0;
+__asmb_line_15:;
+	{ int64_t __asmb_low = -32768, __asmb_high = 32767; if (__asmb_low > __asmb_high) { fflush(stdout); fprintf(stderr, "Line 16: Empty range of RND (%lld is greater than %lld)\n", (long long) __asmb_low, (long long) __asmb_high); return 1; } __asmb_reg_small = (int16_t) ((uint64_t) __asmb_low + (uint64_t) __asmb_random((uint64_t) __asmb_high - (uint64_t) __asmb_low)); }
+__asmb_line_16:;
+	printf("%lld\n", (long long) __asmb_reg_small);
+__asmb_line_17:;
+	__asmb_int __asmb_reg_whole = 0;
+__asmb_line_18:;
+	{ __asmb_int __asmb_low = INT32_MIN, __asmb_high = 2147483647; if (__asmb_low > __asmb_high) { fflush(stdout); fprintf(stderr, "Line 19: Empty range of RND (%d is greater than %d)\n", __asmb_low, __asmb_high); return 1; } __asmb_reg_whole = (__asmb_int) ((__asmb_uint) __asmb_low + (__asmb_uint) __asmb_random((__asmb_uint) __asmb_high - (__asmb_uint) __asmb_low)); }
+__asmb_line_19:;
+	printf("%d\n", __asmb_reg_whole);
+__asmb_line_20:;
+	__asmb_seed = (uint64_t) -1;
+__asmb_line_21:;
+	{ __asmb_int __asmb_low = 7, __asmb_high = 7; if (__asmb_low > __asmb_high) { fflush(stdout); fprintf(stderr, "Line 22: Empty range of RND (%d is greater than %d)\n", __asmb_low, __asmb_high); return 1; } __asmb_reg_die = (__asmb_int) ((__asmb_uint) __asmb_low + (__asmb_uint) __asmb_random((__asmb_uint) __asmb_high - (__asmb_uint) __asmb_low)); }
+__asmb_line_22:;
+	printf("%d\n", __asmb_reg_die);
+__asmb_line_23:;
+return 0;
+}
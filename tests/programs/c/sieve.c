@@ -0,0 +1,46 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+	static __asmb_int __asmb_mem_sieve[50];
+__asmb_line_1:;
+	__asmb_int __asmb_reg_n = 50;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_i = 2;
+__asmb_line_3:;
+	__asmb_int __asmb_reg_j = 0;
+__asmb_line_4:;
+	__asmb_int __asmb_reg_composite = 0;
+__asmb_line_5:;
+	memset(__asmb_mem_sieve, 0, sizeof __asmb_mem_sieve);
+__asmb_line_6:;
+	{ __asmb_int __asmb_index = __asmb_reg_i; if (__asmb_index < 0 || __asmb_index >= (__asmb_int) (sizeof __asmb_mem_sieve / sizeof __asmb_mem_sieve[0])) { fflush(stdout); fprintf(stderr, "Line 8: Index %d is out of bounds for a memory buffer of size %d\n", __asmb_index, (__asmb_int) (sizeof __asmb_mem_sieve / sizeof __asmb_mem_sieve[0])); return 1; } __asmb_reg_composite = __asmb_mem_sieve[__asmb_index]; }
+__asmb_line_7:;
+	if (__asmb_reg_composite != 0) goto __asmb_line_15;
+__asmb_line_8:;
+	printf("%d ", __asmb_reg_i);
+__asmb_line_9:;
+	__asmb_reg_j = __asmb_reg_i;
+__asmb_line_10:;
+	(void) __builtin_mul_overflow(__asmb_reg_j, __asmb_reg_i, &__asmb_reg_j);
+__asmb_line_11:;
+	if (__asmb_reg_j > 49) goto __asmb_line_15;
+__asmb_line_12:;
+	{ __asmb_int __asmb_index = __asmb_reg_j; if (__asmb_index < 0 || __asmb_index >= (__asmb_int) (sizeof __asmb_mem_sieve / sizeof __asmb_mem_sieve[0])) { fflush(stdout); fprintf(stderr, "Line 14: Index %d is out of bounds for a memory buffer of size %d\n", __asmb_index, (__asmb_int) (sizeof __asmb_mem_sieve / sizeof __asmb_mem_sieve[0])); return 1; } __asmb_mem_sieve[__asmb_index] = 1; }
+__asmb_line_13:;
+	(void) __builtin_add_overflow(__asmb_reg_j, __asmb_reg_i, &__asmb_reg_j);
+__asmb_line_14:;
+	if (1 != 0) goto __asmb_line_11;
+__asmb_line_15:;
+	(void) __builtin_add_overflow(__asmb_reg_i, 1, &__asmb_reg_i);
+__asmb_line_16:;
+	if (__asmb_reg_i < __asmb_reg_n) goto __asmb_line_6;
+__asmb_line_17:;
+	printf("%d\n", 0);
+__asmb_line_18:;
+return 0;
+}
@@ -0,0 +1,47 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+	static __asmb_int __asmb_stack[65536];
+	int64_t __asmb_stack_size = 0;
+__asmb_line_1:;
+	__asmb_int __asmb_reg_i = 0;
+__asmb_line_2:;
+	__asmb_int __asmb_reg_a = 1;
+__asmb_line_3:;
+	__asmb_int __asmb_reg_b = 2;
+__asmb_line_4:;
+	(void) __builtin_add_overflow(__asmb_reg_i, 1, &__asmb_reg_i);
+__asmb_line_5:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 7: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = __asmb_reg_i;
+__asmb_line_6:;
+	if (__asmb_reg_i < 5) goto __asmb_line_4;
+__asmb_line_7:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 9: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_reg_i = __asmb_stack[--__asmb_stack_size];
+__asmb_line_8:;
+	printf("%d ", __asmb_reg_i);
+__asmb_line_9:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 11: Stack underflow (PEEK on empty stack)\n", stderr); return 1; } __asmb_reg_a = __asmb_stack[__asmb_stack_size - 1];
+__asmb_line_10:;
+	if (__asmb_reg_a != 1) goto __asmb_line_7;
+__asmb_line_11:;
+	printf("%d\n", 0);
+__asmb_line_12:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 15: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = __asmb_reg_a;
+__asmb_line_13:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 16: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = __asmb_reg_b;
+__asmb_line_14:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 17: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_reg_a = __asmb_stack[--__asmb_stack_size];
+__asmb_line_15:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 18: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_reg_b = __asmb_stack[--__asmb_stack_size];
+__asmb_line_16:;
+	printf("%d ", __asmb_reg_a);
+__asmb_line_17:;
+	printf("%d\n", __asmb_reg_b);
+__asmb_line_18:;
+return 0;
+}
@@ -0,0 +1,27 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+__asmb_line_1:;
+	fwrite("HeLLo WoRlD\n", 1, 12, stdout);
+__asmb_line_2:;
+	__asmb_int __asmb_reg_i = 3;
+__asmb_line_3:;
+	fwrite("Tab:\t\"quoted\" \\ ", 1, 16, stdout);
+__asmb_line_4:;
+	printf("%d\n", __asmb_reg_i);
+__asmb_line_5:;
+	(void) __builtin_sub_overflow(__asmb_reg_i, 1, &__asmb_reg_i);
+__asmb_line_6:;
+	if (__asmb_reg_i != 0) goto __asmb_line_3;
+__asmb_line_7:;
+	fwrite("Unicode: h\303\251llo", 1, 15, stdout);
+__asmb_line_8:;
+	fwrite("\n", 1, 1, stdout);
+__asmb_line_9:;
+return 0;
+}
@@ -0,0 +1,123 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+int main(void) {
+	__asmb_int __asmb_reg_a = 0;
+	int64_t __asmb_ip = 1;
+	uint8_t __asmb_form[10] = {0};
+	while (__asmb_ip >= 1 && __asmb_ip <= 9) {
+		switch (__asmb_ip) {
+		case 1:
+			switch (__asmb_form[1]) {
+			case 0:
+				__asmb_reg_a = 0;
+				break;
+			case 1:
+				if (__asmb_reg_a != 0) { __asmb_ip += 0; if (__asmb_ip < 1) { fflush(stdout); fprintf(stderr, "Line 2: Jump target %lld is before the start of the program\n", (long long) (__asmb_ip - 1)); return 1; } continue; }
+				break;
+			case 2:
+				/* Invalid instruction, skipped */;
+				break;
+			}
+			break;
+		case 2:
+			switch (__asmb_form[2]) {
+			case 0: case 2:
+				__asmb_reg_a = 2;
+				break;
+			case 1:
+				if (2 != 0) { __asmb_ip += __asmb_reg_a; if (__asmb_ip < 1) { fflush(stdout); fprintf(stderr, "Line 4: Jump target %lld is before the start of the program\n", (long long) (__asmb_ip - 1)); return 1; } continue; }
+				break;
+			}
+			break;
+		case 3:
+			switch (__asmb_form[3]) {
+			case 0:
+				{ int64_t __asmb_target = __asmb_ip + __asmb_reg_a; if (__asmb_target >= 1 && __asmb_target <= 9) __asmb_form[__asmb_target] = __asmb_form[__asmb_target] == 1 ? 2 : 1; }
+				break;
+			case 1:
+				(void) __builtin_add_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			case 2:
+				(void) __builtin_sub_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			}
+			break;
+		case 4:
+			switch (__asmb_form[4]) {
+			case 0:
+				{ int64_t __asmb_target = __asmb_ip + __asmb_reg_a; if (__asmb_target >= 1 && __asmb_target <= 9) __asmb_form[__asmb_target] = __asmb_form[__asmb_target] == 1 ? 2 : 1; }
+				break;
+			case 1:
+				(void) __builtin_add_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			case 2:
+				(void) __builtin_sub_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			}
+			break;
+		case 5:
+			switch (__asmb_form[5]) {
+			case 0:
+				{ int64_t __asmb_target = __asmb_ip + __asmb_reg_a; if (__asmb_target >= 1 && __asmb_target <= 9) __asmb_form[__asmb_target] = __asmb_form[__asmb_target] == 1 ? 2 : 1; }
+				break;
+			case 1:
+				(void) __builtin_add_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			case 2:
+				(void) __builtin_sub_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			}
+			break;
+		case 6:
+			switch (__asmb_form[6]) {
+			case 0: case 2:
+				__asmb_reg_a = 1;
+				break;
+			case 1:
+				if (1 != 0) { __asmb_ip += __asmb_reg_a; if (__asmb_ip < 1) { fflush(stdout); fprintf(stderr, "Line 8: Jump target %lld is before the start of the program\n", (long long) (__asmb_ip - 1)); return 1; } continue; }
+				break;
+			}
+			break;
+		case 7:
+			switch (__asmb_form[7]) {
+			case 0: case 2:
+				(void) __builtin_sub_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			case 1:
+				(void) __builtin_add_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			}
+			break;
+		case 8:
+			switch (__asmb_form[8]) {
+			case 0: case 2:
+				(void) __builtin_sub_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			case 1:
+				(void) __builtin_add_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			}
+			break;
+		case 9:
+			switch (__asmb_form[9]) {
+			case 0:
+				printf("%d\n", __asmb_reg_a);
+				break;
+			case 1:
+				(void) __builtin_add_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			case 2:
+				(void) __builtin_sub_overflow(__asmb_reg_a, 1, &__asmb_reg_a);
+				break;
+			}
+			break;
+		}
+		++__asmb_ip;
+	}
+return 0;
+}
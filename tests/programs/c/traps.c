@@ -0,0 +1,124 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+static inline void __asmb_outc(uint32_t code) {
+	if (code < 0x80) {
+		putchar((int) code);
+	} else if (code < 0x800) {
+		putchar((int) (0xC0 | code >> 6));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else if (code < 0x10000) {
+		putchar((int) (0xE0 | code >> 12));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	} else {
+		putchar((int) (0xF0 | code >> 18));
+		putchar((int) (0x80 | (code >> 12 & 0x3F)));
+		putchar((int) (0x80 | (code >> 6 & 0x3F)));
+		putchar((int) (0x80 | (code & 0x3F)));
+	}
+}
+
+int main(void) {
+	int64_t __asmb_calls[4096];
+	int32_t __asmb_call_depth = 0;
+	static __asmb_int __asmb_stack[65536];
+	int64_t __asmb_stack_size = 0;
+	int64_t __asmb_trap_div = 0;
+	int64_t __asmb_trap_outc = 0;
+	int64_t __asmb_trap_stack = 0;
+	int64_t __asmb_trap_mem = 0;
+	static __asmb_int __asmb_mem_buf[4];
+	__asmb_int __asmb_args[8];
+	int __asmb_arg_count = 0;
+	int64_t __asmb_callee = 0;
+	__asmb_int __asmb_local_6_report_code = 0;
+	static __asmb_int __asmb_frames_0_0[4096];
+	__asmb_int __asmb_local_6_report_line = 0;
+	static __asmb_int __asmb_frames_0_1[4096];
+	int32_t __asmb_frame_depth_0 = 0;
+	int64_t __asmb_target = 0;
+__asmb_line_1:;
+	if (__asmb_callee == 1) { __asmb_callee = 0; if (__asmb_arg_count != 2) { fflush(stdout); fprintf(stderr, "Line 3: Procedure expects 2 argument(s), got %lld\n", (long long) __asmb_arg_count); return 1; } if (__asmb_frame_depth_0 == 4096) { fflush(stdout); fputs("Line 3: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_frames_0_0[__asmb_frame_depth_0] = __asmb_local_6_report_code; __asmb_frames_0_1[__asmb_frame_depth_0] = __asmb_local_6_report_line; ++__asmb_frame_depth_0; __asmb_local_6_report_code = __asmb_args[0]; __asmb_local_6_report_line = __asmb_args[1]; } else goto __asmb_line_7;
+__asmb_line_2:;
+	fwrite("error ", 1, 6, stdout);
+__asmb_line_3:;
+	printf("%d ", __asmb_local_6_report_code);
+__asmb_line_4:;
+	fwrite("at line ", 1, 8, stdout);
+__asmb_line_5:;
+	printf("%d\n", __asmb_local_6_report_line);
+__asmb_line_6:;
+	{ if (__asmb_frame_depth_0 == 0) { fflush(stdout); fputs("Line 8: RETURN or ENDP without a call of a procedure\n", stderr); return 1; } --__asmb_frame_depth_0; __asmb_local_6_report_code = __asmb_frames_0_0[__asmb_frame_depth_0]; __asmb_local_6_report_line = __asmb_frames_0_1[__asmb_frame_depth_0]; { if (__asmb_call_depth == 0) { fflush(stdout); fputs("Line 8: Call stack underflow (RET without CALL)\n", stderr); return 1; } __asmb_target = __asmb_calls[--__asmb_call_depth]; goto __asmb_jump_table; } }
+__asmb_line_7:;
+	__asmb_trap_div = 1;
+__asmb_line_8:;
+	__asmb_trap_outc = 1;
+__asmb_line_9:;
+	__asmb_trap_stack = 1;
+__asmb_line_10:;
+	__asmb_trap_mem = 1;
+__asmb_line_11:;
+	__asmb_int __asmb_reg_n = 42;
+__asmb_line_12:;
+	{ __asmb_int __asmb_a = __asmb_reg_n, __asmb_b = 0; if (__asmb_b == 0) { if (__asmb_trap_div != 0) { __asmb_args[0] = 1; __asmb_args[1] = 16; __asmb_arg_count = 2; __asmb_callee = __asmb_trap_div; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 16: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 13; __asmb_target = __asmb_trap_div; goto __asmb_jump_table; } { fflush(stdout); fputs("Line 16: Division by zero\n", stderr); return 1; } } if (__asmb_b == -1 && __asmb_a == INT32_MIN) __asmb_reg_n = __asmb_a; else __asmb_reg_n = __asmb_a / __asmb_b; }
+__asmb_line_13:;
+	printf("%d\n", __asmb_reg_n);
+__asmb_line_14:;
+	{ __asmb_int __asmb_c = -5; if (__asmb_c < 0) { if (__asmb_trap_outc != 0) { __asmb_args[0] = 2; __asmb_args[1] = 18; __asmb_arg_count = 2; __asmb_callee = __asmb_trap_outc; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 18: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 15; __asmb_target = __asmb_trap_outc; goto __asmb_jump_table; } { fflush(stdout); fprintf(stderr, "Line 18: Char code (%d) should not be less than zero\n", __asmb_c); return 1; } } if (__asmb_c > 0x10FFFF || (__asmb_c >= 0xD800 && __asmb_c <= 0xDFFF)) { if (__asmb_trap_outc != 0) { __asmb_args[0] = 2; __asmb_args[1] = 18; __asmb_arg_count = 2; __asmb_callee = __asmb_trap_outc; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 18: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 15; __asmb_target = __asmb_trap_outc; goto __asmb_jump_table; } { fflush(stdout); fprintf(stderr, "Line 18: Char code (%d) is invalid\n", __asmb_c); return 1; } } __asmb_outc((uint32_t) __asmb_c); }
+__asmb_line_15:;
+	__asmb_int __asmb_reg_top = 7;
+__asmb_line_16:;
+	if (__asmb_stack_size == 0) { if (__asmb_trap_stack != 0) { __asmb_args[0] = 3; __asmb_args[1] = 20; __asmb_arg_count = 2; __asmb_callee = __asmb_trap_stack; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 20: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 17; __asmb_target = __asmb_trap_stack; goto __asmb_jump_table; } { fflush(stdout); fputs("Line 20: Stack underflow (POP on empty stack)\n", stderr); return 1; } } __asmb_reg_top = __asmb_stack[--__asmb_stack_size];
+__asmb_line_17:;
+	printf("%d\n", __asmb_reg_top);
+__asmb_line_18:;
+	memset(__asmb_mem_buf, 0, sizeof __asmb_mem_buf);
+__asmb_line_19:;
+	{ __asmb_int __asmb_index = 4; if (__asmb_index < 0 || __asmb_index >= (__asmb_int) (sizeof __asmb_mem_buf / sizeof __asmb_mem_buf[0])) { if (__asmb_trap_mem != 0) { __asmb_args[0] = 4; __asmb_args[1] = 23; __asmb_arg_count = 2; __asmb_callee = __asmb_trap_mem; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 23: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 20; __asmb_target = __asmb_trap_mem; goto __asmb_jump_table; } { fflush(stdout); fprintf(stderr, "Line 23: Index %d is out of bounds for a memory buffer of size %d\n", __asmb_index, (__asmb_int) (sizeof __asmb_mem_buf / sizeof __asmb_mem_buf[0])); return 1; } } __asmb_mem_buf[__asmb_index] = 1; }
+__asmb_line_20:;
+	{ __asmb_int __asmb_index = -1; if (__asmb_index < 0 || __asmb_index >= (__asmb_int) (sizeof __asmb_mem_buf / sizeof __asmb_mem_buf[0])) { if (__asmb_trap_mem != 0) { __asmb_args[0] = 4; __asmb_args[1] = 24; __asmb_arg_count = 2; __asmb_callee = __asmb_trap_mem; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 24: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 21; __asmb_target = __asmb_trap_mem; goto __asmb_jump_table; } { fflush(stdout); fprintf(stderr, "Line 24: Index %d is out of bounds for a memory buffer of size %d\n", __asmb_index, (__asmb_int) (sizeof __asmb_mem_buf / sizeof __asmb_mem_buf[0])); return 1; } } __asmb_reg_top = __asmb_mem_buf[__asmb_index]; }
+__asmb_line_21:;
+	printf("%d\n", __asmb_reg_top);
+__asmb_line_22:;
+	__asmb_trap_div = 0;
+__asmb_line_23:;
+	{ __asmb_int __asmb_a = __asmb_reg_n, __asmb_b = 2; if (__asmb_b == 0) { if (__asmb_trap_div != 0) { __asmb_args[0] = 1; __asmb_args[1] = 29; __asmb_arg_count = 2; __asmb_callee = __asmb_trap_div; if (__asmb_call_depth == 4096) { fflush(stdout); fputs("Line 29: Call stack overflow (more than 4096 nested calls)\n", stderr); return 1; } __asmb_calls[__asmb_call_depth++] = 24; __asmb_target = __asmb_trap_div; goto __asmb_jump_table; } { fflush(stdout); fputs("Line 29: Division by zero\n", stderr); return 1; } } if (__asmb_b == -1 && __asmb_a == INT32_MIN) __asmb_reg_n = __asmb_a; else __asmb_reg_n = __asmb_a / __asmb_b; }
+__asmb_line_24:;
+	printf("%d\n", __asmb_reg_n);
+	goto __asmb_line_25;
+__asmb_jump_table:
+	switch (__asmb_target) {
+	case 1: goto __asmb_line_1;
+	case 2: goto __asmb_line_2;
+	case 3: goto __asmb_line_3;
+	case 4: goto __asmb_line_4;
+	case 5: goto __asmb_line_5;
+	case 6: goto __asmb_line_6;
+	case 7: goto __asmb_line_7;
+	case 8: goto __asmb_line_8;
+	case 9: goto __asmb_line_9;
+	case 10: goto __asmb_line_10;
+	case 11: goto __asmb_line_11;
+	case 12: goto __asmb_line_12;
+	case 13: goto __asmb_line_13;
+	case 14: goto __asmb_line_14;
+	case 15: goto __asmb_line_15;
+	case 16: goto __asmb_line_16;
+	case 17: goto __asmb_line_17;
+	case 18: goto __asmb_line_18;
+	case 19: goto __asmb_line_19;
+	case 20: goto __asmb_line_20;
+	case 21: goto __asmb_line_21;
+	case 22: goto __asmb_line_22;
+	case 23: goto __asmb_line_23;
+	case 24: goto __asmb_line_24;
+	default: goto __asmb_line_25;
+	}
+__asmb_line_25:;
+return 0;
+}
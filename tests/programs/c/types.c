@@ -0,0 +1,106 @@
+#include <stdio.h>
+#include <stdint.h>
+#include <string.h>
+
+typedef int32_t __asmb_int;
+typedef uint32_t __asmb_uint;
+
+static inline int __asmb_read(__asmb_int *out) {
+	int ch, negative = 0, digits = 0;
+	__asmb_int val = 0;
+	fflush(stdout);
+	do ch = getchar(); while (ch == ' ' || (ch >= '\t' && ch <= '\r'));
+	if (ch == '-' || ch == '+') {
+		negative = ch == '-';
+		ch = getchar();
+	}
+	for (; ch >= '0' && ch <= '9'; ch = getchar(), ++digits) {
+		if (__builtin_mul_overflow(val, 10, &val) || (negative ? __builtin_sub_overflow(val, ch - '0', &val) : __builtin_add_overflow(val, ch - '0', &val))) return 0;
+	}
+	if (ch != EOF) ungetc(ch, stdin);
+	*out = val;
+	return digits > 0;
+}
+
+int main(void) {
+	static __asmb_int __asmb_stack[65536];
+	int64_t __asmb_stack_size = 0;
+__asmb_line_1:;
+	uint8_t __asmb_reg_counter = 250;
+__asmb_line_2:;
+	__asmb_reg_counter = (uint8_t) ((uint64_t) __asmb_reg_counter + (uint64_t) 10);
+__asmb_line_3:;
+	printf("%lld\n", (long long) __asmb_reg_counter);
+__asmb_line_4:;
+	int8_t __asmb_reg_small = 127;
+__asmb_line_5:;
+	__asmb_reg_small = (int8_t) ((uint64_t) __asmb_reg_small + (uint64_t) 1);
+__asmb_line_6:;
+	printf("%lld\n", (long long) __asmb_reg_small);
+__asmb_line_7:;
+	__asmb_reg_small = (int8_t) ((uint64_t) 0 - (uint64_t) __asmb_reg_small);
+__asmb_line_8:;
+	printf("%lld\n", (long long) __asmb_reg_small);
+__asmb_line_9:;
+	_Bool __asmb_reg_flag = 0;
+__asmb_line_10:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 11: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = 7;
+__asmb_line_11:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 12: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_reg_flag = __asmb_stack[--__asmb_stack_size];
+__asmb_line_12:;
+	printf("%lld\n", (long long) __asmb_reg_flag);
+__asmb_line_13:;
+	uint64_t __asmb_reg_big = 0;
+__asmb_line_14:;
+	__asmb_reg_big = (uint64_t) ((uint64_t) __asmb_reg_big - (uint64_t) 1);
+__asmb_line_15:;
+	printf("%llu\n", (unsigned long long) __asmb_reg_big);
+__asmb_line_16:;
+	__asmb_reg_big >>= 60 & 63;
+__asmb_line_17:;
+	printf("%llu\n", (unsigned long long) __asmb_reg_big);
+__asmb_line_18:;
+	int16_t __asmb_reg_half = -7;
+__asmb_line_19:;
+	{ int64_t __asmb_a = __asmb_reg_half, __asmb_b = 2; if (__asmb_b == 0) { fflush(stdout); fputs("Line 20: Division by zero\n", stderr); return 1; } __asmb_reg_half = (int16_t) (__asmb_b == -1 ? 0 - (uint64_t) __asmb_a : (uint64_t) (__asmb_a / __asmb_b)); }
+__asmb_line_20:;
+	printf("%lld\n", (long long) __asmb_reg_half);
+__asmb_line_21:;
+	{ int64_t __asmb_divisor = -1; if (__asmb_divisor == 0) { fflush(stdout); fputs("Line 22: Modulo by zero\n", stderr); return 1; } __asmb_reg_half = __asmb_divisor == -1 ? 0 : __asmb_reg_half % __asmb_divisor; }
+__asmb_line_22:;
+	printf("%lld\n", (long long) __asmb_reg_half);
+__asmb_line_23:;
+	int64_t __asmb_reg_wide = 1;
+__asmb_line_24:;
+	__asmb_reg_wide = (int64_t) ((uint64_t) __asmb_reg_wide << (63 & 63));
+__asmb_line_25:;
+	printf("%lld\n", (long long) __asmb_reg_wide);
+__asmb_line_26:;
+	{ __asmb_int __asmb_amount = 62 & 63; __asmb_reg_wide = __asmb_reg_wide < 0 ? ~(~__asmb_reg_wide >> __asmb_amount) : __asmb_reg_wide >> __asmb_amount; }
+__asmb_line_27:;
+	printf("%lld\n", (long long) __asmb_reg_wide);
+__asmb_line_28:;
+	if (__asmb_stack_size == 65536) { fflush(stdout); fputs("Line 29: Stack overflow (more than 65536 values)\n", stderr); return 1; } __asmb_stack[__asmb_stack_size++] = 300;
+__asmb_line_29:;
+	if (__asmb_stack_size == 0) { fflush(stdout); fputs("Line 30: Stack underflow (POP on empty stack)\n", stderr); return 1; } __asmb_reg_counter = __asmb_stack[--__asmb_stack_size];
+__asmb_line_30:;
+	printf("%lld\n", (long long) __asmb_reg_counter);
+__asmb_line_31:;
+	__asmb_int __asmb_reg_n = 0;
+__asmb_line_32:;
+	if (!__asmb_read(&__asmb_reg_n)) { fflush(stdout); fputs("Line 33: Expected an integer from STDIN\n", stderr); return 1; }
+__asmb_line_33:;
+	__asmb_reg_n = __asmb_reg_counter;
+__asmb_line_34:;
+	__asmb_reg_wide = (int64_t) ((uint64_t) __asmb_reg_wide + (uint64_t) __asmb_reg_counter);
+__asmb_line_35:;
+	printf("%lld\n", (long long) __asmb_reg_wide);
+__asmb_line_36:;
+	if (__asmb_reg_counter < __asmb_reg_half) goto __asmb_line_38;
+__asmb_line_37:;
+	fwrite("counter is not below half\n", 1, 26, stdout);
+__asmb_line_38:;
+	printf("%lld\n", (long long) __asmb_reg_counter);
+__asmb_line_39:;
+return 0;
+}